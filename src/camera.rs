@@ -0,0 +1,205 @@
+//! Camera views used for culling and automatically spawning chunks.
+//!
+//! Every 2D camera, which is a camera named `Camera2d` with an
+//! [`OrthographicProjection`] such as the one of a `Camera2dBundle`, can see
+//! the tilemap, while UI cameras are left out. The rectangle it sees in world
+//! space is calculated from its projection and its global transform. Chunks
+//! which do not overlap any of those rectangles are hidden.
+//!
+//! With multiple cameras, such as with split-screen, the union of all their
+//! views is used. If only some cameras should be considered, add the
+//! [`TilemapCamera`] marker component to them.
+//!
+//! [`OrthographicProjection`]: ::bevy_render::camera::OrthographicProjection
+
+use crate::lib::*;
+
+/// An axis aligned rectangle in world space.
+///
+/// # Examples
+/// ```
+/// use bevy_math::Vec2;
+/// use bevy_tilemap::camera::WorldRect;
+///
+/// let a = WorldRect::new(Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0));
+/// let b = WorldRect::new(Vec2::new(5.0, 5.0), Vec2::new(15.0, 15.0));
+/// let c = WorldRect::new(Vec2::new(20.0, 20.0), Vec2::new(30.0, 30.0));
+///
+/// assert!(a.intersects(&b));
+/// assert!(!a.intersects(&c));
+/// assert_eq!(a.width(), 10.0);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct WorldRect {
+    /// The bottom left corner of the rectangle.
+    pub min: Vec2,
+    /// The top right corner of the rectangle.
+    pub max: Vec2,
+}
+
+impl WorldRect {
+    /// Constructs a new rectangle from its minimum and maximum corners.
+    pub fn new(min: Vec2, max: Vec2) -> WorldRect {
+        WorldRect { min, max }
+    }
+
+    /// Constructs a new rectangle around a center with half extents.
+    pub fn from_center(center: Vec2, half_extents: Vec2) -> WorldRect {
+        WorldRect {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    /// The width of the rectangle.
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    /// The height of the rectangle.
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    /// The center of the rectangle.
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Returns `true` if a point is within the rectangle.
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+
+    /// Returns `true` if the rectangles overlap each other.
+    pub fn intersects(&self, other: &WorldRect) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+    }
+
+    /// Returns the smallest rectangle which contains both rectangles.
+    pub fn union(&self, other: &WorldRect) -> WorldRect {
+        WorldRect {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Returns the rectangle grown by an amount on every side.
+    pub fn expand(&self, amount: Vec2) -> WorldRect {
+        WorldRect {
+            min: self.min - amount,
+            max: self.max + amount,
+        }
+    }
+
+    /// Returns the rectangle moved into the space of a global transform.
+    ///
+    /// Rotation is ignored as tilemaps are axis aligned.
+    pub(crate) fn transformed(&self, transform: &GlobalTransform) -> WorldRect {
+        let translation = transform.translation.truncate();
+        let scale = transform.scale.truncate();
+        let a = translation + self.min * scale;
+        let b = translation + self.max * scale;
+        WorldRect {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// Returns the rectangle moved out of the space of a global transform.
+    pub(crate) fn inverse_transformed(&self, transform: &GlobalTransform) -> WorldRect {
+        let translation = transform.translation.truncate();
+        let scale = transform.scale.truncate();
+        let a = (self.min - translation) / scale;
+        let b = (self.max - translation) / scale;
+        WorldRect {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+}
+
+/// A marker component for cameras that tilemaps are spawned and culled for.
+///
+/// By default every 2D camera is used. Once any camera has this component,
+/// only the cameras with it are used. This is useful to leave out cameras that
+/// should not stream in chunks, such as an overview camera.
+///
+/// # Examples
+/// ```no_run
+/// use bevy_ecs::prelude::*;
+/// use bevy_render::entity::Camera2dBundle;
+/// use bevy_tilemap::prelude::*;
+///
+/// fn setup(commands: &mut Commands) {
+///     // Player one.
+///     commands.spawn(Camera2dBundle::default()).with(TilemapCamera);
+///     // Player two.
+///     commands.spawn(Camera2dBundle::default()).with(TilemapCamera);
+/// }
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct TilemapCamera;
+
+/// Statistics of the chunk layers that were last checked for visibility.
+///
+/// This is available as a resource once the [`Tilemap2DPlugin`] has been
+/// added and is updated every frame.
+///
+/// [`Tilemap2DPlugin`]: crate::Tilemap2DPlugin
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct ChunkVisibilityStats {
+    /// The amount of chunk layers that are visible and drawn.
+    pub visible: usize,
+    /// The amount of chunk layers that were hidden as no camera can see them,
+    /// or as they are drawn with a level of detail sprite instead.
+    pub culled: usize,
+}
+
+impl ChunkVisibilityStats {
+    /// The total amount of spawned chunk layers.
+    pub fn total(&self) -> usize {
+        self.visible + self.culled
+    }
+}
+
+/// Returns the rectangle a 2D camera sees in world space.
+pub(crate) fn camera_view_rect(
+    projection: &OrthographicProjection,
+    transform: &GlobalTransform,
+) -> WorldRect {
+    WorldRect::new(
+        Vec2::new(projection.left, projection.bottom),
+        Vec2::new(projection.right, projection.top),
+    )
+    .transformed(transform)
+}
+
+/// Filters values of cameras, such as their views, down to the ones of
+/// cameras that have a [`TilemapCamera`], if any camera has one, or else to
+/// the ones of the 2D cameras.
+pub(crate) fn select_views<'a, T, I>(views: I) -> Vec<T>
+where
+    I: IntoIterator<Item = (T, &'a Camera, bool)>,
+{
+    let views: Vec<(T, &Camera, bool)> = views.into_iter().collect();
+    let any_marked = views.iter().any(|(_view, _camera, marked)| *marked);
+    views
+        .into_iter()
+        .filter(|(_view, camera, marked)| {
+            if any_marked {
+                *marked
+            } else {
+                camera.name.as_deref() == Some(base_camera::CAMERA_2D)
+            }
+        })
+        .map(|(view, _camera, _marked)| view)
+        .collect()
+}
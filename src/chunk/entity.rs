@@ -7,6 +7,46 @@ pub(crate) struct ModifiedLayer(pub usize);
 /// The Z Order of a layer in a chunk.
 pub(crate) struct ZOrder(pub usize);
 
+/// The names of the render resources of a chunk layer, in order.
+const CHUNK_LAYER_RESOURCE_NAMES: [&str; 1] = ["ChunkLayer_color"];
+
+/// A component with the uniforms of a chunk layer which are shared by every
+/// chunk of the layer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ChunkLayerUniform {
+    /// The tint and opacity of the layer, multiplied into every tile.
+    pub color: Color,
+}
+
+impl Default for ChunkLayerUniform {
+    fn default() -> Self {
+        ChunkLayerUniform {
+            color: Color::WHITE,
+        }
+    }
+}
+
+impl RenderResources for ChunkLayerUniform {
+    fn render_resources_len(&self) -> usize {
+        CHUNK_LAYER_RESOURCE_NAMES.len()
+    }
+
+    fn get_render_resource(&self, index: usize) -> Option<&dyn RenderResource> {
+        match index {
+            0 => Some(&self.color),
+            _ => None,
+        }
+    }
+
+    fn get_render_resource_name(&self, index: usize) -> Option<&str> {
+        CHUNK_LAYER_RESOURCE_NAMES.get(index).copied()
+    }
+
+    fn iter(&self) -> RenderResourceIterator {
+        RenderResourceIterator::new(self)
+    }
+}
+
 /// A component bundle for `Chunk` entities.
 #[derive(Bundle)]
 pub(crate) struct ChunkBundle {
@@ -33,4 +73,38 @@ pub(crate) struct ChunkBundle {
     pub global_transform: GlobalTransform,
     /// If a layer has been modified, all are set here.
     pub modified_layer: ModifiedLayer,
+    /// The uniforms of the layer.
+    pub layer_uniform: ChunkLayerUniform,
+}
+
+/// A component bundle for the layers of a chunk that are drawn again past the
+/// seam of a wrapping tilemap.
+///
+/// It shares the mesh of the chunk layer it mirrors, so it is updated along
+/// with it and has no [`ModifiedLayer`] of its own.
+#[derive(Bundle)]
+pub(crate) struct ChunkMirrorBundle {
+    /// The point of the chunk past the seam.
+    pub point: Point2,
+    /// The z order of the layer.
+    pub z_order: ZOrder,
+    /// The handle for a TextureAtlas which contains multiple textures.
+    pub texture_atlas: Handle<TextureAtlas>,
+    /// A component that indicates how to draw a component.
+    pub draw: Draw,
+    /// A component that indicates if the component is visible.
+    pub visible: Visible,
+    /// The pipeline for the renderer.
+    pub render_pipelines: RenderPipelines,
+    /// A component that indicates that an entity should be drawn in the
+    /// "main pass"
+    pub main_pass: MainPass,
+    /// The mesh of the mirrored chunk layer.
+    pub mesh: Handle<Mesh>,
+    /// The transform location in a space for a component.
+    pub transform: Transform,
+    /// The global transform location in a space for a component.
+    pub global_transform: GlobalTransform,
+    /// The uniforms of the layer.
+    pub layer_uniform: ChunkLayerUniform,
 }
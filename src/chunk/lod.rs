@@ -0,0 +1,154 @@
+use crate::{
+    camera::{select_views, TilemapCamera, WorldRect},
+    chunk::{
+        raster::{is_supported_texture, Canvas},
+        Chunk,
+    },
+    lib::*,
+    tilemap::{ErrorKind, TilemapResult},
+    Tilemap,
+};
+
+/// A marker component for the level of detail sprite of a chunk.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub(crate) struct ChunkLod;
+
+/// Bakes every layer of a chunk into a single downsampled texture.
+///
+/// Returns an error if the atlas texture is not RGBA with 8 bits per channel
+/// or if the texture would be too large.
+pub(crate) fn bake_chunk(
+    tilemap: &Tilemap,
+    chunk: &Chunk,
+    atlas: &TextureAtlas,
+    texture: &Texture,
+) -> TilemapResult<Texture> {
+    if !is_supported_texture(texture) {
+        return Err(ErrorKind::UnsupportedTextureFormat.into());
+    }
+    let point = chunk.point();
+    let translation = tilemap.chunk_translation(point);
+    let bounds = tilemap.chunk_bounds(point);
+    let local_bounds = WorldRect::new(bounds.min - translation, bounds.max - translation);
+    let mut canvas = Canvas::new(local_bounds, 1.0 / tilemap.lod_downsample() as f32)?;
+    for z_order in 0..tilemap.layers().len() {
+        canvas.draw_chunk_layer(tilemap, chunk, z_order, Vec2::zero(), atlas, texture);
+    }
+    Ok(canvas.into_texture())
+}
+
+/// Switches chunks between their tiles and level of detail sprites based on
+/// how far the cameras are zoomed out, baking the textures that are needed.
+///
+/// The zoom of the closest camera is used so that chunks are never blurry in
+/// any view. Textures are baked again after the tiles of a chunk change.
+pub(crate) fn chunk_lod(
+    commands: &mut Commands,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    camera_query: Query<(&Camera, &GlobalTransform, Option<&TilemapCamera>)>,
+    mut tilemap_query: Query<(Entity, &mut Tilemap, &GlobalTransform)>,
+) {
+    let zooms =
+        select_views(camera_query.iter().map(|(camera, transform, marker)| {
+            (transform.scale.x.abs(), camera, marker.is_some())
+        }));
+    let closest_zoom = zooms.into_iter().fold(None, |closest: Option<f32>, zoom| {
+        Some(closest.map_or(zoom, |closest| closest.min(zoom)))
+    });
+
+    for (map_entity, mut tilemap, tilemap_transform) in tilemap_query.iter_mut() {
+        let threshold = if let Some(threshold) = tilemap.lod_threshold() {
+            threshold
+        } else {
+            tilemap.set_lod_active(false);
+            continue;
+        };
+        let tilemap_scale = tilemap_transform.scale.x.abs();
+        let is_active = closest_zoom.map_or(false, |zoom| zoom / tilemap_scale >= threshold);
+        if tilemap.is_lod_active() != is_active {
+            tilemap.set_lod_active(is_active);
+        }
+        if !is_active {
+            continue;
+        }
+
+        let mut points: Vec<Point2> = tilemap
+            .spawned_chunks()
+            .iter()
+            .map(|point| Point2::from(*point))
+            .filter(|point| {
+                tilemap.get_chunk(point).map_or(false, |chunk| {
+                    chunk.lod_entity().is_none() || chunk.is_lod_dirty()
+                })
+            })
+            .collect();
+        if points.is_empty() {
+            continue;
+        }
+        if let Some(budget) = tilemap.lod_bake_budget() {
+            points.truncate(budget.max(1));
+        }
+
+        let atlas = if let Some(atlas) = texture_atlases.get(tilemap.texture_atlas()) {
+            atlas
+        } else {
+            continue;
+        };
+        let mut baked = Vec::with_capacity(points.len());
+        if let Some(atlas_texture) = textures.get(&atlas.texture) {
+            for point in points {
+                let chunk = if let Some(chunk) = tilemap.get_chunk(&point) {
+                    chunk
+                } else {
+                    continue;
+                };
+                match bake_chunk(&tilemap, chunk, atlas, atlas_texture) {
+                    Ok(texture) => baked.push((point, texture)),
+                    Err(err) => {
+                        warn!("can not bake level of detail textures: {}", err);
+                        break;
+                    }
+                }
+            }
+        } else {
+            continue;
+        }
+
+        for (point, texture) in baked {
+            let bounds = tilemap.chunk_bounds(point);
+            let chunk = if let Some(chunk) = tilemap.chunks_mut().get_mut(&point) {
+                chunk
+            } else {
+                continue;
+            };
+            if let Some(handle) = chunk.lod_texture() {
+                if let Some(lod_texture) = textures.get_mut(handle) {
+                    *lod_texture = texture;
+                }
+                chunk.clean_lod();
+                continue;
+            }
+            let texture_handle = textures.add(texture);
+            let material = materials.add(ColorMaterial::texture(texture_handle.clone()));
+            let entity = commands
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        size: Vec2::new(bounds.width(), bounds.height()),
+                        resize_mode: SpriteResizeMode::Manual,
+                    },
+                    material,
+                    transform: Transform::from_translation(bounds.center().extend(0.0)),
+                    ..Default::default()
+                })
+                .with(point)
+                .with(ChunkLod)
+                .current_entity();
+            if let Some(entity) = entity {
+                commands.push_children(map_entity, &[entity]);
+                chunk.set_lod(entity, texture_handle);
+            }
+        }
+    }
+}
@@ -12,6 +12,10 @@ impl ChunkMesh {
     pub(crate) const ATTRIBUTE_TILE_INDEX: &'static str = "Vertex_Tile_Index";
     /// Vertex attribute of the tile's color.
     pub(crate) const ATTRIBUTE_TILE_COLOR: &'static str = "Vertex_Tile_Color";
+    /// Vertex attribute of the light that falls on the tile.
+    pub(crate) const ATTRIBUTE_TILE_LIGHT: &'static str = "Vertex_Tile_Light";
+    /// Vertex attribute of the tile's palette.
+    pub(crate) const ATTRIBUTE_TILE_PALETTE: &'static str = "Vertex_Tile_Palette";
 
     /// Constructs a new chunk mesh.
     pub(crate) fn new(dimensions: Dimension2) -> ChunkMesh {
@@ -50,12 +54,16 @@ impl From<&ChunkMesh> for Mesh {
 
         let tile_indexes = vec![0.; vertices.len()];
         let tile_colors: Vec<[f32; 4]> = vec![Color::WHITE.into(); vertices.len()];
+        let tile_lights: Vec<[f32; 4]> = vec![Color::WHITE.into(); vertices.len()];
+        let tile_palettes = vec![0.; vertices.len()];
 
         let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
         mesh.set_indices(Some(indices));
         mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
         mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_INDEX, tile_indexes);
         mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_COLOR, tile_colors);
+        mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_LIGHT, tile_lights);
+        mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_PALETTE, tile_palettes);
 
         mesh
     }
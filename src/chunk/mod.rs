@@ -59,8 +59,12 @@
 pub(crate) mod entity;
 /// Sparse and dense chunk layers.
 mod layer;
+/// Level of detail sprites for zoomed out chunks.
+pub(crate) mod lod;
 /// Meshes for rendering to vertices.
 pub(crate) mod mesh;
+/// Drawing tiles onto images on the CPU.
+pub(crate) mod raster;
 /// Raw tile that is stored in the chunks.
 pub mod raw_tile;
 /// Files and helpers for rendering.
@@ -86,6 +90,18 @@ pub(crate) struct Chunk {
     /// Contains a map of all collision entities.
     #[cfg(feature = "bevy_rapier2d")]
     pub collision_entities: HashMap<usize, Entity>,
+    /// The entity of the level of detail sprite, if spawned.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lod_entity: Option<Entity>,
+    /// The baked level of detail texture, if any.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lod_texture: Option<Handle<Texture>>,
+    /// If the tiles changed since the level of detail texture was baked.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lod_dirty: bool,
+    /// The light that falls on each tile, if the tilemap is lit.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lights: Option<Vec<[f32; 4]>>,
 }
 
 impl Chunk {
@@ -101,6 +117,10 @@ impl Chunk {
             user_data: 0,
             #[cfg(feature = "bevy_rapier2d")]
             collision_entities: HashMap::default(),
+            lod_entity: None,
+            lod_texture: None,
+            lod_dirty: false,
+            lights: None,
         };
         for (z_order, kind) in layers.iter().enumerate() {
             if let Some(kind) = kind {
@@ -113,12 +133,14 @@ impl Chunk {
     /// Adds a layer from a layer kind, the z layer, and dimensions of the
     /// chunk.
     pub(crate) fn add_layer(&mut self, kind: &LayerKind, z_order: usize, dimensions: Dimension2) {
+        self.lod_dirty = true;
         match kind {
             LayerKind::Dense => {
                 let tiles = vec![
                     RawTile {
                         index: 0,
-                        color: Color::rgba(0.0, 0.0, 0.0, 0.0)
+                        color: Color::rgba(0.0, 0.0, 0.0, 0.0),
+                        palette: 0,
                     };
                     dimensions.area() as usize
                 ];
@@ -171,11 +193,13 @@ impl Chunk {
         }
 
         self.sprite_layers.swap(from_z, to_z);
+        self.lod_dirty = true;
     }
 
     /// Removes a layer from the specified layer.
     pub(crate) fn remove_layer(&mut self, z_order: usize) {
         self.sprite_layers.get_mut(z_order).take();
+        self.lod_dirty = true;
     }
 
     /// Sets the mesh for the chunk layer to use.
@@ -198,8 +222,10 @@ impl Chunk {
                 let raw_tile = RawTile {
                     index: tile.sprite_index,
                     color: tile.tint,
+                    palette: tile.palette,
                 };
                 layer.inner.as_mut().set_tile(index, raw_tile);
+                self.lod_dirty = true;
             } else {
                 error!("can not set tile to sprite layer {}", tile.z_order);
             }
@@ -213,6 +239,7 @@ impl Chunk {
         if let Some(layer) = self.sprite_layers.get_mut(z_order) {
             if let Some(layer) = layer.as_mut() {
                 layer.inner.as_mut().remove_tile(index);
+                self.lod_dirty = true;
             } else {
                 error!("can not remove tile on sprite layer {}", z_order);
             }
@@ -267,9 +294,51 @@ impl Chunk {
                 }
             }
         }
+        if let Some(entity) = self.lod_entity {
+            entities.push(entity);
+        }
         entities
     }
 
+    /// Returns the entity of the level of detail sprite, if spawned.
+    pub(crate) fn lod_entity(&self) -> Option<Entity> {
+        self.lod_entity
+    }
+
+    /// Returns the handle of the baked level of detail texture, if any.
+    pub(crate) fn lod_texture(&self) -> Option<&Handle<Texture>> {
+        self.lod_texture.as_ref()
+    }
+
+    /// Sets the level of detail sprite entity and its texture once spawned.
+    pub(crate) fn set_lod(&mut self, entity: Entity, texture: Handle<Texture>) {
+        self.lod_entity = Some(entity);
+        self.lod_texture = Some(texture);
+        self.lod_dirty = false;
+    }
+
+    /// Returns `true` if the level of detail texture needs to be baked again.
+    pub(crate) fn is_lod_dirty(&self) -> bool {
+        self.lod_dirty
+    }
+
+    /// Marks the level of detail texture as baked.
+    pub(crate) fn clean_lod(&mut self) {
+        self.lod_dirty = false;
+    }
+
+    /// Marks the level of detail texture to be baked again.
+    pub(crate) fn mark_lod_dirty(&mut self) {
+        self.lod_dirty = true;
+    }
+
+    /// Forgets the level of detail sprite, always when it is despawned.
+    pub(crate) fn clear_lod(&mut self) {
+        self.lod_entity = None;
+        self.lod_texture = None;
+        self.lod_dirty = false;
+    }
+
     /// Gets a reference to a tile from a provided z order and index.
     pub(crate) fn get_tile(&self, z_order: usize, index: usize) -> Option<&RawTile> {
         self.sprite_layers.get(z_order).and_then(|layer| {
@@ -281,6 +350,7 @@ impl Chunk {
 
     /// Gets a mutable reference to a tile from a provided z order and index.
     pub(crate) fn get_tile_mut(&mut self, z_order: usize, index: usize) -> Option<&mut RawTile> {
+        self.lod_dirty = true;
         self.sprite_layers.get_mut(z_order).and_then(|layer| {
             layer
                 .as_mut()
@@ -298,6 +368,36 @@ impl Chunk {
         })
     }
 
+    /// Returns the light that falls on a tile by its index, if the tilemap is
+    /// lit.
+    pub(crate) fn get_light(&self, index: usize) -> Option<[f32; 4]> {
+        self.lights
+            .as_ref()
+            .and_then(|lights| lights.get(index).cloned())
+    }
+
+    /// Sets the light that falls on each tile, returning `true` if it changed.
+    pub(crate) fn set_lights(&mut self, lights: Option<Vec<[f32; 4]>>) -> bool {
+        if self.lights == lights {
+            return false;
+        }
+        self.lights = lights;
+        true
+    }
+
+    /// Changes the light of each tile into an attribute for use with the
+    /// renderer. Tiles are fully lit if the tilemap is not lit.
+    pub(crate) fn lights_to_attributes(&self, dimensions: Dimension2) -> Vec<[f32; 4]> {
+        let area = dimensions.area() as usize;
+        match &self.lights {
+            Some(lights) => lights
+                .iter()
+                .flat_map(|light| ::std::iter::repeat(*light).take(4))
+                .collect(),
+            None => vec![Color::WHITE.into(); area * 4],
+        }
+    }
+
     /// At the given z layer, changes the tiles into attributes for use with
     /// the renderer using the given dimensions.
     ///
@@ -306,7 +406,7 @@ impl Chunk {
         &self,
         z: usize,
         dimensions: Dimension2,
-    ) -> Option<(Vec<f32>, Vec<[f32; 4]>)> {
+    ) -> Option<(Vec<f32>, Vec<[f32; 4]>, Vec<f32>)> {
         let area = dimensions.area() as usize;
         self.sprite_layers.get(z).and_then(|o| {
             o.as_ref()
@@ -0,0 +1,189 @@
+use crate::{
+    camera::WorldRect,
+    chunk::Chunk,
+    lib::*,
+    tilemap::{ErrorKind, TilemapResult},
+    Tilemap,
+};
+
+/// Returns `true` if a texture can be drawn from, which requires it to be RGBA
+/// with 8 bits per channel.
+pub(crate) fn is_supported_texture(texture: &Texture) -> bool {
+    matches!(
+        texture.format,
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm
+    )
+}
+
+/// An RGBA image that tile sprites are drawn onto on the CPU.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Canvas {
+    /// The area that the canvas covers.
+    bounds: WorldRect,
+    /// The amount of pixels per unit.
+    scale: f32,
+    /// The width in pixels.
+    width: u32,
+    /// The height in pixels.
+    height: u32,
+    /// The RGBA pixels, from the top row down.
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    /// Constructs a new transparent canvas which covers an area with an amount
+    /// of pixels per unit.
+    ///
+    /// Returns an error if the canvas would have more pixels than fit in
+    /// memory.
+    pub(crate) fn new(bounds: WorldRect, scale: f32) -> TilemapResult<Canvas> {
+        // Float to integer casts saturate, so an endless area is caught below.
+        let width = (bounds.width() * scale).ceil().max(1.0) as u32;
+        let height = (bounds.height() * scale).ceil().max(1.0) as u32;
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|area| area.checked_mul(4))
+            .filter(|&len| len <= isize::MAX as usize)
+            .ok_or(ErrorKind::ImageTooLarge)?;
+        Ok(Canvas {
+            bounds,
+            scale,
+            width,
+            height,
+            pixels: vec![0; len],
+        })
+    }
+
+    /// The width in pixels.
+    pub(crate) fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height in pixels.
+    pub(crate) fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Consumes the canvas and returns its RGBA pixels, from the top row down.
+    pub(crate) fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    /// Consumes the canvas and returns it as a texture.
+    pub(crate) fn into_texture(self) -> Texture {
+        Texture::new(
+            Extent3d::new(self.width, self.height, 1),
+            TextureDimension::D2,
+            self.pixels,
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    /// Draws a sprite of an atlas texture into a rectangle of the canvas,
+    /// tinted by a color and blended over what was drawn before.
+    ///
+    /// The texture must be RGBA with 8 bits per channel.
+    pub(crate) fn draw_sprite(
+        &mut self,
+        corner: Vec2,
+        size: Vec2,
+        sprite: &Rect,
+        texture: &Texture,
+        tint: [f32; 4],
+    ) {
+        if size.x <= 0.0 || size.y <= 0.0 {
+            return;
+        }
+        let texture_width = texture.size.width as usize;
+        let sprite_width = sprite.max.x - sprite.min.x;
+        let sprite_height = sprite.max.y - sprite.min.y;
+        let start = (corner - self.bounds.min) * self.scale;
+        let end = (corner + size - self.bounds.min) * self.scale;
+        // Float to integer casts saturate, so anything off the canvas to the
+        // bottom left becomes zero.
+        let min_x = start.x.floor() as u32;
+        let min_y = start.y.floor() as u32;
+        let max_x = (end.x.ceil() as u32).min(self.width);
+        let max_y = (end.y.ceil() as u32).min(self.height);
+        let [tint_r, tint_g, tint_b, tint_a] = tint;
+        for py in min_y..max_y {
+            for px in min_x..max_x {
+                // The center of the pixel.
+                let x = self.bounds.min.x + (px as f32 + 0.5) / self.scale;
+                let y = self.bounds.min.y + (py as f32 + 0.5) / self.scale;
+                let u = (x - corner.x) / size.x;
+                let v = (y - corner.y) / size.y;
+                if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+                    continue;
+                }
+                // Textures go from the top down while the canvas goes up.
+                let texel_x = (sprite.min.x + u * sprite_width) as usize;
+                let texel_y = (sprite.max.y - 1.0 - v * sprite_height).max(sprite.min.y) as usize;
+                let source = (texel_y * texture_width + texel_x) * 4;
+                let (src_r, src_g, src_b, src_a) = match texture.data.get(source..source + 4) {
+                    Some([r, g, b, a]) => (
+                        f32::from(*r) / 255.0 * tint_r,
+                        f32::from(*g) / 255.0 * tint_g,
+                        f32::from(*b) / 255.0 * tint_b,
+                        f32::from(*a) / 255.0 * tint_a,
+                    ),
+                    _ => continue,
+                };
+                if src_a <= 0.0 {
+                    continue;
+                }
+                let row = (self.height - 1 - py) as usize;
+                let dest = (row * self.width as usize + px as usize) * 4;
+                if let Some([r, g, b, a]) = self.pixels.get_mut(dest..dest + 4) {
+                    let dst_a = f32::from(*a) / 255.0;
+                    let out_a = src_a + dst_a * (1.0 - src_a);
+                    let blend = |src: f32, dst: u8| -> u8 {
+                        let dst = f32::from(dst) / 255.0;
+                        let out = (src * src_a + dst * dst_a * (1.0 - src_a)) / out_a;
+                        (out * 255.0).round().clamp(0.0, 255.0) as u8
+                    };
+                    *r = blend(src_r, *r);
+                    *g = blend(src_g, *g);
+                    *b = blend(src_b, *b);
+                    *a = (out_a * 255.0).round().min(255.0) as u8;
+                }
+            }
+        }
+    }
+
+    /// Draws every tile of a chunk layer with the chunk's center at an offset.
+    pub(crate) fn draw_chunk_layer(
+        &mut self,
+        tilemap: &Tilemap,
+        chunk: &Chunk,
+        z_order: usize,
+        offset: Vec2,
+        atlas: &TextureAtlas,
+        texture: &Texture,
+    ) {
+        if !tilemap.is_layer_visible(z_order) {
+            return;
+        }
+        let [layer_r, layer_g, layer_b, layer_a]: [f32; 4] = tilemap.layer_color(z_order).into();
+        let chunk_dimensions = tilemap.chunk_dimensions();
+        let topology = tilemap.topology();
+        for index in 0..chunk_dimensions.area() as usize {
+            let tile = if let Some(tile) = chunk.get_tile(z_order, index) {
+                tile
+            } else {
+                continue;
+            };
+            let sprite = if let Some(sprite) = atlas.textures.get(tile.index) {
+                sprite
+            } else {
+                continue;
+            };
+            let size = Vec2::new(sprite.max.x - sprite.min.x, sprite.max.y - sprite.min.y);
+            let tile_point = chunk_dimensions.decode_point_unchecked(index);
+            let corner = offset + topology.tile_corner(tile_point, chunk_dimensions, size);
+            let [r, g, b, a]: [f32; 4] = tile.color.into();
+            let tint = [r * layer_r, g * layer_g, b * layer_b, a * layer_a];
+            self.draw_sprite(corner, size, sprite, texture, tint);
+        }
+    }
+}
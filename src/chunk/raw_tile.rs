@@ -2,12 +2,16 @@ use crate::lib::*;
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, PartialEq, Debug)]
-/// A raw tile composed of simply an index and a color.
+/// A raw tile composed of simply an index, a color and a palette.
 pub struct RawTile {
     /// The index of the tile in the sprite sheet.
     pub index: usize,
     /// The color, or tint, of the tile.
     pub color: Color,
+    /// The row of the palette texture to color the tile with, for layers
+    /// drawn with a palette.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub palette: u8,
 }
 
 impl Default for RawTile {
@@ -15,32 +19,38 @@ impl Default for RawTile {
         RawTile {
             index: 0,
             color: Color::WHITE,
+            palette: 0,
         }
     }
 }
 
-/// A utility function that takes an array of `Tile`s and splits the indexes and
-/// colors and returns them as separate vectors for use in the renderer.
-pub(crate) fn dense_tiles_to_attributes(tiles: &[RawTile]) -> (Vec<f32>, Vec<[f32; 4]>) {
+/// A utility function that takes an array of `Tile`s and splits the indexes,
+/// colors and palettes and returns them as separate vectors for use in the
+/// renderer.
+pub(crate) fn dense_tiles_to_attributes(tiles: &[RawTile]) -> (Vec<f32>, Vec<[f32; 4]>, Vec<f32>) {
     let capacity = tiles.len() * 4;
     let mut tile_indexes: Vec<f32> = Vec::with_capacity(capacity);
     let mut tile_colors: Vec<[f32; 4]> = Vec::with_capacity(capacity);
+    let mut tile_palettes: Vec<f32> = Vec::with_capacity(capacity);
     for tile in tiles.iter() {
         tile_indexes.extend([tile.index as f32; 4].iter());
         tile_colors.extend([tile.color.into(); 4].iter());
+        tile_palettes.extend([f32::from(tile.palette); 4].iter());
     }
-    (tile_indexes, tile_colors)
+    (tile_indexes, tile_colors, tile_palettes)
 }
 
-/// A utility function that takes a sparse map of `Tile`s and splits the indexes
-/// and colors and returns them as separate vectors for use in the renderer.
+/// A utility function that takes a sparse map of `Tile`s and splits the
+/// indexes, colors and palettes and returns them as separate vectors for use
+/// in the renderer.
 pub(crate) fn sparse_tiles_to_attributes(
     area: usize,
     tiles: &HashMap<usize, RawTile>,
-) -> (Vec<f32>, Vec<[f32; 4]>) {
+) -> (Vec<f32>, Vec<[f32; 4]>, Vec<f32>) {
     let mut tile_indexes = vec![0.; area * 4];
     // If tiles are set with an alpha of 0, they are discarded.
     let mut tile_colors = vec![[0.0, 0.0, 0.0, 0.0]; area * 4];
+    let mut tile_palettes = vec![0.; area * 4];
     for (index, tile) in tiles.iter() {
         for i in 0..4 {
             if let Some(index) = tile_indexes.get_mut(index * 4 + i) {
@@ -49,7 +59,10 @@ pub(crate) fn sparse_tiles_to_attributes(
             if let Some(index) = tile_colors.get_mut(index * 4 + i) {
                 *index = tile.color.into();
             }
+            if let Some(index) = tile_palettes.get_mut(index * 4 + i) {
+                *index = f32::from(tile.palette);
+            }
         }
     }
-    (tile_indexes, tile_colors)
+    (tile_indexes, tile_colors, tile_palettes)
 }
@@ -1,4 +1,37 @@
-use crate::lib::*;
+use crate::{chunk::entity::ChunkLayerUniform, lib::*};
+
+/// The name of the render graph node which binds the tint and opacity of
+/// each chunk layer.
+const CHUNK_LAYER_UNIFORM_NODE: &str = "chunk_layer_uniform";
+
+/// Builds a chunk render pipeline from a vertex and fragment shader.
+fn chunk_pipeline(vertex: Handle<Shader>, fragment: Handle<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::default(),
+            color_blend: LayerBlend::default().color_blend(),
+            alpha_blend: BlendDescriptor {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::One,
+                operation: BlendOperation::Add,
+            },
+            write_mask: ColorWrite::ALL,
+        }],
+        depth_stencil_state: Some(LayerDepth::default().depth_stencil_state()),
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex,
+            fragment: Some(fragment),
+        })
+    }
+}
 
 macro_rules! build_chunk_pipeline {
     ($handle: ident, $id: expr, $name: ident, $file: expr) => {
@@ -8,49 +41,13 @@ macro_rules! build_chunk_pipeline {
 
         /// Builds the chunk render pipeline.
         fn $name(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
-            PipelineDescriptor {
-                rasterization_state: Some(RasterizationStateDescriptor {
-                    front_face: FrontFace::Ccw,
-                    cull_mode: CullMode::None,
-                    depth_bias: 0,
-                    depth_bias_slope_scale: 0.0,
-                    depth_bias_clamp: 0.0,
-                    clamp_depth: false,
-                }),
-                color_states: vec![ColorStateDescriptor {
-                    format: TextureFormat::default(),
-                    color_blend: BlendDescriptor {
-                        src_factor: BlendFactor::SrcAlpha,
-                        dst_factor: BlendFactor::OneMinusSrcAlpha,
-                        operation: BlendOperation::Add,
-                    },
-                    alpha_blend: BlendDescriptor {
-                        src_factor: BlendFactor::One,
-                        dst_factor: BlendFactor::One,
-                        operation: BlendOperation::Add,
-                    },
-                    write_mask: ColorWrite::ALL,
-                }],
-                depth_stencil_state: Some(DepthStencilStateDescriptor {
-                    format: TextureFormat::Depth32Float,
-                    depth_write_enabled: true,
-                    depth_compare: CompareFunction::LessEqual,
-                    stencil: StencilStateDescriptor {
-                        front: StencilStateFaceDescriptor::IGNORE,
-                        back: StencilStateFaceDescriptor::IGNORE,
-                        read_mask: 0,
-                        write_mask: 0,
-                    },
-                }),
-                ..PipelineDescriptor::new(ShaderStages {
-                    vertex: shaders
-                        .add(Shader::from_glsl(ShaderStage::Vertex, include_str!($file))),
-                    fragment: Some(shaders.add(Shader::from_glsl(
-                        ShaderStage::Fragment,
-                        include_str!("tilemap.frag"),
-                    ))),
-                })
-            }
+            chunk_pipeline(
+                shaders.add(Shader::from_glsl(ShaderStage::Vertex, include_str!($file))),
+                shaders.add(Shader::from_glsl(
+                    ShaderStage::Fragment,
+                    include_str!("tilemap.frag"),
+                )),
+            )
         }
     };
 }
@@ -98,6 +95,104 @@ build_chunk_pipeline!(
     "tilemap-hexrows-odd.vert"
 );
 
+/// How the tiles of a layer are blended with what is drawn below them.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayerBlend {
+    /// Standard alpha blending of straight alpha textures.
+    Alpha,
+    /// Adds the tile colors to what is below, such as for glows and fire.
+    Additive,
+    /// Multiplies what is below by the tile colors, such as for shadows.
+    ///
+    /// Transparent parts of the tiles should be white.
+    Multiply,
+    /// Alpha blending of textures with premultiplied alpha.
+    Premultiplied,
+}
+
+impl Default for LayerBlend {
+    fn default() -> LayerBlend {
+        LayerBlend::Alpha
+    }
+}
+
+impl LayerBlend {
+    /// Returns the color blend descriptor of the blend mode.
+    fn color_blend(&self) -> BlendDescriptor {
+        use LayerBlend::*;
+        let (src_factor, dst_factor) = match self {
+            Alpha => (BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+            Additive => (BlendFactor::SrcAlpha, BlendFactor::One),
+            Multiply => (BlendFactor::DstColor, BlendFactor::Zero),
+            Premultiplied => (BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+        };
+        BlendDescriptor {
+            src_factor,
+            dst_factor,
+            operation: BlendOperation::Add,
+        }
+    }
+}
+
+/// How the tiles of a layer are tested against and written to the depth
+/// buffer.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayerDepth {
+    /// Tiles are depth tested and write their depth.
+    Write,
+    /// Tiles are depth tested but do not write their depth, so that
+    /// transparent sprites drawn after the layer are not hidden by it.
+    ReadOnly,
+    /// Tiles are neither depth tested nor write their depth.
+    Ignore,
+}
+
+impl Default for LayerDepth {
+    fn default() -> LayerDepth {
+        LayerDepth::Write
+    }
+}
+
+impl LayerDepth {
+    /// Returns the depth stencil state of the depth mode.
+    fn depth_stencil_state(&self) -> DepthStencilStateDescriptor {
+        use LayerDepth::*;
+        let (depth_write_enabled, depth_compare) = match self {
+            Write => (true, CompareFunction::LessEqual),
+            ReadOnly => (false, CompareFunction::LessEqual),
+            Ignore => (false, CompareFunction::Always),
+        };
+        DepthStencilStateDescriptor {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled,
+            depth_compare,
+            stencil: StencilStateDescriptor {
+                front: StencilStateFaceDescriptor::IGNORE,
+                back: StencilStateFaceDescriptor::IGNORE,
+                read_mask: 0,
+                write_mask: 0,
+            },
+        }
+    }
+}
+
+/// Returns a copy of a chunk pipeline with the blend and depth modes of a
+/// layer applied to it.
+pub(crate) fn layer_blend_pipeline(
+    pipeline: &PipelineDescriptor,
+    blend: LayerBlend,
+    depth: LayerDepth,
+) -> PipelineDescriptor {
+    let mut pipeline = pipeline.clone();
+    for color_state in pipeline.color_states.iter_mut() {
+        color_state.color_blend = blend.color_blend();
+    }
+    pipeline.depth_stencil_state = Some(depth.depth_stencil_state());
+    pipeline
+}
+
 /// Topology of the tilemap grid (square or hex)
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -119,6 +214,97 @@ pub enum GridTopology {
 }
 
 impl GridTopology {
+    /// The GLSL source of the vertex shader of the topology.
+    fn vertex_shader_source(&self) -> &'static str {
+        use GridTopology::*;
+        match self {
+            Square => include_str!("tilemap-square.vert"),
+            HexY => include_str!("tilemap-hex-y.vert"),
+            HexX => include_str!("tilemap-hex-x.vert"),
+            HexEvenRows => include_str!("tilemap-hexrows-even.vert"),
+            HexOddRows => include_str!("tilemap-hexrows-odd.vert"),
+            HexEvenCols => include_str!("tilemap-hexcols-even.vert"),
+            HexOddCols => include_str!("tilemap-hexcols-odd.vert"),
+        }
+    }
+
+    /// Builds a render pipeline for a [`TilemapLayer`] with a custom fragment
+    /// shader, keeping the vertex shader of the topology.
+    ///
+    /// The fragment shader receives the same inputs as the default one:
+    ///
+    /// ```glsl
+    /// layout(location = 0) in vec2 v_Uv;
+    /// layout(location = 1) in vec4 v_Color;
+    /// layout(location = 2) in vec4 v_Light;
+    /// layout(location = 3) in float v_Palette;
+    ///
+    /// layout(set = 1, binding = 2) uniform texture2D TextureAtlas_texture;
+    /// layout(set = 1, binding = 3) uniform sampler TextureAtlas_texture_sampler;
+    ///
+    /// layout(set = 2, binding = 1) uniform ChunkLayer_color {
+    ///     vec4 LayerColor;
+    /// };
+    /// ```
+    ///
+    /// `LayerColor` is the tint of the layer with its opacity in the alpha
+    /// channel, which the shader should multiply into its output.
+    ///
+    /// Extra uniforms and textures can be bound with a
+    /// [`TilemapMaterialPlugin`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use bevy_asset::prelude::*;
+    /// use bevy_ecs::prelude::*;
+    /// use bevy_render::{pipeline::PipelineDescriptor, shader::{Shader, ShaderStage}};
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// fn setup(
+    ///     mut pipelines: ResMut<Assets<PipelineDescriptor>>,
+    ///     mut shaders: ResMut<Assets<Shader>>,
+    /// ) {
+    ///     let source = std::fs::read_to_string("assets/shaders/water.frag").unwrap();
+    ///     let fragment = shaders.add(Shader::from_glsl(ShaderStage::Fragment, &source));
+    ///     let pipeline = GridTopology::Square.layer_pipeline(&mut shaders, fragment);
+    ///     let water_layer = TilemapLayer {
+    ///         kind: LayerKind::Sparse,
+    ///         pipeline: Some(pipelines.add(pipeline)),
+    ///         ..Default::default()
+    ///     };
+    /// }
+    /// ```
+    ///
+    /// [`TilemapLayer`]: crate::TilemapLayer
+    /// [`TilemapMaterialPlugin`]: crate::material::TilemapMaterialPlugin
+    pub fn layer_pipeline(
+        &self,
+        shaders: &mut Assets<Shader>,
+        fragment: Handle<Shader>,
+    ) -> PipelineDescriptor {
+        let vertex = shaders.add(Shader::from_glsl(
+            ShaderStage::Vertex,
+            self.vertex_shader_source(),
+        ));
+        chunk_pipeline(vertex, fragment)
+    }
+
+    /// Builds a render pipeline for a [`TilemapLayer`] that colors an indexed
+    /// color tileset with a [`TilemapPalette`].
+    ///
+    /// See the [`palette`] module for how to bind the palette to the layer.
+    ///
+    /// [`TilemapLayer`]: crate::TilemapLayer
+    /// [`TilemapPalette`]: crate::palette::TilemapPalette
+    /// [`palette`]: crate::palette
+    pub fn palette_pipeline(&self, shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+        let fragment = shaders.add(Shader::from_glsl(
+            ShaderStage::Fragment,
+            include_str!("tilemap-palette.frag"),
+        ));
+        self.layer_pipeline(shaders, fragment)
+    }
+
     /// Takes a grid topology and returns a handle.
     pub(crate) fn to_pipeline_handle(&self) -> HandleUntyped {
         use GridTopology::*;
@@ -134,6 +320,75 @@ impl GridTopology {
     }
 }
 
+impl GridTopology {
+    /// Returns the bottom left corner of a tile relative to the center of its
+    /// chunk.
+    ///
+    /// This mirrors the offsets that the vertex shader of each topology
+    /// applies to the tiles.
+    pub(crate) fn tile_corner(
+        &self,
+        tile_point: Point2,
+        chunk_dimensions: Dimension2,
+        tile_size: Vec2,
+    ) -> Vec2 {
+        let width = tile_size.x;
+        let height = tile_size.y;
+        let x = tile_point.x as f32 - chunk_dimensions.width as f32 / 2.0;
+        let y = tile_point.y as f32 - chunk_dimensions.height as f32 / 2.0;
+        let col = (x + 0.01).floor() as i32 + 1;
+        let row = (y + 0.01).floor() as i32 + 1;
+        let col_compact = col as f32 * (0.25 * width).ceil();
+        let row_compact = row as f32 * (0.25 * height).ceil();
+        let col_offset = (0.25 * height).floor();
+        let row_offset = (0.25 * width).floor();
+        use GridTopology::*;
+        match self {
+            Square => Vec2::new(x * width, y * height),
+            HexX => Vec2::new(
+                x * width - col_compact,
+                y * height + (0.5 * height).floor() * col as f32,
+            ),
+            HexY => Vec2::new(
+                x * width + (0.5 * width).floor() * row as f32,
+                y * height - row_compact,
+            ),
+            HexEvenCols => {
+                let offset = if col.rem_euclid(2) == 0 {
+                    -col_offset
+                } else {
+                    col_offset
+                };
+                Vec2::new(x * width - col_compact, y * height + offset)
+            }
+            HexOddCols => {
+                let offset = if col.rem_euclid(2) == 0 {
+                    col_offset
+                } else {
+                    -col_offset
+                };
+                Vec2::new(x * width - col_compact, y * height + offset)
+            }
+            HexEvenRows => {
+                let offset = if row.rem_euclid(2) == 0 {
+                    -row_offset
+                } else {
+                    row_offset
+                };
+                Vec2::new(x * width + offset, y * height - row_compact)
+            }
+            HexOddRows => {
+                let offset = if row.rem_euclid(2) == 0 {
+                    row_offset
+                } else {
+                    -row_offset
+                };
+                Vec2::new(x * width + offset, y * height - row_compact)
+            }
+        }
+    }
+}
+
 /// A trait which implements the tilemap graph to a render graph.
 pub trait TilemapRenderGraphBuilder {
     /// Adds the tilemaps render graph.
@@ -172,6 +427,17 @@ impl TilemapRenderGraphBuilder for RenderGraph {
             build_chunk_hexrows_odd(&mut shaders),
         );
 
+        self.add_system_node(
+            CHUNK_LAYER_UNIFORM_NODE,
+            RenderResourcesNode::<ChunkLayerUniform>::new(true),
+        );
+        if let Err(e) = self.add_node_edge(CHUNK_LAYER_UNIFORM_NODE, base_node::MAIN_PASS) {
+            error!(
+                "can not add the chunk layer uniform to the main pass: {}",
+                e
+            );
+        }
+
         self
     }
 }
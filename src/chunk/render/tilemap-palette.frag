@@ -0,0 +1,43 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+layout(location = 1) in vec4 v_Color;
+layout(location = 2) in vec4 v_Light;
+layout(location = 3) in float v_Palette;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 1, binding = 2) uniform texture2D TextureAtlas_texture;
+layout(set = 1, binding = 3) uniform sampler TextureAtlas_texture_sampler;
+
+layout(set = 2, binding = 1) uniform ChunkLayer_color {
+    vec4 LayerColor;
+};
+
+layout(set = 3, binding = 0) uniform TilemapPalette_offset {
+    float PaletteOffset;
+};
+layout(set = 3, binding = 1) uniform texture2D TilemapPalette_texture;
+layout(set = 3, binding = 2) uniform sampler TilemapPalette_texture_sampler;
+
+void main() {
+    if (v_Color.a == 0.0) {
+        discard;
+    }
+    // The red channel of the atlas holds the color index into the palette.
+    vec4 indexed = texture(
+        sampler2D(TextureAtlas_texture, TextureAtlas_texture_sampler),
+        v_Uv
+    );
+    vec2 palette_size = vec2(textureSize(
+        sampler2D(TilemapPalette_texture, TilemapPalette_texture_sampler),
+        0
+    ));
+    float column = floor(indexed.r * 255.0 + 0.5);
+    float row = mod(floor(v_Palette + PaletteOffset), palette_size.y);
+    vec4 color = texture(
+        sampler2D(TilemapPalette_texture, TilemapPalette_texture_sampler),
+        (vec2(column, row) + 0.5) / palette_size
+    );
+    o_Target = LayerColor * v_Color * v_Light * vec4(color.rgb, color.a * indexed.a);
+}
@@ -3,9 +3,13 @@
 layout(location = 0) in vec3 Vertex_Position;
 layout(location = 1) in float Vertex_Tile_Index;
 layout(location = 2) in vec4 Vertex_Tile_Color;
+layout(location = 3) in vec4 Vertex_Tile_Light;
+layout(location = 4) in float Vertex_Tile_Palette;
 
 layout(location = 0) out vec2 v_Uv;
 layout(location = 1) out vec4 v_Color;
+layout(location = 2) out vec4 v_Light;
+layout(location = 3) out float v_Palette;
 
 layout(set = 0, binding = 0) uniform Camera {
     mat4 ViewProj;
@@ -47,5 +51,7 @@ void main() {
     );
     v_Uv = floor(atlas_positions[gl_VertexIndex % 4] + vec2(0.01, 0.01)) / AtlasSize;
     v_Color = Vertex_Tile_Color;
+    v_Light = Vertex_Tile_Light;
+    v_Palette = Vertex_Tile_Palette;
     gl_Position = ViewProj * ChunkTransform * vec4(ceil(vertex_position), 1.0);
 }
@@ -2,17 +2,22 @@
 
 layout(location = 0) in vec2 v_Uv;
 layout(location = 1) in vec4 v_Color;
+layout(location = 2) in vec4 v_Light;
 
 layout(location = 0) out vec4 o_Target;
 
 layout(set = 1, binding = 2) uniform texture2D TextureAtlas_texture;
 layout(set = 1, binding = 3) uniform sampler TextureAtlas_texture_sampler;
 
+layout(set = 2, binding = 1) uniform ChunkLayer_color {
+    vec4 LayerColor;
+};
+
 void main() {
     if (v_Color.a == 0.0) {
         discard;
     }
-    o_Target = v_Color * texture(
+    o_Target = LayerColor * v_Color * v_Light * texture(
         sampler2D(TextureAtlas_texture, TextureAtlas_texture_sampler),
         v_Uv
     );
@@ -1,6 +1,8 @@
 use crate::{
+    camera::{camera_view_rect, select_views, ChunkVisibilityStats, TilemapCamera, WorldRect},
     chunk::{
-        entity::{ModifiedLayer, ZOrder},
+        entity::{ChunkLayerUniform, ModifiedLayer, ZOrder},
+        lod::ChunkLod,
         mesh::ChunkMesh,
     },
     lib::*,
@@ -33,115 +35,329 @@ pub(crate) fn chunk_update(
             error!("`Mesh` is missing, can not update chunk");
             return;
         };
-        let (indexes, colors) = if let Some((index, colors)) =
+        let (indexes, colors, palettes) = if let Some(parts) =
             chunk.tiles_to_renderer_parts(z_order.0, tilemap.chunk_dimensions())
         {
-            (index, colors)
+            parts
         } else {
             error!("Tiles are missing, can not update chunk");
             return;
         };
         mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_INDEX, indexes);
         mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_COLOR, colors);
+        mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_PALETTE, palettes);
+        mesh.set_attribute(
+            ChunkMesh::ATTRIBUTE_TILE_LIGHT,
+            chunk.lights_to_attributes(tilemap.chunk_dimensions()),
+        );
     }
 }
 
 /// Actual method used to spawn chunks.
+///
+/// Every camera view spawns the chunks it can see, with a radius of at least
+/// the spawn dimensions around its center plus the margin. The union of those
+/// chunks is kept, along with anything already spawned within the hysteresis
+/// past the margin. Everything else that was spawned is despawned so that
+/// multiple cameras do not fight over the same tilemap.
+///
+/// Layers with a parallax or scroll see the view shifted by their offset, so
+/// the chunks are spawned for each of those shifted views too. The chunks of
+/// wrapping layers are wrapped into the dimensions of the tilemap.
+///
+/// If the tilemap itself wraps, chunk points past its dimensions are wrapped
+/// to the chunk they show, which is spawned, and are kept as mirrored chunks
+/// to be drawn at the point past the seam.
 fn auto_spawn(
-    camera_transform: &Transform,
-    tilemap_transform: &Transform,
+    views: &[WorldRect],
+    tilemap_transform: &GlobalTransform,
     tilemap: &mut Tilemap,
     spawn_dimensions: Dimension2,
 ) {
-    let translation = camera_transform.translation - tilemap_transform.translation;
-    let point_x = translation.x / tilemap.tile_width() as f32;
-    let point_y = translation.y / tilemap.tile_height() as f32;
-    let (chunk_x, chunk_y) = tilemap.point_to_chunk_point((point_x as i32, point_y as i32));
-    let mut new_spawned: Vec<Point2> = Vec::new();
-    let spawn_width = spawn_dimensions.width as i32;
-    let spawn_height = spawn_dimensions.height as i32;
-    for y in -spawn_width as i32..spawn_width + 1 {
-        for x in -spawn_height..spawn_height + 1 {
-            let chunk_x = x + chunk_x;
-            let chunk_y = y + chunk_y;
-            if let Some(width) = tilemap.width() {
-                let width = (width / tilemap.chunk_width()) as i32 / 2;
-                if chunk_x < -width || chunk_x > width {
-                    continue;
+    let chunk_bounds = tilemap.chunk_bounds((0, 0));
+    let margin = tilemap.auto_spawn_margin() as i32;
+    let hysteresis = tilemap.auto_spawn_hysteresis() as i32;
+    let max_chunk_x = tilemap
+        .width()
+        .map(|width| (width / tilemap.chunk_width()) as i32 / 2);
+    let max_chunk_y = tilemap
+        .height()
+        .map(|height| (height / tilemap.chunk_height()) as i32 / 2);
+    let mut new_spawned: HashSet<Point2> = HashSet::default();
+    let mut kept: HashSet<Point2> = HashSet::default();
+    let mut mirrors: HashSet<Point2> = HashSet::default();
+    let mut focus: Vec<Point2> = Vec::with_capacity(views.len());
+    let shifts = tilemap.layer_shifts();
+    let shifted_views = views.iter().flat_map(|view| {
+        // The view in the tilemap's space accounts for the camera's zoom as
+        // well as the scale of the tilemap.
+        let view = view.inverse_transformed(tilemap_transform);
+        shifts
+            .iter()
+            .map(move |(shift, wrap)| (WorldRect::new(view.min - *shift, view.max - *shift), *wrap))
+    });
+    for (view, wrap) in shifted_views {
+        let center = view.center();
+        let point_x = center.x / tilemap.tile_width() as f32;
+        let point_y = center.y / tilemap.tile_height() as f32;
+        // The chunk under the view is not wrapped so that mirrored chunks are
+        // spawned around views past the seam of a wrapping tilemap.
+        let view_chunk = tilemap.unwrapped_chunk_point(Point2::new(point_x as i32, point_y as i32));
+        let (chunk_x, chunk_y) = (view_chunk.x, view_chunk.y);
+        focus.push(tilemap.wrap_chunk_point_of_tilemap(view_chunk));
+        let radius_x = ((view.width() / 2.0 / chunk_bounds.width()).ceil() as i32 + 1)
+            .max(spawn_dimensions.width as i32)
+            + margin;
+        let radius_y = ((view.height() / 2.0 / chunk_bounds.height()).ceil() as i32 + 1)
+            .max(spawn_dimensions.height as i32)
+            + margin;
+        let keep_x = radius_x + hysteresis;
+        let keep_y = radius_y + hysteresis;
+        for y in -keep_y..keep_y + 1 {
+            for x in -keep_x..keep_x + 1 {
+                let unwrapped = Point2::new(x + chunk_x, y + chunk_y);
+                let point = if wrap {
+                    tilemap.wrap_chunk_point(unwrapped, true, true)
+                } else {
+                    tilemap.wrap_chunk_point_of_tilemap(unwrapped)
+                };
+                let (chunk_x, chunk_y) = (point.x, point.y);
+                if let Some(width) = max_chunk_x {
+                    if chunk_x < -width || chunk_x > width {
+                        continue;
+                    }
                 }
-            }
-            if let Some(height) = tilemap.height() {
-                let height = (height / tilemap.chunk_height()) as i32 / 2;
-                if chunk_y < -height || chunk_y > height {
-                    continue;
+                if let Some(height) = max_chunk_y {
+                    if chunk_y < -height || chunk_y > height {
+                        continue;
+                    }
+                }
+                if !wrap && point != unwrapped {
+                    mirrors.insert(unwrapped);
+                }
+                kept.insert(point);
+                if x.abs() <= radius_x && y.abs() <= radius_y {
+                    new_spawned.insert(point);
                 }
             }
+        }
+    }
 
-            if let Err(e) = tilemap.spawn_chunk(Point2::new(chunk_x, chunk_y)) {
-                warn!("{}", e);
-            }
-            new_spawned.push(Point2::new(chunk_x, chunk_y));
+    // The closest chunks to the center of each view are spawned first if
+    // there are more than the budgets allow in a frame.
+    tilemap.set_spawn_focus(focus);
+    tilemap.set_mirror_chunks(mirrors);
+    for point in new_spawned.iter() {
+        if let Err(e) = tilemap.spawn_chunk(*point) {
+            warn!("{}", e);
         }
     }
 
     let spawned_list = tilemap.spawned_chunks_mut().clone();
     for point in spawned_list.iter() {
-        if !new_spawned.contains(&point.into()) {
+        if !kept.contains(&point.into()) {
             if let Err(e) = tilemap.despawn_chunk(point) {
                 warn!("{}", e);
             }
         }
     }
+
+    // Chunks that are still waiting on the budgets but are out of view would
+    // otherwise be spawned later only to be despawned again.
+    tilemap.retain_spawn_queue(&new_spawned);
+}
+
+/// Collects the views of all the cameras that tilemaps are spawned for.
+fn tilemap_camera_views(
+    camera_query: &Query<(
+        &Camera,
+        &OrthographicProjection,
+        &GlobalTransform,
+        Option<&TilemapCamera>,
+    )>,
+) -> Vec<WorldRect> {
+    select_views(
+        camera_query
+            .iter()
+            .map(|(camera, projection, transform, marker)| {
+                (
+                    camera_view_rect(projection, transform),
+                    camera,
+                    marker.is_some(),
+                )
+            }),
+    )
 }
 
-/// On window size change, the radius of chunks changes if needed.
-pub(crate) fn chunk_auto_radius(
-    window_resized_events: Res<Events<WindowResized>>,
-    mut tilemap_query: Query<(&mut Tilemap, &Transform)>,
-    camera_query: Query<(&Camera, &Transform)>,
+/// Spawns and despawns chunks automatically based on the cameras' views.
+///
+/// The chunks are recalculated whenever a camera moves, zooms or has its
+/// projection changed, such as when the window is resized, or when a tilemap
+/// is moved or scaled.
+pub(crate) fn chunk_auto_spawn(
+    mut tilemap_query: Query<(&mut Tilemap, &GlobalTransform)>,
+    camera_query: Query<(
+        &Camera,
+        &OrthographicProjection,
+        &GlobalTransform,
+        Option<&TilemapCamera>,
+    )>,
+    moved_camera_query: Query<&Camera, Changed<GlobalTransform>>,
+    projected_camera_query: Query<&Camera, Changed<OrthographicProjection>>,
+    moved_tilemap_query: Query<Entity, (With<Tilemap>, Changed<GlobalTransform>)>,
 ) {
-    let mut window_reader = window_resized_events.get_reader();
-    for event in window_reader.iter(&window_resized_events) {
-        for (mut tilemap, tilemap_transform) in tilemap_query.iter_mut() {
-            let window_width = event.width as u32;
-            let window_height = event.height as u32;
-            let chunk_px_width = tilemap.chunk_width() * tilemap.tile_width();
-            let chunk_px_height = tilemap.chunk_height() * tilemap.tile_height();
-            let chunks_wide = (window_width as f32 / chunk_px_width as f32).ceil() as u32 + 1;
-            let chunks_high = (window_height as f32 / chunk_px_height as f32).ceil() as u32 + 1;
-            let spawn_dimensions = Dimension2::new(chunks_wide, chunks_high);
-            tilemap.set_auto_spawn(spawn_dimensions);
-            for (_camera, camera_transform) in camera_query.iter() {
-                auto_spawn(
-                    camera_transform,
-                    &tilemap_transform,
-                    &mut tilemap,
-                    spawn_dimensions,
-                );
-            }
+    let moved = moved_camera_query.iter().next().is_some()
+        || projected_camera_query.iter().next().is_some()
+        || moved_tilemap_query.iter().next().is_some();
+    let views = tilemap_camera_views(&camera_query);
+    for (mut tilemap, tilemap_transform) in tilemap_query.iter_mut() {
+        // Scrolling layers move without the cameras moving.
+        if !moved && !tilemap.has_scrolling_layers() {
+            continue;
         }
+        let spawn_dimensions = if let Some(dimensions) = tilemap.auto_spawn() {
+            dimensions
+        } else {
+            continue;
+        };
+        auto_spawn(&views, tilemap_transform, &mut tilemap, spawn_dimensions);
     }
 }
 
-/// Spawns and despawns chunks automatically based on a camera's position.
-pub(crate) fn chunk_auto_spawn(
-    mut tilemap_query: Query<(&mut Tilemap, &Transform)>,
-    camera_query: Query<(&Camera, &Transform), Changed<Transform>>,
+/// Hides chunk layers which are not within the view of any tilemap camera.
+///
+/// If there are no 2D cameras, nothing is hidden. While a tilemap is drawn
+/// with level of detail sprites, the layers of the chunks that have a baked
+/// sprite are hidden instead and the sprites are culled in the same way.
+pub(crate) fn chunk_visibility(
+    mut stats: ResMut<ChunkVisibilityStats>,
+    camera_query: Query<(
+        &Camera,
+        &OrthographicProjection,
+        &GlobalTransform,
+        Option<&TilemapCamera>,
+    )>,
+    tilemap_query: Query<(&Tilemap, &GlobalTransform)>,
+    mut chunk_query: Query<
+        (
+            &Parent,
+            &Point2,
+            &Transform,
+            &mut Visible,
+            Option<&ZOrder>,
+            Option<&ChunkLod>,
+        ),
+        Or<(With<ZOrder>, With<ChunkLod>)>,
+    >,
 ) {
-    // For the transform, get chunk coord.
-    for (mut tilemap, tilemap_transform) in tilemap_query.iter_mut() {
-        for (_camera, camera_transform) in camera_query.iter() {
-            let spawn_dimensions = if let Some(dimensions) = tilemap.auto_spawn() {
-                dimensions
+    let views = tilemap_camera_views(&camera_query);
+
+    let mut visible_count = 0;
+    let mut culled_count = 0;
+    for (parent, point, transform, mut visible, z_order, lod) in chunk_query.iter_mut() {
+        let (tilemap, tilemap_transform) = if let Ok(tilemap) = tilemap_query.get(**parent) {
+            tilemap
+        } else {
+            continue;
+        };
+        // Chunks are drawn with their layers until their level of detail
+        // texture is baked. Mirrored chunks past the seam of a wrapping
+        // tilemap have no level of detail sprite of their own, so they keep
+        // drawing their layers to not leave a hole at the seam.
+        let is_lod = !tilemap.is_mirror_chunk(point)
+            && tilemap.is_lod_active()
+            && tilemap
+                .get_chunk(point)
+                .map_or(false, |chunk| chunk.lod_entity().is_some());
+        let is_hidden = z_order.map_or(false, |z_order| !tilemap.is_layer_visible(z_order.0));
+        let is_visible = if is_lod != lod.is_some() || is_hidden {
+            false
+        } else if views.is_empty() {
+            true
+        } else {
+            let bounds = tilemap.chunk_bounds(*point);
+            // Chunk layers are moved away from their chunk by their parallax.
+            let shift = if z_order.is_some() {
+                transform.translation.truncate() - tilemap.chunk_translation(*point)
             } else {
-                continue;
+                Vec2::zero()
             };
-            auto_spawn(
-                camera_transform,
-                &tilemap_transform,
-                &mut tilemap,
-                spawn_dimensions,
-            );
+            let bounds = WorldRect::new(bounds.min + shift, bounds.max + shift)
+                .transformed(tilemap_transform);
+            views.iter().any(|view| view.intersects(&bounds))
+        };
+        if visible.is_visible != is_visible {
+            visible.is_visible = is_visible;
+        }
+        if is_visible {
+            visible_count += 1;
+        } else {
+            culled_count += 1;
+        }
+    }
+
+    stats.visible = visible_count;
+    stats.culled = culled_count;
+}
+
+/// Keeps the tint and opacity of chunk layers in sync with the layers of
+/// their tilemap.
+pub(crate) fn chunk_layer_uniforms(
+    tilemap_query: Query<&Tilemap>,
+    mut chunk_query: Query<(&Parent, &ZOrder, &mut ChunkLayerUniform)>,
+) {
+    for (parent, z_order, mut uniform) in chunk_query.iter_mut() {
+        let tilemap = if let Ok(tilemap) = tilemap_query.get(**parent) {
+            tilemap
+        } else {
+            continue;
+        };
+        let color = tilemap.layer_color(z_order.0);
+        if uniform.color != color {
+            uniform.color = color;
+        }
+    }
+}
+
+/// Moves the chunk layers of parallax, scrolling and wrapping layers relative
+/// to the first camera that tilemaps are spawned for.
+pub(crate) fn chunk_parallax(
+    time: Res<Time>,
+    camera_query: Query<(
+        &Camera,
+        &OrthographicProjection,
+        &GlobalTransform,
+        Option<&TilemapCamera>,
+    )>,
+    mut tilemap_query: Query<(&mut Tilemap, &GlobalTransform)>,
+    mut chunk_query: Query<(&Parent, &Point2, &ZOrder, &mut Transform)>,
+) {
+    let focus = tilemap_camera_views(&camera_query)
+        .first()
+        .map(|view| view.center());
+    for (mut tilemap, tilemap_transform) in tilemap_query.iter_mut() {
+        if !tilemap.has_parallax_layers() {
+            continue;
+        }
+        let focus = focus.map_or_else(Vec2::zero, |focus| {
+            (focus - tilemap_transform.translation.truncate()) / tilemap_transform.scale.truncate()
+        });
+        tilemap.update_parallax(focus, time.delta_seconds());
+    }
+
+    for (parent, point, z_order, mut transform) in chunk_query.iter_mut() {
+        let tilemap = if let Ok((tilemap, _)) = tilemap_query.get_mut(**parent) {
+            tilemap
+        } else {
+            continue;
+        };
+        if !tilemap.has_parallax_layers() {
+            continue;
+        }
+        let translation = tilemap.layer_chunk_translation(*point, z_order.0);
+        if transform.translation.truncate() != translation {
+            transform.translation.x = translation.x;
+            transform.translation.y = translation.y;
         }
     }
 }
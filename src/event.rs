@@ -1,29 +1,105 @@
 //! The tilemap events.
 
-use crate::lib::*;
 #[cfg(feature = "bevy_rapier2d")]
 use crate::Tile;
+use crate::{chunk::RawTile, lib::*};
 
-#[derive(Debug)]
-/// Events that can happen to chunks.
+#[derive(Clone, PartialEq, Debug)]
+/// Events that happened to the chunks of a tilemap.
+///
+/// These are sent as an app resource, so they are read with an `EventReader`
+/// like any other event.
+///
+/// # Examples
+/// ```
+/// use bevy_app::prelude::*;
+/// use bevy_ecs::prelude::*;
+/// use bevy_tilemap::event::TilemapChunkEvent;
+///
+/// fn populate_chunks(
+///     mut reader: Local<EventReader<TilemapChunkEvent>>,
+///     events: Res<Events<TilemapChunkEvent>>,
+/// ) {
+///     for event in reader.iter(&events) {
+///         if let TilemapChunkEvent::Loaded { tilemap, point } = event {
+///             // Generate the tiles of the new chunk...
+///         }
+///     }
+/// }
+/// ```
 pub enum TilemapChunkEvent {
-    /// An event when a chunk needs to be spawned.
+    /// An event when a chunk was inserted into a tilemap.
+    Loaded {
+        /// The tilemap entity of the chunk.
+        tilemap: Entity,
+        /// The point of the chunk.
+        point: Point2,
+    },
+    /// An event when a chunk was removed from a tilemap.
+    Unloaded {
+        /// The tilemap entity of the chunk.
+        tilemap: Entity,
+        /// The point of the chunk.
+        point: Point2,
+    },
+    /// An event when the entities of the layers of a chunk were spawned.
     Spawned {
-        /// The point to get the correct chunk to spawn.
+        /// The tilemap entity of the chunk.
+        tilemap: Entity,
+        /// The point of the chunk.
+        point: Point2,
+    },
+    /// An event when the entities of the layers of a chunk were despawned.
+    Despawned {
+        /// The tilemap entity of the chunk.
+        tilemap: Entity,
+        /// The point of the chunk.
         point: Point2,
     },
-    /// An event when a chunk has been modified and needs to reload its layer.
+    /// An event when tiles of a chunk were modified, whether it is spawned or
+    /// not.
     Modified {
-        /// The layers that had been modified.
-        layers: HashMap<usize, Entity>,
+        /// The tilemap entity of the chunk.
+        tilemap: Entity,
+        /// The point of the chunk.
+        point: Point2,
+        /// The z orders of the layers that were modified.
+        layers: Vec<usize>,
     },
-    /// An event when a chunk needs to be despawned.
-    Despawned {
+}
+
+#[derive(Clone, PartialEq, Debug)]
+/// Changes to chunks that the tilemap system still needs to handle.
+pub(crate) enum ChunkRequest {
+    /// A chunk was inserted.
+    Load {
+        /// The point of the chunk.
+        point: Point2,
+    },
+    /// A chunk was removed.
+    Unload {
+        /// The point of the chunk.
+        point: Point2,
+    },
+    /// A chunk needs to be spawned.
+    Spawn {
+        /// The point of the chunk to spawn.
+        point: Point2,
+    },
+    /// A chunk needs to be despawned.
+    Despawn {
         /// The entities that need to be despawned.
         entities: Vec<Entity>,
         /// The point of the chunk to despawn.
         point: Point2,
     },
+    /// A chunk was modified and needs to reload its layers.
+    Modify {
+        /// The point of the chunk that was modified.
+        point: Point2,
+        /// The z orders of the layers that were modified.
+        layers: Vec<usize>,
+    },
 }
 
 #[cfg(feature = "bevy_rapier2d")]
@@ -45,3 +121,72 @@ pub enum TilemapCollisionEvent {
         tiles: Vec<Tile<Point2>>,
     },
 }
+
+#[derive(Clone, PartialEq, Debug)]
+/// Events of entities with a tile position entering and exiting the regions of
+/// their tilemap.
+pub enum RegionEvent {
+    /// An event when an entity entered a region.
+    Entered {
+        /// The tilemap entity of the region.
+        tilemap: Entity,
+        /// The entity that entered the region.
+        entity: Entity,
+        /// The name of the region.
+        region: String,
+    },
+    /// An event when an entity exited a region, or was despawned in it.
+    Exited {
+        /// The tilemap entity of the region.
+        tilemap: Entity,
+        /// The entity that exited the region.
+        entity: Entity,
+        /// The name of the region.
+        region: String,
+    },
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+/// An event when a tile of a tilemap changed.
+///
+/// Changes are batched per frame: a tile that changed several times in a
+/// frame sends a single event with the tile as it was at the start of the
+/// frame and as it is at the end, and none if it ended up the same.
+pub struct TileChanged {
+    /// The tilemap entity of the tile.
+    pub tilemap: Entity,
+    /// The global tile point of the tile.
+    pub point: Point2,
+    /// The z order of the layer of the tile.
+    pub z_order: usize,
+    /// The tile before it changed, if there was one.
+    pub old: Option<RawTile>,
+    /// The tile after it changed, if there is one.
+    pub new: Option<RawTile>,
+}
+
+/// The tiles that changed since the changes were last taken, as they were
+/// before their first change.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub(crate) struct TileChanges {
+    /// The point, z order and previous tile of each change, in order.
+    changes: Vec<(Point2, usize, Option<RawTile>)>,
+    /// The points and layers that were already recorded.
+    recorded: HashSet<(Point2, usize)>,
+}
+
+impl TileChanges {
+    /// Records a tile as it was before it changed, if it was not already
+    /// recorded.
+    pub(crate) fn record(&mut self, point: Point2, z_order: usize, tile: Option<RawTile>) {
+        if self.recorded.insert((point, z_order)) {
+            self.changes.push((point, z_order, tile));
+        }
+    }
+
+    /// Takes every recorded change.
+    pub(crate) fn take(&mut self) -> Vec<(Point2, usize, Option<RawTile>)> {
+        self.recorded.clear();
+        mem::take(&mut self.changes)
+    }
+}
@@ -0,0 +1,245 @@
+//! Rooms and corridors laid out with binary space partitioning.
+
+use crate::{
+    generate::{GenerateArea, GenerateRng},
+    lib::*,
+    region::Region,
+    tilemap::TilemapResult,
+    Tilemap,
+};
+
+/// Lays out rooms connected by corridors by splitting an area in two over and
+/// over again, with binary space partitioning.
+///
+/// The area is filled with walls and every leaf of the partition gets a room
+/// of floors, with corridors joining the rooms of sibling leaves so that every
+/// room can be reached. Cells with a tile on the constraint layer are never
+/// carved out.
+///
+/// # Examples
+/// ```
+/// use bevy_asset::{prelude::*, HandleId};
+/// use bevy_sprite::prelude::*;
+/// use bevy_tilemap::{generate::{bsp::BspGenerator, GenerateArea}, prelude::*};
+///
+/// // In production use a strong handle from an actual source.
+/// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+///
+/// let new_tilemap = || {
+///     TilemapBuilder::new()
+///         .texture_atlas(texture_atlas_handle.clone())
+///         .tile_dimensions(32, 32)
+///         .auto_chunk()
+///         .finish()
+///         .unwrap()
+/// };
+/// let tiles = |tilemap: &mut Tilemap| -> Vec<Option<usize>> {
+///     (0..64 * 48)
+///         .map(|index| tilemap.get_tile((index % 64, index / 64), 0).map(|tile| tile.index))
+///         .collect()
+/// };
+///
+/// let dungeon = BspGenerator::new(7, 1);
+/// let area = GenerateArea::new((0, 0), 64, 48, 0);
+/// let mut tilemap = new_tilemap();
+/// let rooms = dungeon.generate(&mut tilemap, area, 42).unwrap();
+/// assert!(rooms.len() > 1);
+///
+/// // The same seed always lays out the same rooms.
+/// let mut other = new_tilemap();
+/// assert_eq!(dungeon.generate(&mut other, area, 42).unwrap(), rooms);
+/// assert_eq!(tiles(&mut other), tiles(&mut tilemap));
+///
+/// // Rock on the constraint layer is not carved out of the rooms.
+/// let floor = tiles(&mut tilemap).iter().position(|cell| *cell == Some(1)).unwrap() as i32;
+/// let rock = (floor % 64, floor / 64);
+/// let mut rocky = new_tilemap();
+/// rocky.insert_tile(Tile { point: rock, z_order: 1, sprite_index: 9, ..Default::default() }).unwrap();
+/// dungeon.generate(&mut rocky, area.with_constraint(1), 42).unwrap();
+/// assert_eq!(rocky.get_tile(rock, 0).map(|tile| tile.index), Some(7));
+///
+/// for (index, room) in rooms.into_iter().enumerate() {
+///     tilemap.insert_region(format!("room {}", index), room);
+/// }
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BspGenerator {
+    /// The sprite index of the walls.
+    pub wall: usize,
+    /// The sprite index of the floors of rooms and corridors.
+    pub floor: usize,
+    /// The smallest width and height of a room.
+    pub min_room: u32,
+    /// The smallest width and height that a leaf is split into, which needs to
+    /// fit a room and the walls around it.
+    pub min_leaf: u32,
+    /// The most times the area is split in two.
+    pub max_depth: usize,
+}
+
+impl BspGenerator {
+    /// Constructs a new generator of walls and floors of sprite indices.
+    ///
+    /// Rooms are at least 4 by 4 tiles in leaves of at least 8 by 8 tiles,
+    /// split up to 6 times.
+    pub fn new(wall: usize, floor: usize) -> BspGenerator {
+        BspGenerator {
+            wall,
+            floor,
+            min_room: 4,
+            min_leaf: 8,
+            max_depth: 6,
+        }
+    }
+
+    /// Generates rooms and corridors into an area of a tilemap from a seed,
+    /// returning a rectangular region for each room.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a tile is out of bounds or its chunk does not exist
+    /// and the tilemap does not automatically create chunks.
+    pub fn generate(
+        &self,
+        tilemap: &mut Tilemap,
+        area: GenerateArea,
+        seed: u64,
+    ) -> TilemapResult<Vec<Region>> {
+        let constraints = area.constraints(tilemap);
+        let width = area.width() as i32;
+        let height = area.height() as i32;
+        let mut rng = area.rng(seed);
+        let mut rooms = Vec::new();
+        let mut corridors = Vec::new();
+        let root = Leaf {
+            min: Point2::new(0, 0),
+            max: Point2::new(width - 1, height - 1),
+        };
+        self.split(root, 0, &mut rng, &mut rooms, &mut corridors);
+
+        let mut cells: Vec<Option<usize>> = vec![Some(self.wall); area.len()];
+        let mut carve = |x: i32, y: i32| {
+            if x < 0 || y < 0 || x >= width || y >= height {
+                return;
+            }
+            let index = (y * width + x) as usize;
+            if constraints.get(index).map_or(false, Option::is_some) {
+                return;
+            }
+            if let Some(cell) = cells.get_mut(index) {
+                *cell = Some(self.floor);
+            }
+        };
+        for (min, max) in &rooms {
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    carve(x, y);
+                }
+            }
+        }
+        // Corridors go across first and then up or down, in an L shape.
+        for (from, to) in corridors {
+            for x in from.x.min(to.x)..=from.x.max(to.x) {
+                carve(x, from.y);
+            }
+            for y in from.y.min(to.y)..=from.y.max(to.y) {
+                carve(to.x, y);
+            }
+        }
+
+        area.write(tilemap, &cells)?;
+        Ok(rooms
+            .into_iter()
+            .map(|(min, max)| Region::rect(min + area.origin, max + area.origin))
+            .collect())
+    }
+
+    /// Splits a leaf in two until it is too small or deep enough, placing a
+    /// room in every leaf and a corridor between the rooms of both halves.
+    ///
+    /// Returns the center of a room in the leaf, if it has one.
+    fn split(
+        &self,
+        leaf: Leaf,
+        depth: usize,
+        rng: &mut GenerateRng,
+        rooms: &mut Vec<(Point2, Point2)>,
+        corridors: &mut Vec<(Point2, Point2)>,
+    ) -> Option<Point2> {
+        let min_leaf = self.min_leaf.max(self.min_room + 2) as i32;
+        let width = leaf.max.x - leaf.min.x + 1;
+        let height = leaf.max.y - leaf.min.y + 1;
+        let can_split_x = width >= min_leaf * 2;
+        let can_split_y = height >= min_leaf * 2;
+        if depth < self.max_depth && (can_split_x || can_split_y) {
+            let split_x = if can_split_x && can_split_y {
+                // Long leaves are split across so the rooms are not too thin.
+                if width > height {
+                    true
+                } else if height > width {
+                    false
+                } else {
+                    rng.below(2) == 0
+                }
+            } else {
+                can_split_x
+            };
+            let (first, second) = if split_x {
+                let at = rng.range(leaf.min.x + min_leaf, leaf.max.x + 1 - min_leaf);
+                (
+                    Leaf {
+                        min: leaf.min,
+                        max: Point2::new(at - 1, leaf.max.y),
+                    },
+                    Leaf {
+                        min: Point2::new(at, leaf.min.y),
+                        max: leaf.max,
+                    },
+                )
+            } else {
+                let at = rng.range(leaf.min.y + min_leaf, leaf.max.y + 1 - min_leaf);
+                (
+                    Leaf {
+                        min: leaf.min,
+                        max: Point2::new(leaf.max.x, at - 1),
+                    },
+                    Leaf {
+                        min: Point2::new(leaf.min.x, at),
+                        max: leaf.max,
+                    },
+                )
+            };
+            let first = self.split(first, depth + 1, rng, rooms, corridors);
+            let second = self.split(second, depth + 1, rng, rooms, corridors);
+            if let (Some(from), Some(to)) = (first, second) {
+                corridors.push((from, to));
+            }
+            return first.or(second);
+        }
+
+        // Rooms keep a wall between them and the edge of their leaf.
+        let max_width = width - 2;
+        let max_height = height - 2;
+        let min_room = self.min_room as i32;
+        if max_width < min_room || max_height < min_room {
+            return None;
+        }
+        let room_width = rng.range(min_room, max_width);
+        let room_height = rng.range(min_room, max_height);
+        let x = rng.range(leaf.min.x + 1, leaf.max.x - room_width);
+        let y = rng.range(leaf.min.y + 1, leaf.max.y - room_height);
+        let min = Point2::new(x, y);
+        let max = Point2::new(x + room_width - 1, y + room_height - 1);
+        rooms.push((min, max));
+        Some(Point2::new((min.x + max.x) / 2, (min.y + max.y) / 2))
+    }
+}
+
+/// A rectangle of the partition, including both corners.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct Leaf {
+    /// The corner with the lowest coordinates.
+    min: Point2,
+    /// The corner with the highest coordinates.
+    max: Point2,
+}
@@ -0,0 +1,165 @@
+//! Caves grown with cellular automata.
+
+use crate::{generate::GenerateArea, lib::*, tilemap::TilemapResult, Tilemap};
+
+/// Grows caves by filling an area with random walls and smoothing them with
+/// a cellular automaton.
+///
+/// On every step a cell becomes a wall if at least `birth` of its eight
+/// neighbours are walls, and stays a wall if at least `survival` are. Cells
+/// outside of the area count as walls, so the caves are closed off at the
+/// edges, and cells with a tile on the constraint layer are always walls.
+///
+/// # Examples
+/// ```
+/// use bevy_asset::{prelude::*, HandleId};
+/// use bevy_sprite::prelude::*;
+/// use bevy_tilemap::{generate::{cave::CaveGenerator, GenerateArea}, prelude::*};
+///
+/// // In production use a strong handle from an actual source.
+/// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+///
+/// let new_tilemap = || {
+///     TilemapBuilder::new()
+///         .texture_atlas(texture_atlas_handle.clone())
+///         .tile_dimensions(32, 32)
+///         .auto_chunk()
+///         .finish()
+///         .unwrap()
+/// };
+/// let tiles = |tilemap: &mut Tilemap| -> Vec<Option<usize>> {
+///     (0..40 * 30)
+///         .map(|index| tilemap.get_tile((index % 40, index / 40), 0).map(|tile| tile.index))
+///         .collect()
+/// };
+///
+/// let caves = CaveGenerator::new(7, 1);
+/// let area = GenerateArea::new((0, 0), 40, 30, 0);
+/// let mut tilemap = new_tilemap();
+/// caves.generate(&mut tilemap, area, 42).unwrap();
+///
+/// // The edges are closed off.
+/// assert_eq!(tilemap.get_tile((0, 0), 0).map(|tile| tile.index), Some(7));
+///
+/// // The same seed always grows the same caves.
+/// let mut other = new_tilemap();
+/// caves.generate(&mut other, area, 42).unwrap();
+/// assert_eq!(tiles(&mut other), tiles(&mut tilemap));
+///
+/// // A tile on the constraint layer is always a wall.
+/// let floor = tiles(&mut tilemap).iter().position(|cell| *cell == Some(1)).unwrap() as i32;
+/// let rock = (floor % 40, floor / 40);
+/// let mut rocky = new_tilemap();
+/// rocky.insert_tile(Tile { point: rock, z_order: 1, sprite_index: 9, ..Default::default() }).unwrap();
+/// caves.generate(&mut rocky, area.with_constraint(1), 42).unwrap();
+/// assert_eq!(rocky.get_tile(rock, 0).map(|tile| tile.index), Some(7));
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CaveGenerator {
+    /// The sprite index of the walls.
+    pub wall: usize,
+    /// The sprite index of the floors.
+    pub floor: usize,
+    /// The chance for each cell to start as a wall.
+    pub fill: f32,
+    /// The amount of smoothing steps.
+    pub steps: usize,
+    /// The amount of neighbouring walls that turn a floor into a wall.
+    pub birth: usize,
+    /// The amount of neighbouring walls that keep a wall.
+    pub survival: usize,
+}
+
+impl CaveGenerator {
+    /// Constructs a new generator of walls and floors of sprite indices.
+    ///
+    /// It starts out with 45% walls, smoothed over 5 steps where 5
+    /// neighbouring walls grow a wall and 4 keep one.
+    pub fn new(wall: usize, floor: usize) -> CaveGenerator {
+        CaveGenerator {
+            wall,
+            floor,
+            fill: 0.45,
+            steps: 5,
+            birth: 5,
+            survival: 4,
+        }
+    }
+
+    /// Generates caves into an area of a tilemap from a seed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a tile is out of bounds or its chunk does not exist
+    /// and the tilemap does not automatically create chunks.
+    pub fn generate(
+        &self,
+        tilemap: &mut Tilemap,
+        area: GenerateArea,
+        seed: u64,
+    ) -> TilemapResult<()> {
+        let constraints = area.constraints(tilemap);
+        let cells: Vec<Option<usize>> = self
+            .walls(&area, &constraints, seed)
+            .into_iter()
+            .map(|is_wall| Some(if is_wall { self.wall } else { self.floor }))
+            .collect();
+        area.write(tilemap, &cells)
+    }
+
+    /// Returns `true` for every cell of an area that is a wall.
+    fn walls(&self, area: &GenerateArea, constraints: &[Option<usize>], seed: u64) -> Vec<bool> {
+        let width = area.width();
+        let height = area.height();
+        let mut rng = area.rng(seed);
+        let mut walls: Vec<bool> = constraints
+            .iter()
+            .map(|constraint| constraint.is_some() || rng.next_f32() < self.fill)
+            .collect();
+
+        let mut next = walls.clone();
+        for _ in 0..self.steps {
+            for y in 0..height {
+                for x in 0..width {
+                    let index = y * width + x;
+                    let is_fixed = constraints.get(index).map_or(false, Option::is_some);
+                    let neighbours = count_walls(&walls, width, height, x, y);
+                    let is_wall = walls.get(index).copied().unwrap_or(true);
+                    let threshold = if is_wall { self.survival } else { self.birth };
+                    if let Some(cell) = next.get_mut(index) {
+                        *cell = is_fixed || neighbours >= threshold;
+                    }
+                }
+            }
+            mem::swap(&mut walls, &mut next);
+        }
+        walls
+    }
+}
+
+/// Returns the amount of walls around a cell, counting cells outside of the
+/// area as walls.
+fn count_walls(walls: &[bool], width: usize, height: usize, x: usize, y: usize) -> usize {
+    let mut count = 0;
+    for dy in -1..=1_i32 {
+        for dx in -1..=1_i32 {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
+            let nx = x as i32 + dx;
+            let ny = y as i32 + dy;
+            let is_wall = if nx < 0 || ny < 0 || nx >= width as i32 || ny >= height as i32 {
+                true
+            } else {
+                walls
+                    .get(ny as usize * width + nx as usize)
+                    .copied()
+                    .unwrap_or(true)
+            };
+            if is_wall {
+                count += 1;
+            }
+        }
+    }
+    count
+}
@@ -0,0 +1,242 @@
+//! Procedural generation of tiles, enabled with the `generate` feature.
+//!
+//! Every generator writes into a [`GenerateArea`], which is a rectangle of
+//! tiles on a layer of a tilemap. They are seeded, so the same seed and area
+//! always generate the same tiles, while every area gets its own sequence of
+//! random numbers. This makes it easy to generate the tilemap chunk by chunk
+//! as the chunks are loaded, with [`GenerateArea::chunk`].
+//!
+//! An area can have a constraint layer. The tiles that exist on it are kept
+//! out of the generation: they are fixed tiles for wave function collapse,
+//! walls for caves, rock that rooms are not carved through and tiles that
+//! terrain is not generated over.
+//!
+//! * [`wfc`] for wave function collapse with tiled and overlapping models.
+//! * [`cave`] for caves grown with cellular automata.
+//! * [`bsp`] for rooms and corridors laid out with binary space partitioning.
+//! * [`noise`] for terrain from fractal noise for height and moisture.
+//!
+//! # Examples
+//! ```
+//! use bevy_app::prelude::*;
+//! use bevy_ecs::prelude::*;
+//! use bevy_tilemap::{
+//!     event::TilemapChunkEvent,
+//!     generate::{cave::CaveGenerator, GenerateArea},
+//!     prelude::*,
+//! };
+//!
+//! fn generate_caves(
+//!     mut reader: Local<EventReader<TilemapChunkEvent>>,
+//!     events: Res<Events<TilemapChunkEvent>>,
+//!     mut tilemap_query: Query<&mut Tilemap>,
+//! ) {
+//!     let caves = CaveGenerator::new(7, 1);
+//!     for event in reader.iter(&events) {
+//!         if let TilemapChunkEvent::Loaded { tilemap, point } = event {
+//!             if let Ok(mut tilemap) = tilemap_query.get_mut(*tilemap) {
+//!                 let area = GenerateArea::chunk(&tilemap, *point, 0);
+//!                 caves.generate(&mut tilemap, area, 1234).unwrap();
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+pub mod bsp;
+pub mod cave;
+pub mod noise;
+pub mod wfc;
+
+use crate::{lib::*, tile::Tile, tilemap::TilemapResult, Tilemap};
+
+/// A rectangle of tiles on a layer of a tilemap to generate into.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct GenerateArea {
+    /// The bottom left tile point of the area.
+    pub origin: Point2,
+    /// The width and height of the area in tiles.
+    pub dimensions: Dimension2,
+    /// The z order of the layer to generate into.
+    pub z_order: usize,
+    /// The z order of the layer whose tiles constrain the generation, if any.
+    pub constraint: Option<usize>,
+}
+
+impl GenerateArea {
+    /// Constructs a new area of `width` by `height` tiles from a bottom left
+    /// tile point, on the layer of a z order.
+    pub fn new<P: Into<Point2>>(
+        origin: P,
+        width: u32,
+        height: u32,
+        z_order: usize,
+    ) -> GenerateArea {
+        GenerateArea {
+            origin: origin.into(),
+            dimensions: Dimension2::new(width, height),
+            z_order,
+            constraint: None,
+        }
+    }
+
+    /// Constructs a new area of every tile of a chunk, on the layer of a z
+    /// order.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::{generate::GenerateArea, prelude::*};
+    /// use bevy_tilemap_types::point::Point2;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// let area = GenerateArea::chunk(&tilemap, (1, 0), 0);
+    ///
+    /// assert_eq!(area.origin, Point2::new(16, -16));
+    /// assert_eq!(area.dimensions.width, 32);
+    /// ```
+    pub fn chunk<P: Into<Point2>>(
+        tilemap: &Tilemap,
+        chunk_point: P,
+        z_order: usize,
+    ) -> GenerateArea {
+        let chunk_point: Point2 = chunk_point.into();
+        let dimensions = tilemap.chunk_dimensions();
+        let width = dimensions.width as i32;
+        let height = dimensions.height as i32;
+        GenerateArea {
+            origin: Point2::new(
+                width * chunk_point.x - width / 2,
+                height * chunk_point.y - height / 2,
+            ),
+            dimensions,
+            z_order,
+            constraint: None,
+        }
+    }
+
+    /// Sets the layer whose tiles constrain the generation and returns the
+    /// area.
+    pub fn with_constraint(mut self, z_order: usize) -> GenerateArea {
+        self.constraint = Some(z_order);
+        self
+    }
+
+    /// Returns the width of the area.
+    fn width(&self) -> usize {
+        self.dimensions.width as usize
+    }
+
+    /// Returns the height of the area.
+    fn height(&self) -> usize {
+        self.dimensions.height as usize
+    }
+
+    /// Returns the amount of tiles in the area.
+    fn len(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    /// Returns the tile point of a cell index of the area.
+    fn point(&self, index: usize) -> Point2 {
+        let width = self.width().max(1);
+        Point2::new(
+            self.origin.x + (index % width) as i32,
+            self.origin.y + (index / width) as i32,
+        )
+    }
+
+    /// Returns the sprite indices of the tiles on the constraint layer of
+    /// every cell, if the area has one.
+    fn constraints(&self, tilemap: &Tilemap) -> Vec<Option<usize>> {
+        let z_order = if let Some(z_order) = self.constraint {
+            z_order
+        } else {
+            return vec![None; self.len()];
+        };
+        (0..self.len())
+            .map(|index| {
+                tilemap
+                    .raw_tile(self.point(index), z_order)
+                    .map(|tile| tile.index)
+            })
+            .collect()
+    }
+
+    /// Returns a random number generator for the area, so that every area
+    /// gets its own sequence from the same seed.
+    fn rng(&self, seed: u64) -> GenerateRng {
+        GenerateRng::for_point(seed, self.origin)
+    }
+
+    /// Sets the tiles of the area to sprite indices, leaving the cells without
+    /// one as they are.
+    fn write(&self, tilemap: &mut Tilemap, cells: &[Option<usize>]) -> TilemapResult<()> {
+        let z_order = self.z_order;
+        tilemap.insert_tiles(cells.iter().enumerate().filter_map(|(index, cell)| {
+            cell.map(|sprite_index| Tile {
+                point: self.point(index),
+                z_order,
+                sprite_index,
+                ..Default::default()
+            })
+        }))
+    }
+}
+
+/// A small seeded random number generator, the same on every platform.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) struct GenerateRng {
+    /// The state, which is advanced on every number.
+    state: u64,
+}
+
+impl GenerateRng {
+    /// Constructs a new generator from a seed.
+    pub(crate) fn new(seed: u64) -> GenerateRng {
+        GenerateRng { state: seed }
+    }
+
+    /// Constructs a new generator from a seed mixed with a tile point.
+    pub(crate) fn for_point(seed: u64, point: Point2) -> GenerateRng {
+        let x = u64::from(point.x as u32);
+        let y = u64::from(point.y as u32);
+        let mut rng = GenerateRng::new(seed ^ (x << 32 | y));
+        rng.next_u64();
+        rng
+    }
+
+    /// Returns the next random number, with SplitMix64.
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number from 0 up to 1.
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns a random number from 0 up to an exclusive maximum.
+    pub(crate) fn below(&mut self, max: usize) -> usize {
+        if max == 0 {
+            return 0;
+        }
+        (self.next_u64() % max as u64) as usize
+    }
+
+    /// Returns a random number within an inclusive range.
+    pub(crate) fn range(&mut self, min: i32, max: i32) -> i32 {
+        if max <= min {
+            return min;
+        }
+        min + self.below((max - min + 1) as usize) as i32
+    }
+}
@@ -0,0 +1,269 @@
+//! Terrain from fractal noise for height and moisture.
+//!
+//! Noise is sampled at the center of every tile as it is placed by the
+//! topology of the tilemap, so hex tilemaps are not stretched and the terrain
+//! is the same wherever a tile is generated from. Areas, such as chunks, that
+//! are generated separately with the same seed join up seamlessly.
+//!
+//! The height and moisture of a tile are mapped to a sprite index by
+//! [`Biomes`], either with thresholds of height or a lookup table of both.
+
+use crate::{
+    generate::{GenerateArea, GenerateRng},
+    lib::*,
+    tilemap::TilemapResult,
+    Tilemap,
+};
+
+/// Fractal gradient noise, made of octaves of Perlin noise that each add
+/// smaller details.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FractalNoise {
+    /// The seed of the noise.
+    pub seed: u64,
+    /// The frequency of the first octave, in features per tile.
+    pub frequency: f32,
+    /// The amount of octaves.
+    pub octaves: usize,
+    /// How much the frequency grows with every octave.
+    pub lacunarity: f32,
+    /// How much the amplitude shrinks with every octave.
+    pub persistence: f32,
+}
+
+impl FractalNoise {
+    /// Constructs new noise from a seed.
+    ///
+    /// It has a frequency of 1/32, over 4 octaves that each double the
+    /// frequency and halve the amplitude.
+    pub fn new(seed: u64) -> FractalNoise {
+        FractalNoise {
+            seed,
+            frequency: 1.0 / 32.0,
+            octaves: 4,
+            lacunarity: 2.0,
+            persistence: 0.5,
+        }
+    }
+
+    /// Returns the noise at a position in tiles, from 0 to 1.
+    pub fn get(&self, x: f32, y: f32) -> f32 {
+        let mut frequency = self.frequency;
+        let mut amplitude = 1.0;
+        let mut total = 0.0;
+        let mut value = 0.0;
+        for octave in 0..self.octaves.max(1) {
+            let seed = self.seed.wrapping_add(octave as u64);
+            value += perlin(seed, x * frequency, y * frequency) * amplitude;
+            total += amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.persistence;
+        }
+        if total <= 0.0 {
+            return 0.5;
+        }
+        (value / total * 0.5 + 0.5).clamp(0.0, 1.0)
+    }
+}
+
+/// How the height and moisture of a tile, each from 0 to 1, map to a sprite
+/// index.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Biomes {
+    /// Sprite indices with the height they go up to, from the lowest. Heights
+    /// above the last threshold take the last sprite index.
+    Thresholds(Vec<(f32, usize)>),
+    /// Rows of sprite indices from dry to wet, each with columns from low to
+    /// high. Moisture and height are split evenly over the rows and columns.
+    Table(Vec<Vec<usize>>),
+}
+
+impl Biomes {
+    /// Returns the sprite index of a height and moisture, if there are any
+    /// sprite indices.
+    pub fn sprite_index(&self, height: f32, moisture: f32) -> Option<usize> {
+        match self {
+            Biomes::Thresholds(thresholds) => thresholds
+                .iter()
+                .find(|(threshold, _)| height < *threshold)
+                .or_else(|| thresholds.last())
+                .map(|(_, sprite_index)| *sprite_index),
+            Biomes::Table(rows) => {
+                let row = rows.get(band(moisture, rows.len()))?;
+                row.get(band(height, row.len())).copied()
+            }
+        }
+    }
+}
+
+/// Generates terrain from fractal noise for height and moisture.
+///
+/// # Examples
+/// ```
+/// use bevy_asset::{prelude::*, HandleId};
+/// use bevy_sprite::prelude::*;
+/// use bevy_tilemap::{
+///     generate::{noise::{Biomes, TerrainGenerator}, GenerateArea},
+///     prelude::*,
+/// };
+///
+/// // In production use a strong handle from an actual source.
+/// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+///
+/// let new_tilemap = || {
+///     TilemapBuilder::new()
+///         .texture_atlas(texture_atlas_handle.clone())
+///         .chunk_dimensions(16, 16)
+///         .tile_dimensions(32, 32)
+///         .topology(GridTopology::HexY)
+///         .auto_chunk()
+///         .finish()
+///         .unwrap()
+/// };
+///
+/// let (water, sand, grass, forest, rock) = (0, 1, 2, 3, 4);
+/// let terrain = TerrainGenerator::new(
+///     1234,
+///     Biomes::Table(vec![
+///         vec![water, sand, sand, rock],
+///         vec![water, sand, grass, rock],
+///         vec![water, grass, forest, rock],
+///     ]),
+/// );
+///
+/// let mut tilemap = new_tilemap();
+/// terrain.generate(&mut tilemap, GenerateArea::new((0, 0), 32, 32, 0)).unwrap();
+///
+/// // Generating the same tile again always gives the same sprite index.
+/// let index = tilemap.get_tile((5, 5), 0).map(|tile| tile.index);
+/// assert_eq!(index, terrain.sprite_index(&tilemap, (5, 5)));
+///
+/// // Chunks generated one at a time join up as if they were generated at once.
+/// let mut chunks = new_tilemap();
+/// for &point in &[(0, 0), (1, 0)] {
+///     let area = GenerateArea::chunk(&chunks, point, 0);
+///     terrain.generate(&mut chunks, area).unwrap();
+/// }
+/// let mut whole = new_tilemap();
+/// terrain.generate(&mut whole, GenerateArea::new((-8, -8), 32, 16, 0)).unwrap();
+/// for y in -8..8 {
+///     // The last column of the first chunk and the first of the second.
+///     for &x in &[7, 8] {
+///         assert!(chunks.get_tile((x, y), 0).is_some());
+///         assert_eq!(chunks.get_tile((x, y), 0), whole.get_tile((x, y), 0));
+///     }
+///     let (left, _) = terrain.sample(&chunks, (7, y));
+///     let (right, _) = terrain.sample(&chunks, (8, y));
+///     assert!((left - right).abs() < 0.1);
+/// }
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct TerrainGenerator {
+    /// The noise of the height.
+    pub height: FractalNoise,
+    /// The noise of the moisture.
+    pub moisture: FractalNoise,
+    /// How height and moisture map to sprite indices.
+    pub biomes: Biomes,
+}
+
+impl TerrainGenerator {
+    /// Constructs a new generator with height and moisture noise from a seed.
+    pub fn new(seed: u64, biomes: Biomes) -> TerrainGenerator {
+        let mut moisture = FractalNoise::new(GenerateRng::new(seed).next_u64());
+        moisture.frequency = 1.0 / 64.0;
+        TerrainGenerator {
+            height: FractalNoise::new(seed),
+            moisture,
+            biomes,
+        }
+    }
+
+    /// Returns the height and moisture of a tile, each from 0 to 1.
+    pub fn sample<P: Into<Point2>>(&self, tilemap: &Tilemap, point: P) -> (f32, f32) {
+        let translation = tilemap.tile_translation(point);
+        let x = translation.x / tilemap.tile_width().max(1) as f32;
+        let y = translation.y / tilemap.tile_height().max(1) as f32;
+        (self.height.get(x, y), self.moisture.get(x, y))
+    }
+
+    /// Returns the sprite index of a tile, if the biomes have any.
+    pub fn sprite_index<P: Into<Point2>>(&self, tilemap: &Tilemap, point: P) -> Option<usize> {
+        let (height, moisture) = self.sample(tilemap, point);
+        self.biomes.sprite_index(height, moisture)
+    }
+
+    /// Generates terrain into an area of a tilemap.
+    ///
+    /// Cells with a tile on the constraint layer are left as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a tile is out of bounds or its chunk does not exist
+    /// and the tilemap does not automatically create chunks.
+    pub fn generate(&self, tilemap: &mut Tilemap, area: GenerateArea) -> TilemapResult<()> {
+        let constraints = area.constraints(tilemap);
+        let cells: Vec<Option<usize>> = constraints
+            .iter()
+            .enumerate()
+            .map(|(index, constraint)| {
+                if constraint.is_some() {
+                    return None;
+                }
+                self.sprite_index(tilemap, area.point(index))
+            })
+            .collect();
+        area.write(tilemap, &cells)
+    }
+}
+
+/// Returns the band that a value from 0 to 1 falls in, out of an amount of
+/// even bands.
+fn band(value: f32, bands: usize) -> usize {
+    let band = (value.max(0.0) * bands as f32) as usize;
+    band.min(bands.saturating_sub(1))
+}
+
+/// Returns 2D Perlin noise at a position, roughly from -1 to 1.
+fn perlin(seed: u64, x: f32, y: f32) -> f32 {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let (ix, iy) = (x0 as i32, y0 as i32);
+    let (fx, fy) = (x - x0, y - y0);
+    let u = fade(fx);
+    let v = fade(fy);
+
+    let n00 = gradient(seed, ix, iy, fx, fy);
+    let n10 = gradient(seed, ix + 1, iy, fx - 1.0, fy);
+    let n01 = gradient(seed, ix, iy + 1, fx, fy - 1.0);
+    let n11 = gradient(seed, ix + 1, iy + 1, fx - 1.0, fy - 1.0);
+    let bottom = lerp(n00, n10, u);
+    let top = lerp(n01, n11, u);
+    lerp(bottom, top, v)
+}
+
+/// Returns the dot product of the pseudo random gradient of a lattice point
+/// and the offset from it.
+fn gradient(seed: u64, ix: i32, iy: i32, dx: f32, dy: f32) -> f32 {
+    let hash = GenerateRng::for_point(seed, Point2::new(ix, iy)).next_u64();
+    match hash & 7 {
+        0 => dx + dy,
+        1 => dx - dy,
+        2 => -dx + dy,
+        3 => -dx - dy,
+        4 => dx,
+        5 => -dx,
+        6 => dy,
+        _ => -dy,
+    }
+}
+
+/// Eases a fraction so that the noise is smooth across lattice points.
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+/// Linearly interpolates between two values.
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
@@ -0,0 +1,693 @@
+//! Wave function collapse with tiled and overlapping models.
+//!
+//! Wave function collapse fills an area with tiles which all fit together
+//! with their neighbours, by repeatedly placing the tile with the fewest
+//! options left and ruling out everything that no longer fits around it.
+//!
+//! The rules of what fits together come from a model:
+//!
+//! * [`TiledModel`] is a set of weighted tiles with the tiles that each one
+//!   allows next to it, in each direction.
+//! * [`OverlappingModel`] learns patterns of tiles from a small sample grid,
+//!   and fills the area so that every pattern of the area is in the sample.
+//!
+//! Cells with a tile on the constraint layer of the area are fixed to that
+//! sprite index. Tiles that already exist on the layer just outside of the
+//! area constrain the cells at its edges, so areas generated next to each
+//! other, such as chunks, join up seamlessly.
+//!
+//! Both models only have the four directions of a square grid. Hex tilemaps
+//! are generated as if they were square, which works for rules that do not
+//! depend on the direction.
+
+use crate::{
+    generate::{GenerateArea, GenerateRng},
+    lib::*,
+    tilemap::{ErrorKind, TilemapResult},
+    Tilemap,
+};
+
+/// The default amount of times generation is tried before giving up.
+const DEFAULT_ATTEMPTS: usize = 10;
+
+/// The directions between neighbouring cells of a square grid.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Direction {
+    /// Towards positive y.
+    Up,
+    /// Towards positive x.
+    Right,
+    /// Towards negative y.
+    Down,
+    /// Towards negative x.
+    Left,
+}
+
+impl Direction {
+    /// Every direction, in the order of their indices.
+    const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    /// Returns the direction that points the other way.
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+        }
+    }
+
+    /// Returns the offset to the neighbouring cell in the direction.
+    pub fn offset(self) -> Point2 {
+        match self {
+            Direction::Up => Point2::new(0, 1),
+            Direction::Right => Point2::new(1, 0),
+            Direction::Down => Point2::new(0, -1),
+            Direction::Left => Point2::new(-1, 0),
+        }
+    }
+
+    /// Returns the index of the direction.
+    fn index(self) -> usize {
+        match self {
+            Direction::Up => 0,
+            Direction::Right => 1,
+            Direction::Down => 2,
+            Direction::Left => 3,
+        }
+    }
+}
+
+/// A model of weighted tiles and which tiles are allowed next to each other.
+///
+/// # Examples
+/// ```
+/// use bevy_asset::{prelude::*, HandleId};
+/// use bevy_sprite::prelude::*;
+/// use bevy_tilemap::{
+///     generate::{wfc::{Direction, TiledModel}, GenerateArea},
+///     prelude::*,
+/// };
+///
+/// // In production use a strong handle from an actual source.
+/// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+///
+/// let new_tilemap = || {
+///     TilemapBuilder::new()
+///         .texture_atlas(texture_atlas_handle.clone())
+///         .tile_dimensions(32, 32)
+///         .auto_chunk()
+///         .finish()
+///         .unwrap()
+/// };
+/// let tiles = |tilemap: &mut Tilemap| -> Vec<Option<usize>> {
+///     (0..16 * 16)
+///         .map(|index| tilemap.get_tile((index % 16, index / 16), 0).map(|tile| tile.index))
+///         .collect()
+/// };
+///
+/// // Water never touches mountains, there is always grass in between.
+/// let (water, grass, mountain) = (0, 1, 2);
+/// let mut model = TiledModel::new();
+/// model.add_tile(water, 2.0);
+/// model.add_tile(grass, 3.0);
+/// model.add_tile(mountain, 1.0);
+/// for &direction in &[Direction::Up, Direction::Right] {
+///     model.allow(water, water, direction);
+///     model.allow(water, grass, direction);
+///     model.allow(grass, grass, direction);
+///     model.allow(grass, mountain, direction);
+///     model.allow(mountain, mountain, direction);
+/// }
+///
+/// let area = GenerateArea::new((0, 0), 16, 16, 0);
+/// let mut tilemap = new_tilemap();
+/// model.generate(&mut tilemap, area, 42).unwrap();
+/// let cells = tiles(&mut tilemap);
+/// assert!(cells.iter().all(Option::is_some));
+///
+/// // The same seed always generates the same tiles.
+/// let mut other = new_tilemap();
+/// model.generate(&mut other, area, 42).unwrap();
+/// assert_eq!(tiles(&mut other), cells);
+///
+/// // A mountain on the constraint layer is kept, with grass around it.
+/// let mut constrained = new_tilemap();
+/// constrained
+///     .insert_tile(Tile { point: (8, 8), z_order: 1, sprite_index: mountain, ..Default::default() })
+///     .unwrap();
+/// model.generate(&mut constrained, area.with_constraint(1), 42).unwrap();
+/// assert_eq!(constrained.get_tile((8, 8), 0).map(|tile| tile.index), Some(mountain));
+/// for &point in &[(7, 8), (9, 8), (8, 7), (8, 9)] {
+///     assert_ne!(constrained.get_tile(point, 0).map(|tile| tile.index), Some(water));
+/// }
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct TiledModel {
+    /// The sprite indices of the tiles with their weights.
+    tiles: Vec<(usize, f32)>,
+    /// The tiles that are allowed in each direction of a tile.
+    rules: HashSet<(usize, usize, Direction)>,
+    /// The amount of times generation is tried before giving up.
+    attempts: usize,
+}
+
+impl Default for TiledModel {
+    fn default() -> TiledModel {
+        TiledModel::new()
+    }
+}
+
+impl TiledModel {
+    /// Constructs a new empty model.
+    pub fn new() -> TiledModel {
+        TiledModel {
+            tiles: Vec::new(),
+            rules: HashSet::default(),
+            attempts: DEFAULT_ATTEMPTS,
+        }
+    }
+
+    /// Adds a tile of a sprite index with a weight, where tiles with a higher
+    /// weight are placed more often.
+    ///
+    /// Adding a tile again replaces its weight.
+    pub fn add_tile(&mut self, sprite_index: usize, weight: f32) {
+        if let Some(tile) = self.tiles.iter_mut().find(|tile| tile.0 == sprite_index) {
+            tile.1 = weight;
+        } else {
+            self.tiles.push((sprite_index, weight));
+        }
+    }
+
+    /// Allows the tile of sprite index `to` in a direction of the tile of
+    /// sprite index `from`, and the other way around.
+    pub fn allow(&mut self, from: usize, to: usize, direction: Direction) {
+        self.rules.insert((from, to, direction));
+        self.rules.insert((to, from, direction.opposite()));
+    }
+
+    /// Sets the amount of times generation is tried before giving up, which
+    /// is 10 by default.
+    pub fn set_attempts(&mut self, attempts: usize) {
+        self.attempts = attempts;
+    }
+
+    /// Generates tiles into an area of a tilemap from a seed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no tiles fit together on any attempt, if a tile is
+    /// out of bounds or if its chunk does not exist and the tilemap does not
+    /// automatically create chunks.
+    pub fn generate(
+        &self,
+        tilemap: &mut Tilemap,
+        area: GenerateArea,
+        seed: u64,
+    ) -> TilemapResult<()> {
+        let patterns = Patterns {
+            sprites: self.tiles.iter().map(|tile| tile.0).collect(),
+            weights: self.tiles.iter().map(|tile| tile.1).collect(),
+            allowed: self
+                .tiles
+                .iter()
+                .map(|&(from, _)| {
+                    let mut allowed: [Vec<bool>; 4] = Default::default();
+                    for &direction in &Direction::ALL {
+                        if let Some(row) = allowed.get_mut(direction.index()) {
+                            *row = self
+                                .tiles
+                                .iter()
+                                .map(|&(to, _)| self.rules.contains(&(from, to, direction)))
+                                .collect();
+                        }
+                    }
+                    allowed
+                })
+                .collect(),
+        };
+        patterns.generate(tilemap, area, seed, self.attempts)
+    }
+}
+
+/// A model of square patterns of tiles learned from a sample, where each
+/// cell of the area takes the bottom left tile of a pattern.
+///
+/// # Examples
+/// ```
+/// use bevy_asset::{prelude::*, HandleId};
+/// use bevy_sprite::prelude::*;
+/// use bevy_tilemap::{generate::{wfc::OverlappingModel, GenerateArea}, prelude::*};
+///
+/// // In production use a strong handle from an actual source.
+/// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+///
+/// let new_tilemap = || {
+///     TilemapBuilder::new()
+///         .texture_atlas(texture_atlas_handle.clone())
+///         .tile_dimensions(32, 32)
+///         .auto_chunk()
+///         .finish()
+///         .unwrap()
+/// };
+/// let tiles = |tilemap: &mut Tilemap| -> Vec<Option<usize>> {
+///     (0..12 * 12)
+///         .map(|index| tilemap.get_tile((index % 12, index / 12), 0).map(|tile| tile.index))
+///         .collect()
+/// };
+///
+/// // Rows from top to bottom, as they read.
+/// let sample = vec![
+///     vec![0, 0, 0, 0, 0],
+///     vec![0, 1, 1, 1, 0],
+///     vec![0, 1, 2, 1, 0],
+///     vec![0, 1, 1, 1, 0],
+///     vec![0, 0, 0, 0, 0],
+/// ];
+/// let model = OverlappingModel::new(&sample, 2);
+///
+/// let area = GenerateArea::new((0, 0), 12, 12, 0);
+/// let mut tilemap = new_tilemap();
+/// model.generate(&mut tilemap, area, 7).unwrap();
+/// let cells = tiles(&mut tilemap);
+/// assert!(cells.iter().all(|cell| matches!(cell, Some(0..=2))));
+///
+/// // The same seed always generates the same tiles.
+/// let mut other = new_tilemap();
+/// model.generate(&mut other, area, 7).unwrap();
+/// assert_eq!(tiles(&mut other), cells);
+///
+/// // A tile on the constraint layer is kept, and a 2 is always within 1s.
+/// let mut constrained = new_tilemap();
+/// constrained
+///     .insert_tile(Tile { point: (5, 5), z_order: 1, sprite_index: 2, ..Default::default() })
+///     .unwrap();
+/// model.generate(&mut constrained, area.with_constraint(1), 7).unwrap();
+/// assert_eq!(constrained.get_tile((5, 5), 0).map(|tile| tile.index), Some(2));
+/// assert_eq!(constrained.get_tile((6, 5), 0).map(|tile| tile.index), Some(1));
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct OverlappingModel {
+    /// The patterns, as rows of sprite indices from the bottom up.
+    patterns: Vec<Vec<usize>>,
+    /// The amount of times each pattern is in the sample.
+    weights: Vec<f32>,
+    /// The width and height of the patterns.
+    size: usize,
+    /// The amount of times generation is tried before giving up.
+    attempts: usize,
+}
+
+impl OverlappingModel {
+    /// Constructs a new model from the patterns of a size in a sample grid of
+    /// sprite indices, with rows from top to bottom.
+    ///
+    /// Patterns wrap around the edges of the sample, so a sample that tiles
+    /// seamlessly makes an area that does too.
+    pub fn new(sample: &[Vec<usize>], size: usize) -> OverlappingModel {
+        let size = size.max(1);
+        let height = sample.len();
+        let width = sample.iter().map(Vec::len).min().unwrap_or(0);
+        let sprite = |x: usize, y: usize| -> usize {
+            // The sample is flipped so that y goes up like the tilemap.
+            sample
+                .get(height - 1 - y % height)
+                .and_then(|row| row.get(x % width))
+                .copied()
+                .unwrap_or(0)
+        };
+
+        let mut patterns: Vec<Vec<usize>> = Vec::new();
+        let mut weights: Vec<f32> = Vec::new();
+        let mut indices: HashMap<Vec<usize>, usize> = HashMap::default();
+        if width > 0 && height > 0 {
+            for y in 0..height {
+                for x in 0..width {
+                    let mut pattern = Vec::with_capacity(size * size);
+                    for dy in 0..size {
+                        for dx in 0..size {
+                            pattern.push(sprite(x + dx, y + dy));
+                        }
+                    }
+                    if let Some(&index) = indices.get(&pattern) {
+                        if let Some(weight) = weights.get_mut(index) {
+                            *weight += 1.0;
+                        }
+                    } else {
+                        indices.insert(pattern.clone(), patterns.len());
+                        patterns.push(pattern);
+                        weights.push(1.0);
+                    }
+                }
+            }
+        }
+
+        OverlappingModel {
+            patterns,
+            weights,
+            size,
+            attempts: DEFAULT_ATTEMPTS,
+        }
+    }
+
+    /// Returns the amount of distinct patterns in the sample.
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Returns `true` if the sample had no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Sets the amount of times generation is tried before giving up, which
+    /// is 10 by default.
+    pub fn set_attempts(&mut self, attempts: usize) {
+        self.attempts = attempts;
+    }
+
+    /// Generates tiles into an area of a tilemap from a seed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no patterns fit together on any attempt, if a tile
+    /// is out of bounds or if its chunk does not exist and the tilemap does
+    /// not automatically create chunks.
+    pub fn generate(
+        &self,
+        tilemap: &mut Tilemap,
+        area: GenerateArea,
+        seed: u64,
+    ) -> TilemapResult<()> {
+        let patterns = Patterns {
+            sprites: self
+                .patterns
+                .iter()
+                .map(|pattern| pattern.first().copied().unwrap_or(0))
+                .collect(),
+            weights: self.weights.clone(),
+            allowed: self
+                .patterns
+                .iter()
+                .map(|from| {
+                    let mut allowed: [Vec<bool>; 4] = Default::default();
+                    for &direction in &Direction::ALL {
+                        if let Some(row) = allowed.get_mut(direction.index()) {
+                            *row = self
+                                .patterns
+                                .iter()
+                                .map(|to| self.overlaps(from, to, direction.offset()))
+                                .collect();
+                        }
+                    }
+                    allowed
+                })
+                .collect(),
+        };
+        patterns.generate(tilemap, area, seed, self.attempts)
+    }
+
+    /// Returns `true` if a pattern agrees with another one placed at an
+    /// offset from it, where they overlap.
+    fn overlaps(&self, from: &[usize], to: &[usize], offset: Point2) -> bool {
+        let size = self.size as i32;
+        for y in 0..size {
+            for x in 0..size {
+                let tx = x - offset.x;
+                let ty = y - offset.y;
+                if tx < 0 || ty < 0 || tx >= size || ty >= size {
+                    continue;
+                }
+                let from = from.get((y * size + x) as usize);
+                let to = to.get((ty * size + tx) as usize);
+                if from != to {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// The patterns shared by both models, which the solver works with.
+struct Patterns {
+    /// The sprite index each pattern places in its cell.
+    sprites: Vec<usize>,
+    /// The weight of each pattern.
+    weights: Vec<f32>,
+    /// If each pattern allows each other pattern, in each direction of each
+    /// pattern, so that a pair is checked without searching.
+    allowed: Vec<[Vec<bool>; 4]>,
+}
+
+impl Patterns {
+    /// Returns the amount of patterns.
+    fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    /// Returns which patterns are allowed in a direction of a cell, by any of
+    /// the patterns that are possible in it.
+    fn supported(&self, possible: &[bool], direction: Direction) -> Vec<bool> {
+        let mut supported = vec![false; self.len()];
+        for (from, _) in possible
+            .iter()
+            .enumerate()
+            .filter(|&(_, &is_possible)| is_possible)
+        {
+            let row = match self
+                .allowed
+                .get(from)
+                .and_then(|allowed| allowed.get(direction.index()))
+            {
+                Some(row) => row,
+                None => continue,
+            };
+            for (is_supported, &is_allowed) in supported.iter_mut().zip(row.iter()) {
+                *is_supported = *is_supported || is_allowed;
+            }
+        }
+        supported
+    }
+
+    /// Generates the patterns into an area, retrying with new random numbers
+    /// after a contradiction.
+    fn generate(
+        &self,
+        tilemap: &mut Tilemap,
+        area: GenerateArea,
+        seed: u64,
+        attempts: usize,
+    ) -> TilemapResult<()> {
+        let initial = self.initial_wave(tilemap, &area);
+        let mut rng = area.rng(seed);
+        for _ in 0..attempts.max(1) {
+            let mut wave = initial.clone();
+            if let Some(cells) = self.solve(&mut wave, &area, &mut rng) {
+                return area.write(tilemap, &cells);
+            }
+        }
+        Err(ErrorKind::GenerationFailed(attempts.max(1)).into())
+    }
+
+    /// Returns the patterns that are possible in every cell before anything
+    /// is placed, narrowed down by the constraint layer and the tiles around
+    /// the area.
+    fn initial_wave(&self, tilemap: &Tilemap, area: &GenerateArea) -> Vec<Vec<bool>> {
+        let constraints = area.constraints(tilemap);
+        let width = area.width() as i32;
+        let height = area.height() as i32;
+        let mut wave = Vec::with_capacity(area.len());
+        for index in 0..area.len() {
+            let x = index as i32 % width.max(1);
+            let y = index as i32 / width.max(1);
+            let constraint = constraints.get(index).copied().flatten();
+            let mut possible: Vec<bool> = self
+                .sprites
+                .iter()
+                .map(|&sprite| constraint.map_or(true, |constraint| constraint == sprite))
+                .collect();
+
+            for &direction in &Direction::ALL {
+                let offset = direction.offset();
+                let (nx, ny) = (x + offset.x, y + offset.y);
+                if nx >= 0 && ny >= 0 && nx < width && ny < height {
+                    continue;
+                }
+                let point = area.point(index) + offset;
+                let sprite = match tilemap.raw_tile(point, area.z_order) {
+                    Some(tile) => tile.index,
+                    None => continue,
+                };
+                let outside: Vec<bool> =
+                    self.sprites.iter().map(|&other| other == sprite).collect();
+                let supported = self.supported(&outside, direction.opposite());
+                for (is_possible, &is_supported) in possible.iter_mut().zip(supported.iter()) {
+                    *is_possible = *is_possible && is_supported;
+                }
+            }
+            wave.push(possible);
+        }
+        wave
+    }
+
+    /// Collapses every cell of the wave to a single pattern, returning the
+    /// sprite indices of the cells or `None` on a contradiction.
+    fn solve(
+        &self,
+        wave: &mut [Vec<bool>],
+        area: &GenerateArea,
+        rng: &mut GenerateRng,
+    ) -> Option<Vec<Option<usize>>> {
+        let width = area.width();
+        let height = area.height();
+        let all: Vec<usize> = (0..wave.len()).collect();
+        if !self.propagate(wave, width, height, all) {
+            return None;
+        }
+
+        loop {
+            // Observe the cell with the lowest entropy that is not decided.
+            let mut lowest: Option<(usize, f32)> = None;
+            for (index, possible) in wave.iter().enumerate() {
+                let count = possible.iter().filter(|&&is_possible| is_possible).count();
+                if count == 0 {
+                    return None;
+                }
+                if count == 1 {
+                    continue;
+                }
+                let entropy = self.entropy(possible) + rng.next_f32() * 1e-3;
+                if lowest.map_or(true, |(_, lowest)| entropy < lowest) {
+                    lowest = Some((index, entropy));
+                }
+            }
+            let index = match lowest {
+                Some((index, _)) => index,
+                None => break,
+            };
+
+            let possible = wave.get_mut(index)?;
+            let total: f32 = possible
+                .iter()
+                .zip(self.weights.iter())
+                .filter(|&(&is_possible, _)| is_possible)
+                .map(|(_, &weight)| weight)
+                .sum();
+            let mut pick = rng.next_f32() * total;
+            let mut chosen = None;
+            for (pattern, (&is_possible, &weight)) in
+                possible.iter().zip(self.weights.iter()).enumerate()
+            {
+                if !is_possible {
+                    continue;
+                }
+                chosen = Some(pattern);
+                if pick < weight {
+                    break;
+                }
+                pick -= weight;
+            }
+            let chosen = chosen?;
+            for (pattern, is_possible) in possible.iter_mut().enumerate() {
+                *is_possible = pattern == chosen;
+            }
+
+            if !self.propagate(wave, width, height, vec![index]) {
+                return None;
+            }
+        }
+
+        Some(
+            wave.iter()
+                .map(|possible| {
+                    possible
+                        .iter()
+                        .position(|&is_possible| is_possible)
+                        .and_then(|pattern| self.sprites.get(pattern).copied())
+                })
+                .collect(),
+        )
+    }
+
+    /// Rules out the patterns of the neighbours of changed cells that no
+    /// longer fit, until nothing changes, returning `false` if a cell has no
+    /// patterns left.
+    fn propagate(
+        &self,
+        wave: &mut [Vec<bool>],
+        width: usize,
+        height: usize,
+        mut stack: Vec<usize>,
+    ) -> bool {
+        while let Some(index) = stack.pop() {
+            let x = (index % width) as i32;
+            let y = (index / width) as i32;
+            // The patterns each neighbour may keep, which only depend on the
+            // changed cell.
+            let supported: Vec<Vec<bool>> = match wave.get(index) {
+                Some(possible) => Direction::ALL
+                    .iter()
+                    .map(|&direction| self.supported(possible, direction))
+                    .collect(),
+                None => continue,
+            };
+            for (&direction, supported) in Direction::ALL.iter().zip(supported.iter()) {
+                let offset = direction.offset();
+                let (nx, ny) = (x + offset.x, y + offset.y);
+                if nx < 0 || ny < 0 || nx >= width as i32 || ny >= height as i32 {
+                    continue;
+                }
+                let neighbour = ny as usize * width + nx as usize;
+                let neighbour_possible = match wave.get_mut(neighbour) {
+                    Some(neighbour_possible) => neighbour_possible,
+                    None => continue,
+                };
+                let mut changed = false;
+                let mut any = false;
+                for (is_possible, &is_supported) in
+                    neighbour_possible.iter_mut().zip(supported.iter())
+                {
+                    if !*is_possible {
+                        continue;
+                    }
+                    if is_supported {
+                        any = true;
+                    } else {
+                        *is_possible = false;
+                        changed = true;
+                    }
+                }
+                if !any {
+                    return false;
+                }
+                if changed {
+                    stack.push(neighbour);
+                }
+            }
+        }
+        true
+    }
+
+    /// Returns the Shannon entropy of the weights of the possible patterns.
+    fn entropy(&self, possible: &[bool]) -> f32 {
+        let mut total = 0.0;
+        let mut total_log = 0.0;
+        for (&is_possible, &weight) in possible.iter().zip(self.weights.iter()) {
+            if is_possible && weight > 0.0 {
+                total += weight;
+                total_log += weight * weight.ln();
+            }
+        }
+        if total <= 0.0 {
+            return 0.0;
+        }
+        total.ln() - total_log / total
+    }
+}
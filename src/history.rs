@@ -0,0 +1,338 @@
+//! Undo and redo of tile edits.
+//!
+//! Once a tilemap has a [`TilemapHistory`], every change made with
+//! [`insert_tiles`], [`clear_tiles`] and [`get_tile_mut`] records the tiles as
+//! they were before. Changes are grouped into named transactions with
+//! [`begin_transaction`] and [`commit_transaction`], such as all the tiles of
+//! a single brush stroke. Changes made outside of a transaction are each a
+//! transaction of their own.
+//!
+//! [`undo`] puts the tiles of the last transaction back as they were and
+//! [`redo`] applies it again. Both go through the same methods as any other
+//! edit, so the chunks are modified with the same events.
+//!
+//! The history is bounded by an amount of transactions to undo and to redo
+//! each, and an estimate of the memory that the recorded tiles take, dropping
+//! the transactions to redo first and then the oldest transactions to undo.
+//!
+//! # Examples
+//! ```
+//! use bevy_asset::{prelude::*, HandleId};
+//! use bevy_sprite::prelude::*;
+//! use bevy_tilemap::{history::TilemapHistory, prelude::*};
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! let mut tilemap = TilemapBuilder::new()
+//!     .texture_atlas(texture_atlas_handle)
+//!     .tile_dimensions(32, 32)
+//!     .auto_chunk()
+//!     .history(TilemapHistory::default())
+//!     .finish()
+//!     .unwrap();
+//!
+//! tilemap.begin_transaction("Paint");
+//! tilemap.insert_tile(Tile { point: (1, 1), sprite_index: 3, ..Default::default() }).unwrap();
+//! tilemap.insert_tile(Tile { point: (2, 1), sprite_index: 3, ..Default::default() }).unwrap();
+//! tilemap.commit_transaction();
+//!
+//! assert_eq!(tilemap.undo().unwrap(), Some("Paint".to_string()));
+//! assert_eq!(tilemap.get_tile((1, 1), 0).map(|tile| tile.index), None);
+//! assert_eq!(tilemap.get_tile((2, 1), 0).map(|tile| tile.index), None);
+//! assert_eq!(tilemap.undo().unwrap(), None);
+//!
+//! assert_eq!(tilemap.redo().unwrap(), Some("Paint".to_string()));
+//! assert_eq!(tilemap.get_tile((1, 1), 0).map(|tile| tile.index), Some(3));
+//! assert_eq!(tilemap.get_tile((2, 1), 0).map(|tile| tile.index), Some(3));
+//! assert_eq!(tilemap.redo().unwrap(), None);
+//! ```
+//!
+//! [`insert_tiles`]: crate::tilemap::Tilemap::insert_tiles
+//! [`clear_tiles`]: crate::tilemap::Tilemap::clear_tiles
+//! [`get_tile_mut`]: crate::tilemap::Tilemap::get_tile_mut
+//! [`begin_transaction`]: crate::tilemap::Tilemap::begin_transaction
+//! [`commit_transaction`]: crate::tilemap::Tilemap::commit_transaction
+//! [`undo`]: crate::tilemap::Tilemap::undo
+//! [`redo`]: crate::tilemap::Tilemap::redo
+
+use crate::{chunk::RawTile, lib::*};
+
+/// The default maximum amount of transactions kept to undo.
+const DEFAULT_MAX_TRANSACTIONS: usize = 100;
+/// The default maximum amount of bytes that the recorded tiles may take.
+const DEFAULT_MEMORY_BUDGET: usize = 16 * 1024 * 1024;
+
+/// A tile as it was at a point and layer, where `None` means it did not exist.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) struct TileEdit {
+    /// The global tile point.
+    pub point: Point2,
+    /// The z order of the layer.
+    pub z_order: usize,
+    /// The tile, if there was one.
+    pub tile: Option<RawTile>,
+}
+
+/// A named group of tile edits that is undone and redone at once.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Transaction {
+    /// The name of the transaction.
+    pub name: String,
+    /// The tiles as they were before the transaction, once per point and
+    /// layer.
+    pub edits: Vec<TileEdit>,
+    /// The points and layers that were already recorded.
+    recorded: HashSet<(Point2, usize)>,
+    /// If the transaction was started by an edit outside of a transaction.
+    implicit: bool,
+}
+
+impl Transaction {
+    /// Constructs a new empty transaction.
+    fn new(name: String, implicit: bool) -> Transaction {
+        Transaction {
+            name,
+            edits: Vec::new(),
+            recorded: HashSet::default(),
+            implicit,
+        }
+    }
+
+    /// Constructs a transaction which restores a list of edits.
+    pub(crate) fn from_edits(name: String, edits: Vec<TileEdit>) -> Transaction {
+        Transaction {
+            name,
+            edits,
+            recorded: HashSet::default(),
+            implicit: false,
+        }
+    }
+
+    /// Returns an estimate of the bytes that the transaction takes.
+    fn memory(&self) -> usize {
+        self.name.len() + self.edits.len() * size_of::<TileEdit>()
+    }
+}
+
+/// The undo and redo history of the tile edits of a tilemap.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TilemapHistory {
+    /// The maximum amount of transactions kept to undo.
+    max_transactions: usize,
+    /// The maximum amount of bytes that the recorded tiles may take.
+    memory_budget: usize,
+    /// The transactions that can be undone, oldest first.
+    undo: VecDeque<Transaction>,
+    /// The transactions that can be redone, most recently undone last.
+    redo: Vec<Transaction>,
+    /// The transaction that edits are currently recorded into.
+    current: Option<Transaction>,
+}
+
+impl Default for TilemapHistory {
+    fn default() -> TilemapHistory {
+        TilemapHistory::new(DEFAULT_MAX_TRANSACTIONS, DEFAULT_MEMORY_BUDGET)
+    }
+}
+
+impl TilemapHistory {
+    /// Constructs a new history which keeps up to an amount of transactions
+    /// to undo and to redo each, and an estimated amount of bytes of recorded
+    /// tiles.
+    ///
+    /// By default this is 100 transactions and 16 MiB. At least the newest
+    /// transaction to undo and to redo is kept, even past the memory budget.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::{history::TilemapHistory, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.set_history(Some(TilemapHistory::new(2, 16 * 1024)));
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// for sprite_index in 1..=3 {
+    ///     tilemap.insert_tile(Tile { point: (0, 0), sprite_index, ..Default::default() }).unwrap();
+    /// }
+    /// assert_eq!(tilemap.history().unwrap().undo_len(), 2);
+    ///
+    /// // The oldest transaction was dropped, so its tile stays.
+    /// assert!(tilemap.undo().unwrap().is_some());
+    /// assert!(tilemap.undo().unwrap().is_some());
+    /// assert!(tilemap.undo().unwrap().is_none());
+    /// assert_eq!(tilemap.get_tile((0, 0), 0).map(|tile| tile.index), Some(1));
+    /// assert_eq!(tilemap.history().unwrap().redo_len(), 2);
+    ///
+    /// // Without any memory to spare only the newest transactions are kept.
+    /// tilemap.set_history(Some(TilemapHistory::new(100, 0)));
+    /// for sprite_index in 4..=6 {
+    ///     tilemap.insert_tile(Tile { point: (0, 0), sprite_index, ..Default::default() }).unwrap();
+    /// }
+    /// assert_eq!(tilemap.history().unwrap().undo_len(), 1);
+    /// assert!(tilemap.undo().unwrap().is_some());
+    /// assert_eq!(tilemap.history().unwrap().undo_len(), 0);
+    /// assert_eq!(tilemap.history().unwrap().redo_len(), 1);
+    /// ```
+    pub fn new(max_transactions: usize, memory_budget: usize) -> TilemapHistory {
+        TilemapHistory {
+            max_transactions,
+            memory_budget,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            current: None,
+        }
+    }
+
+    /// Returns the name of the transaction that would be undone next.
+    pub fn next_undo(&self) -> Option<&str> {
+        self.undo
+            .back()
+            .map(|transaction| transaction.name.as_str())
+    }
+
+    /// Returns the name of the transaction that would be redone next.
+    pub fn next_redo(&self) -> Option<&str> {
+        self.redo
+            .last()
+            .map(|transaction| transaction.name.as_str())
+    }
+
+    /// Returns the amount of transactions that can be undone.
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Returns the amount of transactions that can be redone.
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Returns an estimate of the bytes that the recorded tiles take.
+    pub fn memory(&self) -> usize {
+        self.undo
+            .iter()
+            .chain(self.redo.iter())
+            .map(|transaction| transaction.memory())
+            .sum()
+    }
+
+    /// Clears every transaction.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.current = None;
+    }
+
+    /// Starts a named transaction, committing the one before it.
+    pub(crate) fn begin(&mut self, name: String) {
+        self.commit();
+        self.current = Some(Transaction::new(name, false));
+    }
+
+    /// Starts a transaction for a single edit if no transaction is started,
+    /// returning `true` if it did.
+    ///
+    /// A transaction of a single edit that was left open by an error is
+    /// committed first.
+    pub(crate) fn begin_implicit(&mut self, name: &str) -> bool {
+        if self
+            .current
+            .as_ref()
+            .map_or(false, |transaction| transaction.implicit)
+        {
+            self.commit();
+        }
+        if self.current.is_some() {
+            return false;
+        }
+        self.current = Some(Transaction::new(String::from(name), true));
+        true
+    }
+
+    /// Records a tile as it was before it is changed, if it was not already
+    /// recorded in the current transaction.
+    pub(crate) fn record(&mut self, point: Point2, z_order: usize, tile: Option<RawTile>) {
+        if let Some(transaction) = self.current.as_mut() {
+            if transaction.recorded.insert((point, z_order)) {
+                transaction.edits.push(TileEdit {
+                    point,
+                    z_order,
+                    tile,
+                });
+            }
+        }
+    }
+
+    /// Commits the current transaction, if it has any edits.
+    ///
+    /// This clears the transactions that can be redone.
+    pub(crate) fn commit(&mut self) {
+        let mut transaction = if let Some(transaction) = self.current.take() {
+            transaction
+        } else {
+            return;
+        };
+        if transaction.edits.is_empty() {
+            return;
+        }
+        // The recorded points are only needed while recording.
+        transaction.recorded = HashSet::default();
+        self.redo.clear();
+        self.push_undo(transaction);
+    }
+
+    /// Takes the transaction to undo next.
+    pub(crate) fn pop_undo(&mut self) -> Option<Transaction> {
+        self.commit();
+        self.undo.pop_back()
+    }
+
+    /// Takes the transaction to redo next.
+    pub(crate) fn pop_redo(&mut self) -> Option<Transaction> {
+        self.commit();
+        self.redo.pop()
+    }
+
+    /// Adds a transaction that can be undone, dropping the oldest ones past
+    /// the bounds.
+    pub(crate) fn push_undo(&mut self, transaction: Transaction) {
+        self.undo.push_back(transaction);
+        self.enforce_bounds();
+    }
+
+    /// Adds a transaction that can be redone.
+    pub(crate) fn push_redo(&mut self, transaction: Transaction) {
+        self.redo.push(transaction);
+        self.enforce_bounds();
+    }
+
+    /// Drops transactions until the history is within its bounds, keeping at
+    /// least the newest one to undo and to redo.
+    ///
+    /// Transactions to redo are dropped first, those which would be redone
+    /// last first, followed by the oldest transactions to undo.
+    fn enforce_bounds(&mut self) {
+        let max_transactions = self.max_transactions.max(1);
+        while self.undo.len() > max_transactions {
+            self.undo.pop_front();
+        }
+        if self.redo.len() > max_transactions {
+            let excess = self.redo.len() - max_transactions;
+            self.redo.drain(..excess);
+        }
+        let mut memory = self.memory();
+        while memory > self.memory_budget && self.redo.len() > 1 {
+            memory -= self.redo.remove(0).memory();
+        }
+        while memory > self.memory_budget && self.undo.len() > 1 {
+            if let Some(transaction) = self.undo.pop_front() {
+                memory -= transaction.memory();
+            }
+        }
+    }
+}
@@ -89,15 +89,40 @@ pub use bevy_tilemap_types::dimension;
 #[doc(inline)]
 pub use bevy_tilemap_types::point;
 
+#[no_implicit_prelude]
+pub mod camera;
 #[no_implicit_prelude]
 pub mod chunk;
 #[no_implicit_prelude]
 pub mod default_plugin;
 #[no_implicit_prelude]
 pub mod entity;
+#[cfg(feature = "generate")]
+#[no_implicit_prelude]
+pub mod generate;
+#[no_implicit_prelude]
+pub mod history;
+#[no_implicit_prelude]
+pub mod light;
+#[no_implicit_prelude]
+pub mod material;
+#[no_implicit_prelude]
+pub mod minimap;
+#[no_implicit_prelude]
+pub mod palette;
+#[no_implicit_prelude]
+pub mod position;
+#[no_implicit_prelude]
+pub mod prefab;
 #[no_implicit_prelude]
 pub mod prelude;
 #[no_implicit_prelude]
+pub mod raster;
+#[no_implicit_prelude]
+pub mod region;
+#[no_implicit_prelude]
+pub mod stamp;
+#[no_implicit_prelude]
 pub mod stage {
     //! The stages for the tilemap in the bevy app.
 
@@ -113,7 +138,15 @@ pub mod tile;
 #[no_implicit_prelude]
 pub mod tilemap;
 
-use crate::{chunk::render::TilemapRenderGraphBuilder, event::TilemapChunkEvent, lib::*};
+use crate::{
+    camera::ChunkVisibilityStats,
+    chunk::render::TilemapRenderGraphBuilder,
+    event::{RegionEvent, TileChanged, TilemapChunkEvent},
+    lib::*,
+    material::TilemapMaterialPlugin,
+    palette::TilemapPalette,
+    stamp::TileStamp,
+};
 pub use crate::{
     tile::Tile,
     tilemap::{Tilemap, TilemapLayer},
@@ -126,40 +159,83 @@ pub struct Tilemap2DPlugin;
 impl Plugin for Tilemap2DPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_asset::<Tilemap>()
+            .add_asset::<TilemapPalette>()
+            .add_asset::<TileStamp>()
+            .add_event::<RegionEvent>()
+            .add_event::<TileChanged>()
+            .add_event::<TilemapChunkEvent>()
+            .init_resource::<ChunkVisibilityStats>()
             .add_stage_before(
                 app_stage::POST_UPDATE,
                 stage::TILEMAP,
                 SystemStage::parallel(),
             )
             .add_system_to_stage(stage::TILEMAP, crate::system::tilemap_events.system())
+            .add_system_to_stage(stage::TILEMAP, crate::prefab::tile_prefabs.system())
+            .add_system_to_stage(stage::TILEMAP, crate::system::tile_change_events.system())
             .add_system_to_stage(stage::TILEMAP, crate::chunk::system::chunk_update.system())
             .add_system_to_stage(
                 stage::TILEMAP,
-                crate::chunk::system::chunk_auto_radius.system(),
+                crate::chunk::system::chunk_auto_spawn.system(),
             )
             .add_system_to_stage(
                 stage::TILEMAP,
-                crate::chunk::system::chunk_auto_spawn.system(),
-            );
+                crate::chunk::system::chunk_parallax.system(),
+            )
+            .add_system_to_stage(
+                stage::TILEMAP,
+                crate::chunk::system::chunk_layer_uniforms.system(),
+            )
+            .add_system_to_stage(stage::TILEMAP, crate::light::tilemap_lighting.system())
+            .add_system_to_stage(stage::TILEMAP, crate::chunk::lod::chunk_lod.system())
+            .add_system_to_stage(stage::TILEMAP, crate::minimap::minimap_update.system())
+            .add_system_to_stage(stage::TILEMAP, crate::palette::palette_animation.system())
+            .add_system_to_stage(
+                stage::TILEMAP,
+                crate::position::tile_position_transforms.system(),
+            )
+            .add_system_to_stage(
+                stage::TILEMAP,
+                crate::position::tile_position_index.system(),
+            )
+            .add_system_to_stage(
+                stage::TILEMAP,
+                crate::position::tile_position_streaming.system(),
+            )
+            .add_system_to_stage(stage::TILEMAP, crate::region::region_events.system())
+            .add_system_to_stage(
+                stage::TILEMAP,
+                crate::chunk::system::chunk_visibility.system(),
+            )
+            .add_system_to_stage(stage::TILEMAP, crate::prefab::prefab_visibility.system());
+        #[cfg(feature = "ron-stamps")]
+        app.init_asset_loader::<crate::stamp::TileStampLoader>();
         #[cfg(feature = "bevy_rapier2d")]
         app.add_system_to_stage(
             stage::TILEMAP,
             crate::system::tilemap_collision_events.system(),
         );
 
-        let resources = app.resources_mut();
-        let mut render_graph = resources
-            .get_mut::<RenderGraph>()
-            .expect("`RenderGraph` is missing.");
-        render_graph.add_tilemap_graph(resources);
+        {
+            let resources = app.resources_mut();
+            let mut render_graph = resources
+                .get_mut::<RenderGraph>()
+                .expect("`RenderGraph` is missing.");
+            render_graph.add_tilemap_graph(resources);
+        }
+
+        app.add_plugin(TilemapMaterialPlugin::<TilemapPalette>::default());
     }
 }
 
 /// A custom prelude around everything that we only need to use.
 #[no_implicit_prelude]
 mod lib {
+    #[cfg(feature = "ron-stamps")]
+    extern crate anyhow;
     extern crate bevy_app;
     extern crate bevy_asset;
+    extern crate bevy_core;
     extern crate bevy_ecs;
     extern crate bevy_log;
     extern crate bevy_math;
@@ -171,21 +247,29 @@ mod lib {
     extern crate bevy_tilemap_types;
     extern crate bevy_transform;
     extern crate bevy_utils;
-    extern crate bevy_window;
     pub extern crate bitflags;
+    #[cfg(feature = "png")]
+    extern crate image;
+    #[cfg(feature = "ron-stamps")]
+    pub(crate) extern crate ron;
     #[cfg(feature = "serde")]
     extern crate serde;
     extern crate std;
 
     pub(crate) use bevy_app::{
-        stage as app_stage, AppBuilder, Events, Plugin, PluginGroup, PluginGroupBuilder,
+        stage as app_stage, AppBuilder, EventReader, Events, Plugin, PluginGroup,
+        PluginGroupBuilder,
     };
-    pub(crate) use bevy_asset::{AddAsset, Assets, Handle, HandleUntyped};
+    pub(crate) use bevy_asset::{AddAsset, Asset, Assets, Handle, HandleId, HandleUntyped};
+    #[cfg(feature = "ron-stamps")]
+    pub(crate) use bevy_asset::{AssetLoader, LoadContext, LoadedAsset};
+    pub(crate) use bevy_core::Time;
     pub(crate) use bevy_ecs::{
-        Bundle, Changed, Commands, Entity, IntoSystem, Query, Res, ResMut, Resources, SystemStage,
+        Bundle, Changed, Commands, Entity, IntoSystem, Local, Or, Query, Res, ResMut, Resources,
+        SystemStage, With,
     };
     pub(crate) use bevy_log::{error, info, warn};
-    pub(crate) use bevy_math::Vec3;
+    pub(crate) use bevy_math::{Vec2, Vec3};
     #[cfg(feature = "bevy_rapier2d")]
     pub(crate) use bevy_rapier2d::rapier::{
         dynamics::RigidBodyBuilder,
@@ -193,7 +277,7 @@ mod lib {
     };
     pub(crate) use bevy_reflect::{TypeUuid, Uuid};
     pub(crate) use bevy_render::{
-        camera::Camera,
+        camera::{Camera, OrthographicProjection},
         color::Color,
         draw::{Draw, Visible},
         mesh::{Indices, Mesh},
@@ -203,11 +287,17 @@ mod lib {
             PrimitiveTopology, RasterizationStateDescriptor, RenderPipeline, RenderPipelines,
             StencilStateDescriptor, StencilStateFaceDescriptor,
         },
-        render_graph::{base::MainPass, RenderGraph},
+        render_graph::{
+            base::{camera as base_camera, node as base_node, MainPass},
+            AssetRenderResourcesNode, RenderGraph, RenderResourcesNode,
+        },
+        renderer::{RenderResource, RenderResourceIterator, RenderResources},
         shader::{Shader, ShaderStage, ShaderStages},
-        texture::TextureFormat,
+        texture::{Extent3d, Texture, TextureDimension, TextureFormat},
+    };
+    pub(crate) use bevy_sprite::{
+        entity::SpriteBundle, ColorMaterial, Rect, Sprite, SpriteResizeMode, TextureAtlas,
     };
-    pub(crate) use bevy_sprite::TextureAtlas;
     pub(crate) use bevy_tilemap_types::{
         dimension::{Dimension2, DimensionError},
         point::Point2,
@@ -216,30 +306,46 @@ mod lib {
         components::{GlobalTransform, Parent, Transform},
         hierarchy::{BuildChildren, DespawnRecursiveExt},
     };
+    #[cfg(feature = "ron-stamps")]
+    pub(crate) use bevy_utils::BoxedFuture;
     pub(crate) use bevy_utils::{HashMap, HashSet};
-    pub(crate) use bevy_window::WindowResized;
+
+    #[cfg(feature = "ron-stamps")]
+    pub(crate) use anyhow::Error as AnyhowError;
 
     pub(crate) use crate::bitflags::*;
 
+    #[cfg(feature = "png")]
+    pub(crate) use image::{png::PngEncoder, ColorType};
+
     #[cfg(feature = "serde")]
     pub(crate) use serde::{Deserialize, Serialize};
 
     pub(crate) use std::{
+        any::type_name,
         boxed::Box,
         clone::Clone,
         cmp::Ord,
+        collections::VecDeque,
         convert::{AsMut, AsRef, From, Into},
         default::Default,
         error::Error,
         fmt::{Debug, Display, Formatter, Result as FmtResult},
         iter::{Extend, IntoIterator, Iterator},
+        marker::{PhantomData, Send, Sync},
+        mem::{self, size_of},
+        ops::Fn,
         option::Option::{self, *},
         result::Result::{self, *},
+        string::String,
         vec::Vec,
     };
 
+    #[cfg(feature = "png")]
+    pub(crate) use std::{fs::File, io::Write, path::Path, string::ToString};
+
     // Macros
-    pub(crate) use std::{vec, write};
+    pub(crate) use std::{matches, vec, write};
 
     #[cfg(debug_assertions)]
     #[allow(unused_imports)]
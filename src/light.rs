@@ -0,0 +1,306 @@
+//! Lighting of tiles which spreads over the grid.
+//!
+//! Once a tilemap has [`TilemapLighting`], every tile receives the ambient
+//! light and the light of each [`TileLight`] flood fills outwards from its
+//! point, losing some of its intensity on every step to a neighbouring tile.
+//! Tiles with sprites that are set as opaque block more of it, much like
+//! walls in Terraria or Minecraft.
+//!
+//! Light spreads to the same neighbours as [`Tilemap::neighbours`], so it
+//! follows the hexes of hex topologies and crosses the seams of wrapping
+//! tilemaps. When tiles or lights change, only the lights which reach the
+//! changed chunks are spread again.
+//!
+//! The light of each tile is multiplied into its color when rendered. It is
+//! kept apart from the tint of the tiles so that setting tiles does not
+//! overwrite it and lights changing does not overwrite the tints.
+//!
+//! # Examples
+//! ```
+//! use bevy_asset::{prelude::*, HandleId};
+//! use bevy_render::prelude::*;
+//! use bevy_sprite::prelude::*;
+//! use bevy_tilemap::{light::{TileLight, TilemapLighting}, prelude::*};
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! let mut lighting = TilemapLighting::default();
+//! // Walls with the sprite index of 3 block most of the light.
+//! lighting.opacity.insert(3, 0.5);
+//!
+//! let mut tilemap = TilemapBuilder::new()
+//!     .texture_atlas(texture_atlas_handle)
+//!     .tile_dimensions(32, 32)
+//!     .lighting(lighting)
+//!     .finish()
+//!     .unwrap();
+//!
+//! tilemap.insert_light((0, 0), TileLight { color: Color::rgb(1.0, 0.8, 0.6), intensity: 1.0 });
+//! ```
+
+use crate::{chunk::entity::ModifiedLayer, lib::*, Tilemap};
+
+/// The default amount of light lost on each step to a neighbouring tile.
+const DEFAULT_FALLOFF: f32 = 0.0625;
+
+/// The settings of the lighting of a tilemap.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct TilemapLighting {
+    /// The light that every tile receives, even without any lights nearby.
+    pub ambient: Color,
+    /// The amount of intensity that light loses on each step to a
+    /// neighbouring tile. Default is `0.0625`, which makes a light with an
+    /// intensity of `1.0` reach 16 tiles.
+    pub falloff: f32,
+    /// The extra amount of intensity lost when light steps onto a tile, by the
+    /// sprite index of the tile. The opacity of each layer's tile is added up.
+    pub opacity: HashMap<usize, f32>,
+}
+
+impl Default for TilemapLighting {
+    fn default() -> TilemapLighting {
+        TilemapLighting {
+            ambient: Color::BLACK,
+            falloff: DEFAULT_FALLOFF,
+            opacity: HashMap::default(),
+        }
+    }
+}
+
+/// A light at a tile point which spreads to the tiles around it.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TileLight {
+    /// The color of the light.
+    pub color: Color,
+    /// The intensity of the light at its own tile, where `1.0` is fully lit.
+    pub intensity: f32,
+}
+
+impl Default for TileLight {
+    fn default() -> TileLight {
+        TileLight {
+            color: Color::WHITE,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Returns the amount of steps to neighbouring tiles that a light reaches.
+fn light_steps(lighting: &TilemapLighting, light: &TileLight) -> i32 {
+    // A light does not lose less than this on every step so that it always
+    // stops spreading.
+    let falloff = lighting.falloff.max(0.001);
+    (light.intensity / falloff).ceil().max(0.0) as i32
+}
+
+/// Returns the points of the chunks that a light at a tile point may reach.
+///
+/// Every step to a neighbouring tile moves at most one tile along each axis,
+/// for every topology.
+pub(crate) fn light_reach(
+    tilemap: &Tilemap,
+    lighting: &TilemapLighting,
+    source: Point2,
+    light: &TileLight,
+) -> HashSet<Point2> {
+    let steps = light_steps(lighting, light);
+    let min = tilemap.unwrapped_chunk_point(Point2::new(source.x - steps, source.y - steps));
+    let max = tilemap.unwrapped_chunk_point(Point2::new(source.x + steps, source.y + steps));
+    let mut reach = HashSet::default();
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            reach.insert(tilemap.wrap_chunk_point_of_tilemap(Point2::new(x, y)));
+        }
+    }
+    reach
+}
+
+/// Returns the chunks whose light needs to be spread again after a set of
+/// chunks changed, which are those chunks and every chunk reached by a light
+/// that reaches them.
+pub(crate) fn light_region(
+    tilemap: &Tilemap,
+    lighting: &TilemapLighting,
+    dirty: HashSet<Point2>,
+) -> HashSet<Point2> {
+    let mut region = dirty.clone();
+    for (source, light) in tilemap.lights() {
+        let reach = light_reach(tilemap, lighting, *source, light);
+        if reach.iter().any(|point| dirty.contains(point)) {
+            region.extend(reach);
+        }
+    }
+    region
+}
+
+/// Spreads the lights of a tilemap over a region of its chunks, or all of
+/// them, and returns the light of every tile in each of those chunks.
+pub(crate) fn propagate_lights(
+    tilemap: &Tilemap,
+    lighting: &TilemapLighting,
+    region: Option<&HashSet<Point2>>,
+) -> HashMap<Point2, Vec<[f32; 4]>> {
+    let chunk_dimensions = tilemap.chunk_dimensions();
+    let area = chunk_dimensions.area() as usize;
+    let layers = tilemap.layers().len();
+    let [ambient_r, ambient_g, ambient_b, _ambient_a]: [f32; 4] = lighting.ambient.into();
+    let mut chunk_lights: HashMap<Point2, Vec<[f32; 4]>> = tilemap
+        .chunks()
+        .keys()
+        .filter(|point| region.map_or(true, |region| region.contains(point)))
+        .map(|point| (*point, vec![[ambient_r, ambient_g, ambient_b, 1.0]; area]))
+        .collect();
+    // A light does not lose less than this on every step so that it always
+    // stops spreading.
+    let falloff = lighting.falloff.max(0.001);
+
+    // Returns the chunk and tile index of a point, if the chunk exists.
+    let locate = |point: Point2| -> Option<(Point2, usize)> {
+        let chunk_point: Point2 = tilemap.point_to_chunk_point(point).into();
+        tilemap.get_chunk(&chunk_point)?;
+        let tile_point = tilemap.point_to_tile_point(point);
+        Some((
+            chunk_point,
+            chunk_dimensions.encode_point_unchecked(tile_point),
+        ))
+    };
+    // Returns the extra intensity lost by stepping onto a tile.
+    let opacity = |chunk_point: Point2, index: usize| -> f32 {
+        let chunk = if let Some(chunk) = tilemap.get_chunk(&chunk_point) {
+            chunk
+        } else {
+            return 0.0;
+        };
+        (0..layers)
+            .filter_map(|z_order| chunk.get_tile(z_order, index))
+            .filter(|tile| tile.color.a() > 0.0)
+            .filter_map(|tile| lighting.opacity.get(&tile.index))
+            .sum()
+    };
+
+    for (source, light) in tilemap.lights() {
+        // Lights that do not reach the region can not change its light.
+        if let Some(region) = region {
+            let reach = light_reach(tilemap, lighting, *source, light);
+            if !reach.iter().any(|point| region.contains(point)) {
+                continue;
+            }
+        }
+        let [light_r, light_g, light_b, _light_a]: [f32; 4] = light.color.into();
+        let mut levels: HashMap<Point2, f32> = HashMap::default();
+        let mut queue = VecDeque::new();
+        levels.insert(*source, light.intensity);
+        queue.push_back(*source);
+        while let Some(point) = queue.pop_front() {
+            let level = if let Some(level) = levels.get(&point) {
+                *level
+            } else {
+                continue;
+            };
+            let (chunk_point, index) = if let Some(location) = locate(point) {
+                location
+            } else {
+                continue;
+            };
+            if let Some(tile_light) = chunk_lights
+                .get_mut(&chunk_point)
+                .and_then(|lights| lights.get_mut(index))
+            {
+                let [r, g, b, _a] = tile_light;
+                *r = r.max(light_r * level);
+                *g = g.max(light_g * level);
+                *b = b.max(light_b * level);
+            }
+
+            for neighbour in tilemap.neighbours(point).iter() {
+                let (chunk_point, index) = if let Some(location) = locate(*neighbour) {
+                    location
+                } else {
+                    continue;
+                };
+                let next_level = level - falloff - opacity(chunk_point, index);
+                if next_level <= 0.0 {
+                    continue;
+                }
+                if levels
+                    .get(neighbour)
+                    .map_or(false, |known| *known >= next_level)
+                {
+                    continue;
+                }
+                levels.insert(*neighbour, next_level);
+                queue.push_back(*neighbour);
+            }
+        }
+    }
+
+    for lights in chunk_lights.values_mut() {
+        for [r, g, b, _a] in lights.iter_mut() {
+            *r = r.min(1.0);
+            *g = g.min(1.0);
+            *b = b.min(1.0);
+        }
+    }
+    chunk_lights
+}
+
+/// Spreads the lights of tilemaps again after their lights or tiles changed
+/// and updates the meshes of the chunks whose light changed.
+///
+/// Only the chunks around the changes are spread again, unless the lighting
+/// itself changed.
+pub(crate) fn tilemap_lighting(
+    mut tilemap_query: Query<&mut Tilemap>,
+    mut layer_query: Query<&mut ModifiedLayer>,
+) {
+    for mut tilemap in tilemap_query.iter_mut() {
+        let dirty = tilemap.take_light_dirty_chunks();
+        if !tilemap.is_light_dirty() && dirty.is_empty() {
+            continue;
+        }
+        let region = if tilemap.is_light_dirty() {
+            None
+        } else {
+            tilemap
+                .lighting()
+                .map(|lighting| light_region(&tilemap, lighting, dirty))
+        };
+        tilemap.clean_light();
+        let mut chunk_lights = if let Some(lighting) = tilemap.lighting() {
+            propagate_lights(&tilemap, lighting, region.as_ref())
+        } else {
+            HashMap::default()
+        };
+        let layers = tilemap.layers().len();
+        let points: Vec<Point2> = tilemap
+            .chunks()
+            .keys()
+            .filter(|point| {
+                region
+                    .as_ref()
+                    .map_or(true, |region| region.contains(point))
+            })
+            .cloned()
+            .collect();
+        for point in points {
+            let chunk = if let Some(chunk) = tilemap.chunks_mut().get_mut(&point) {
+                chunk
+            } else {
+                continue;
+            };
+            if !chunk.set_lights(chunk_lights.remove(&point)) {
+                continue;
+            }
+            for z_order in 0..layers {
+                if let Some(entity) = chunk.get_entity(z_order) {
+                    if let Ok(mut modified_layer) = layer_query.get_mut(entity) {
+                        modified_layer.0 += 1;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Extra uniforms and textures for the custom pipelines of layers.
+//!
+//! A layer drawn with a custom pipeline, see [`GridTopology::layer_pipeline`],
+//! often needs more than the texture atlas, such as the time for animated
+//! water or a second texture for distortion. Any asset that implements
+//! `RenderResources` can be bound to the chunks of a layer as a material:
+//!
+//! 1. Add the asset with `add_asset` and the [`TilemapMaterialPlugin`] for it
+//! to the app.
+//! 1. Add the [`LayerMaterials`] component to the tilemap entity, with the
+//! handle of the material for each layer by its z order.
+//!
+//! The fields of the material are then available in the shaders of the
+//! layer's pipeline by the name of the type and field, such as
+//! `WaterMaterial_time`.
+//!
+//! # Examples
+//! ```no_run
+//! use bevy_app::prelude::*;
+//! use bevy_asset::{prelude::*, AddAsset};
+//! use bevy_reflect::TypeUuid;
+//! use bevy_render::renderer::RenderResources;
+//! use bevy_tilemap::{material::TilemapMaterialPlugin, prelude::*};
+//!
+//! #[derive(RenderResources, TypeUuid)]
+//! #[uuid = "5ba3b5a4-5b5e-4cbb-8a3e-9c71d19c8a7d"]
+//! struct WaterMaterial {
+//!     time: f32,
+//! }
+//!
+//! fn main() {
+//!     App::build()
+//!         .add_plugins(TilemapDefaultPlugins)
+//!         .add_asset::<WaterMaterial>()
+//!         .add_plugin(TilemapMaterialPlugin::<WaterMaterial>::default())
+//!         .run()
+//! }
+//! ```
+//!
+//! [`GridTopology::layer_pipeline`]: crate::chunk::render::GridTopology::layer_pipeline
+
+use crate::{chunk::entity::ZOrder, lib::*, stage};
+
+/// A component for tilemap entities which binds a material to the chunks of
+/// each layer, by z order.
+#[derive(Debug)]
+pub struct LayerMaterials<M: Asset> {
+    /// The material of each layer by its z order.
+    pub materials: HashMap<usize, Handle<M>>,
+}
+
+impl<M: Asset> Default for LayerMaterials<M> {
+    fn default() -> Self {
+        LayerMaterials {
+            materials: HashMap::default(),
+        }
+    }
+}
+
+impl<M: Asset> LayerMaterials<M> {
+    /// Sets the material of a layer by its z order, returning the material it
+    /// had before, if any.
+    pub fn insert(&mut self, z_order: usize, material: Handle<M>) -> Option<Handle<M>> {
+        self.materials.insert(z_order, material)
+    }
+
+    /// Removes the material of a layer by its z order, returning it if there
+    /// was one.
+    pub fn remove(&mut self, z_order: usize) -> Option<Handle<M>> {
+        self.materials.remove(&z_order)
+    }
+}
+
+/// A plugin which binds the materials in [`LayerMaterials`] to the chunks of
+/// the layers.
+///
+/// The material asset must be added to the app beforehand with `add_asset`.
+#[derive(Debug)]
+pub struct TilemapMaterialPlugin<M> {
+    /// The material type.
+    _material: PhantomData<M>,
+}
+
+impl<M> Default for TilemapMaterialPlugin<M> {
+    fn default() -> Self {
+        TilemapMaterialPlugin {
+            _material: PhantomData,
+        }
+    }
+}
+
+impl<M: Asset + RenderResources> Plugin for TilemapMaterialPlugin<M> {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_to_stage(stage::TILEMAP, layer_materials::<M>.system());
+
+        let resources = app.resources_mut();
+        let mut render_graph = resources
+            .get_mut::<RenderGraph>()
+            .expect("`RenderGraph` is missing.");
+        let node_name = type_name::<M>();
+        render_graph.add_system_node(node_name, AssetRenderResourcesNode::<M>::new(true));
+        if let Err(e) = render_graph.add_node_edge(node_name, base_node::MAIN_PASS) {
+            error!("can not add the tilemap material to the main pass: {}", e);
+        }
+    }
+}
+
+/// Keeps the material handles of chunk layers in sync with the
+/// [`LayerMaterials`] of their tilemap.
+fn layer_materials<M: Asset>(
+    commands: &mut Commands,
+    tilemap_query: Query<&LayerMaterials<M>>,
+    chunk_query: Query<(Entity, &Parent, &ZOrder, Option<&Handle<M>>)>,
+) {
+    for (entity, parent, z_order, current) in chunk_query.iter() {
+        let layer_materials = if let Ok(layer_materials) = tilemap_query.get(**parent) {
+            layer_materials
+        } else {
+            continue;
+        };
+        match (current, layer_materials.materials.get(&z_order.0)) {
+            (Some(current), Some(material)) if current == material => {}
+            (_, Some(material)) => {
+                commands.insert_one(entity, material.clone());
+            }
+            (Some(_), None) => {
+                commands.remove_one::<Handle<M>>(entity);
+            }
+            (None, None) => {}
+        }
+    }
+}
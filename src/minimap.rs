@@ -0,0 +1,660 @@
+//! Minimaps which draw an overview of a tilemap into a texture.
+//!
+//! A [`Minimap`] is a component that keeps a texture up to date with one
+//! colored block per tile of a region of a tilemap. The texture can be used
+//! anywhere a texture can, such as in an image in the UI. Only the chunks that
+//! were modified are drawn again, which makes them cheap to keep around.
+//!
+//! Entities with a [`MinimapMarker`] are drawn on top as small squares and the
+//! views of the tilemap cameras can be outlined as well. Only the pixels under
+//! the markers and outlines that moved are drawn again, and the texture is
+//! left alone on frames where nothing changed.
+
+use crate::{
+    camera::{camera_view_rect, select_views, TilemapCamera},
+    event::TilemapChunkEvent,
+    lib::*,
+    Tilemap,
+};
+
+/// The colors that the tiles of a minimap are drawn with.
+#[derive(Clone, PartialEq, Debug)]
+pub enum MinimapColors {
+    /// The average color of each sprite in the texture atlas.
+    AtlasAverage,
+    /// One color for each sprite index. Sprites without a color are not drawn.
+    Palette(Vec<Color>),
+}
+
+impl Default for MinimapColors {
+    fn default() -> MinimapColors {
+        MinimapColors::AtlasAverage
+    }
+}
+
+/// A component which draws an overview of a tilemap into a texture.
+///
+/// The highest tile of every point that is not transparent is drawn, tinted
+/// by the tile's color. The palettes of layers are not applied.
+///
+/// # Examples
+/// ```
+/// use bevy_asset::prelude::*;
+/// use bevy_ecs::prelude::*;
+/// use bevy_render::prelude::*;
+/// use bevy_tilemap::{minimap::Minimap, prelude::*};
+///
+/// fn setup(
+///     commands: &mut Commands,
+///     mut textures: ResMut<Assets<Texture>>,
+///     tilemap_query: Query<Entity, With<Tilemap>>,
+/// ) {
+///     for tilemap_entity in tilemap_query.iter() {
+///         let minimap = Minimap::new(&mut textures, tilemap_entity, (-64, -64), 128, 128)
+///             .pixels_per_tile(2)
+///             .viewport_color(Color::WHITE);
+///         // Use `minimap.texture()` to show it in the UI.
+///         commands.spawn((minimap,));
+///     }
+/// }
+/// ```
+pub struct Minimap {
+    /// The tilemap entity to draw.
+    tilemap: Entity,
+    /// The texture that is drawn into.
+    texture: Handle<Texture>,
+    /// The bottom left tile point of the region to draw.
+    origin: Point2,
+    /// The dimensions of the region to draw in tiles.
+    dimensions: Dimension2,
+    /// The width and height of each tile in pixels.
+    pixels_per_tile: u32,
+    /// The colors that the tiles are drawn with.
+    colors: MinimapColors,
+    /// The color to outline the views of the tilemap cameras with, if any.
+    viewport_color: Option<Color>,
+    /// The RGBA color of each sprite index, once resolved.
+    sprite_colors: Vec<[u8; 4]>,
+    /// The RGBA pixels of the tiles alone, from the top row down.
+    pixels: Vec<u8>,
+    /// If every tile needs to be drawn again.
+    redraw_all: bool,
+    /// The rectangles of the markers and outlines drawn into the texture on
+    /// top of the tiles, with their colors.
+    overlay: Vec<(PixelRect, [u8; 4])>,
+}
+
+impl Minimap {
+    /// Constructs a new minimap of a region of a tilemap entity, adding its
+    /// texture to the texture assets.
+    ///
+    /// The region starts at the bottom left tile point and is `width` by
+    /// `height` tiles.
+    pub fn new<P: Into<Point2>>(
+        textures: &mut Assets<Texture>,
+        tilemap: Entity,
+        origin: P,
+        width: u32,
+        height: u32,
+    ) -> Minimap {
+        let dimensions = Dimension2::new(width.max(1), height.max(1));
+        let texture = textures.add(Texture::new(
+            Extent3d::new(dimensions.width, dimensions.height, 1),
+            TextureDimension::D2,
+            vec![0; (dimensions.area() * 4) as usize],
+            TextureFormat::Rgba8UnormSrgb,
+        ));
+        Minimap::with_texture(texture, tilemap, origin, width, height)
+    }
+
+    /// Constructs a new minimap of a region of a tilemap entity which draws
+    /// into a texture that already exists.
+    ///
+    /// The texture is replaced with one of the size of the minimap when it is
+    /// first drawn.
+    pub fn with_texture<P: Into<Point2>>(
+        texture: Handle<Texture>,
+        tilemap: Entity,
+        origin: P,
+        width: u32,
+        height: u32,
+    ) -> Minimap {
+        let dimensions = Dimension2::new(width.max(1), height.max(1));
+        Minimap {
+            tilemap,
+            texture,
+            origin: origin.into(),
+            dimensions,
+            pixels_per_tile: 1,
+            colors: MinimapColors::default(),
+            viewport_color: None,
+            sprite_colors: Vec::new(),
+            pixels: vec![0; (dimensions.area() * 4) as usize],
+            redraw_all: true,
+            overlay: Vec::new(),
+        }
+    }
+
+    /// Sets the width and height of each tile in pixels. Default is 1.
+    pub fn pixels_per_tile(mut self, pixels: u32) -> Minimap {
+        self.pixels_per_tile = pixels.max(1);
+        self.pixels = vec![0; (self.width() * self.height() * 4) as usize];
+        self.redraw_all = true;
+        self
+    }
+
+    /// Sets the colors that the tiles are drawn with. Default is the average
+    /// color of each sprite.
+    pub fn colors(mut self, colors: MinimapColors) -> Minimap {
+        self.colors = colors;
+        self.sprite_colors.clear();
+        self.redraw_all = true;
+        self
+    }
+
+    /// Outlines the views of the tilemap cameras with a color.
+    pub fn viewport_color(mut self, color: Color) -> Minimap {
+        self.viewport_color = Some(color);
+        self
+    }
+
+    /// Returns the tilemap entity that is drawn.
+    pub fn tilemap(&self) -> Entity {
+        self.tilemap
+    }
+
+    /// Returns the handle of the texture that is drawn into.
+    pub fn texture(&self) -> &Handle<Texture> {
+        &self.texture
+    }
+
+    /// The width of the texture in pixels.
+    pub fn width(&self) -> u32 {
+        self.dimensions.width * self.pixels_per_tile
+    }
+
+    /// The height of the texture in pixels.
+    pub fn height(&self) -> u32 {
+        self.dimensions.height * self.pixels_per_tile
+    }
+
+    /// Returns the RGBA color of a pixel of the tiles, counted from the top
+    /// left corner, without the markers and camera views drawn on top.
+    pub fn tile_pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        let index = (y as usize * self.width() as usize + x as usize) * 4;
+        match self.pixels.get(index..index + 4) {
+            Some([r, g, b, a]) => Some([*r, *g, *b, *a]),
+            _ => None,
+        }
+    }
+
+    /// Draws the tiles of chunks which were modified, or every tile the first
+    /// time and after the colors or size of the minimap changed.
+    ///
+    /// The plugin does this every frame for the modified chunks and copies the
+    /// tiles into the texture along with the markers and camera views, so this
+    /// is only needed to draw a minimap without it. Tiles are not drawn with
+    /// [`MinimapColors::AtlasAverage`] until the plugin has read the atlas.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_ecs::prelude::*;
+    /// use bevy_render::prelude::*;
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::{minimap::{Minimap, MinimapColors}, prelude::*};
+    /// use bevy_tilemap_types::point::Point2;
+    ///
+    /// // In production use strong handles from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    /// let texture_handle = Handle::weak(HandleId::random::<Texture>());
+    ///
+    /// let mut tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .chunk_dimensions(4, 4)
+    ///     .tile_dimensions(8, 8)
+    ///     .auto_chunk()
+    ///     .finish()
+    ///     .unwrap();
+    /// tilemap.insert_tile(Tile { point: (0, 0), sprite_index: 1, ..Default::default() }).unwrap();
+    ///
+    /// let mut minimap = Minimap::with_texture(texture_handle, Entity::new(0), (-2, -2), 4, 4)
+    ///     .colors(MinimapColors::Palette(vec![Color::BLACK, Color::RED]));
+    /// minimap.redraw(&tilemap, Vec::new());
+    ///
+    /// // Tile points go up while the rows of pixels go down.
+    /// assert_eq!(minimap.tile_pixel(2, 1), Some([255, 0, 0, 255]));
+    /// assert_eq!(minimap.tile_pixel(1, 1), Some([0, 0, 0, 0]));
+    ///
+    /// // Only the chunks that are passed in are drawn again.
+    /// tilemap.clear_tile((0, 0), 0).unwrap();
+    /// minimap.redraw(&tilemap, Vec::new());
+    /// assert_eq!(minimap.tile_pixel(2, 1), Some([255, 0, 0, 255]));
+    /// minimap.redraw(&tilemap, vec![Point2::new(0, 0)]);
+    /// assert_eq!(minimap.tile_pixel(2, 1), Some([0, 0, 0, 0]));
+    /// ```
+    pub fn redraw<I: IntoIterator<Item = Point2>>(&mut self, tilemap: &Tilemap, chunks: I) {
+        self.draw_tiles(tilemap, chunks);
+    }
+
+    /// Draws the tiles of chunks, or of every chunk if the whole minimap needs
+    /// to be drawn. Returns the rectangles of pixels that were drawn, or
+    /// `None` if every pixel was.
+    fn draw_tiles<I: IntoIterator<Item = Point2>>(
+        &mut self,
+        tilemap: &Tilemap,
+        chunks: I,
+    ) -> Option<Vec<PixelRect>> {
+        if self.sprite_colors.is_empty() {
+            if let MinimapColors::Palette(palette) = &self.colors {
+                self.sprite_colors = palette.iter().map(|color| color_to_rgba(*color)).collect();
+            }
+        }
+        if self.redraw_all {
+            for pixel in self.pixels.iter_mut() {
+                *pixel = 0;
+            }
+            for point in tilemap.chunks().keys() {
+                self.draw_chunk(tilemap, *point);
+            }
+            self.redraw_all = false;
+            return None;
+        }
+        Some(
+            chunks
+                .into_iter()
+                .map(|point| {
+                    self.draw_chunk(tilemap, point);
+                    self.chunk_rect(tilemap, point)
+                })
+                .collect(),
+        )
+    }
+
+    /// Draws every tile of a chunk into the pixels.
+    fn draw_chunk(&mut self, tilemap: &Tilemap, chunk_point: Point2) {
+        let chunk = if let Some(chunk) = tilemap.get_chunk(&chunk_point) {
+            chunk
+        } else {
+            return;
+        };
+        let chunk_dimensions = tilemap.chunk_dimensions();
+        let layers = tilemap.layers().len();
+        let chunk_width = chunk_dimensions.width as i32;
+        let chunk_height = chunk_dimensions.height as i32;
+        for index in 0..chunk_dimensions.area() as usize {
+            let tile_point = chunk_dimensions.decode_point_unchecked(index);
+            let point = Point2::new(
+                tile_point.x + chunk_point.x * chunk_width - chunk_width / 2,
+                tile_point.y + chunk_point.y * chunk_height - chunk_height / 2,
+            );
+            let color = (0..layers)
+                .rev()
+                .filter_map(|z_order| chunk.get_tile(z_order, index))
+                .filter_map(|tile| {
+                    let sprite = self.sprite_colors.get(tile.index)?;
+                    let tint: [f32; 4] = tile.color.into();
+                    let [r, g, b, a] = *sprite;
+                    let [tint_r, tint_g, tint_b, tint_a] = tint;
+                    let color = [
+                        (f32::from(r) * tint_r).round() as u8,
+                        (f32::from(g) * tint_g).round() as u8,
+                        (f32::from(b) * tint_b).round() as u8,
+                        (f32::from(a) * tint_a).round() as u8,
+                    ];
+                    Some(color)
+                })
+                .find(|[_r, _g, _b, a]| *a > 0)
+                .unwrap_or([0, 0, 0, 0]);
+            self.draw_tile(point, color);
+        }
+    }
+
+    /// Returns the rectangle of pixels of a chunk.
+    fn chunk_rect(&self, tilemap: &Tilemap, chunk_point: Point2) -> PixelRect {
+        let chunk_dimensions = tilemap.chunk_dimensions();
+        let chunk_width = chunk_dimensions.width as i32;
+        let chunk_height = chunk_dimensions.height as i32;
+        let size = self.pixels_per_tile as i32;
+        let x = chunk_point.x * chunk_width - chunk_width / 2 - self.origin.x;
+        let y = chunk_point.y * chunk_height - chunk_height / 2 - self.origin.y;
+        let top = self.dimensions.height as i32 - (y + chunk_height);
+        PixelRect {
+            left: x * size,
+            top: top * size,
+            width: chunk_width * size,
+            height: chunk_height * size,
+        }
+    }
+
+    /// Fills the block of pixels of a tile point with a color.
+    fn draw_tile(&mut self, point: Point2, color: [u8; 4]) {
+        let x = point.x - self.origin.x;
+        let y = point.y - self.origin.y;
+        if x < 0 || y < 0 || x >= self.dimensions.width as i32 || y >= self.dimensions.height as i32
+        {
+            return;
+        }
+        let size = self.pixels_per_tile;
+        let left = x as u32 * size;
+        let top = (self.dimensions.height - 1 - y as u32) * size;
+        let width = self.width();
+        fill_rect(&mut self.pixels, width, left, top, size, size, color);
+    }
+}
+
+/// A component for entities to be drawn on minimaps as a square.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct MinimapMarker {
+    /// The color of the square.
+    pub color: Color,
+    /// The width and height of the square in pixels.
+    pub size: u32,
+}
+
+impl Default for MinimapMarker {
+    fn default() -> MinimapMarker {
+        MinimapMarker {
+            color: Color::RED,
+            size: 3,
+        }
+    }
+}
+
+/// Converts a color into RGBA with 8 bits per channel.
+fn color_to_rgba(color: Color) -> [u8; 4] {
+    let [r, g, b, a]: [f32; 4] = color.into();
+    [
+        (r * 255.0).round() as u8,
+        (g * 255.0).round() as u8,
+        (b * 255.0).round() as u8,
+        (a * 255.0).round() as u8,
+    ]
+}
+
+/// Returns the average color of a sprite in an RGBA texture, weighted by the
+/// alpha of each texel.
+fn average_sprite_color(sprite: &Rect, texture: &Texture) -> [u8; 4] {
+    let texture_width = texture.size.width as usize;
+    let mut sum = [0.0_f32; 4];
+    let mut count = 0.0;
+    for y in sprite.min.y as usize..sprite.max.y as usize {
+        for x in sprite.min.x as usize..sprite.max.x as usize {
+            let index = (y * texture_width + x) * 4;
+            if let Some([r, g, b, a]) = texture.data.get(index..index + 4) {
+                let alpha = f32::from(*a);
+                let [sum_r, sum_g, sum_b, sum_a] = &mut sum;
+                *sum_r += f32::from(*r) * alpha;
+                *sum_g += f32::from(*g) * alpha;
+                *sum_b += f32::from(*b) * alpha;
+                *sum_a += alpha;
+                count += 1.0;
+            }
+        }
+    }
+    let [sum_r, sum_g, sum_b, sum_a] = sum;
+    if sum_a <= 0.0 {
+        return [0, 0, 0, 0];
+    }
+    [
+        (sum_r / sum_a) as u8,
+        (sum_g / sum_a) as u8,
+        (sum_b / sum_a) as u8,
+        (sum_a / count) as u8,
+    ]
+}
+
+/// Fills a rectangle of RGBA pixels with a color, clipped to the image.
+fn fill_rect(
+    pixels: &mut [u8],
+    image_width: u32,
+    left: u32,
+    top: u32,
+    width: u32,
+    height: u32,
+    color: [u8; 4],
+) {
+    let image_height = pixels.len() as u32 / 4 / image_width.max(1);
+    for y in top..(top + height).min(image_height) {
+        for x in left..(left + width).min(image_width) {
+            let index = ((y * image_width + x) * 4) as usize;
+            if let Some(pixel) = pixels.get_mut(index..index + 4) {
+                pixel.copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+/// A rectangle of pixels from its top left corner, which may be partly or
+/// entirely outside of the image.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct PixelRect {
+    /// The column of the left edge.
+    left: i32,
+    /// The row of the top edge.
+    top: i32,
+    /// The width in pixels.
+    width: i32,
+    /// The height in pixels.
+    height: i32,
+}
+
+impl PixelRect {
+    /// Returns the four one pixel wide edges of a rectangle from its left,
+    /// top, right and bottom pixels.
+    fn outline(left: i32, top: i32, right: i32, bottom: i32) -> [PixelRect; 4] {
+        let width = right - left + 1;
+        let height = bottom - top + 1;
+        [
+            PixelRect {
+                left,
+                top,
+                width,
+                height: 1,
+            },
+            PixelRect {
+                left,
+                top: bottom,
+                width,
+                height: 1,
+            },
+            PixelRect {
+                left,
+                top,
+                width: 1,
+                height,
+            },
+            PixelRect {
+                left: right,
+                top,
+                width: 1,
+                height,
+            },
+        ]
+    }
+
+    /// Returns the left, top, width and height of the part of the rectangle
+    /// that is within an image, if any.
+    fn clip(&self, image_width: u32, image_height: u32) -> Option<(u32, u32, u32, u32)> {
+        let left = self.left.max(0);
+        let top = self.top.max(0);
+        let right = (self.left + self.width).min(image_width as i32);
+        let bottom = (self.top + self.height).min(image_height as i32);
+        if right <= left || bottom <= top {
+            return None;
+        }
+        Some((
+            left as u32,
+            top as u32,
+            (right - left) as u32,
+            (bottom - top) as u32,
+        ))
+    }
+}
+
+/// Copies a rectangle of RGBA pixels from one image to another of the same
+/// size, clipped to the image.
+fn copy_rect(target: &mut [u8], source: &[u8], image_width: u32, rect: PixelRect) {
+    let image_height = source.len() as u32 / 4 / image_width.max(1);
+    let (left, top, width, height) = if let Some(clipped) = rect.clip(image_width, image_height) {
+        clipped
+    } else {
+        return;
+    };
+    for y in top..top + height {
+        let start = ((y * image_width + left) * 4) as usize;
+        let end = start + (width * 4) as usize;
+        if let (Some(target), Some(source)) = (target.get_mut(start..end), source.get(start..end)) {
+            target.copy_from_slice(source);
+        }
+    }
+}
+
+/// Draws the minimaps, only drawing the chunks which were modified since the
+/// last frame and the markers and camera views which moved.
+pub(crate) fn minimap_update(
+    mut reader: Local<EventReader<TilemapChunkEvent>>,
+    chunk_events: Res<Events<TilemapChunkEvent>>,
+    mut textures: ResMut<Assets<Texture>>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    camera_query: Query<(
+        &Camera,
+        &OrthographicProjection,
+        &GlobalTransform,
+        Option<&TilemapCamera>,
+    )>,
+    marker_query: Query<(&MinimapMarker, &GlobalTransform)>,
+    tilemap_query: Query<(&Tilemap, &GlobalTransform)>,
+    mut minimap_query: Query<&mut Minimap>,
+) {
+    let mut modified: HashMap<Entity, HashSet<Point2>> = HashMap::default();
+    for event in reader.iter(&chunk_events) {
+        if let TilemapChunkEvent::Modified { tilemap, point, .. } = event {
+            modified.entry(*tilemap).or_default().insert(*point);
+        }
+    }
+
+    for mut minimap in minimap_query.iter_mut() {
+        let (tilemap, tilemap_transform) = if let Ok(tilemap) = tilemap_query.get(minimap.tilemap) {
+            tilemap
+        } else {
+            continue;
+        };
+
+        let modified_chunks = modified.get(&minimap.tilemap).cloned().unwrap_or_default();
+
+        if minimap.sprite_colors.is_empty() && minimap.colors == MinimapColors::AtlasAverage {
+            let atlas = texture_atlases.get(tilemap.texture_atlas());
+            let texture = atlas.and_then(|atlas| textures.get(&atlas.texture));
+            let sprite_colors: Vec<[u8; 4]> = match (atlas, texture) {
+                (Some(atlas), Some(texture)) => atlas
+                    .textures
+                    .iter()
+                    .map(|sprite| average_sprite_color(sprite, texture))
+                    .collect(),
+                // Tried again next frame once the atlas has loaded.
+                _ => Vec::new(),
+            };
+            if sprite_colors.is_empty() {
+                continue;
+            }
+            minimap.sprite_colors = sprite_colors;
+            minimap.redraw_all = true;
+        }
+
+        let dirty = minimap.draw_tiles(tilemap, modified_chunks);
+        let redraw_all = dirty.is_none();
+        let dirty = dirty.unwrap_or_default();
+
+        // Converts a position in the world into a pixel position.
+        let tile_width = tilemap.tile_width() as f32;
+        let tile_height = tilemap.tile_height() as f32;
+        let tilemap_translation = tilemap_transform.translation.truncate();
+        let tilemap_scale = tilemap_transform.scale.truncate();
+        let origin = minimap.origin;
+        let pixels_per_tile = minimap.pixels_per_tile as f32;
+        let map_height = minimap.dimensions.height as f32;
+        let to_pixel = |position: Vec2| -> (i32, i32) {
+            let local = (position - tilemap_translation) / tilemap_scale;
+            let x = (local.x / tile_width - origin.x as f32) * pixels_per_tile;
+            let y = (map_height - (local.y / tile_height - origin.y as f32)) * pixels_per_tile;
+            (x.floor() as i32, y.floor() as i32)
+        };
+
+        let mut overlay = Vec::new();
+        if let Some(color) = minimap.viewport_color {
+            let color = color_to_rgba(color);
+            let views = select_views(camera_query.iter().map(
+                |(camera, projection, transform, marker)| {
+                    (
+                        camera_view_rect(projection, transform),
+                        camera,
+                        marker.is_some(),
+                    )
+                },
+            ));
+            for view in views {
+                let (left, bottom) = to_pixel(view.min);
+                let (right, top) = to_pixel(view.max);
+                for edge in PixelRect::outline(left, top, right, bottom).iter() {
+                    overlay.push((*edge, color));
+                }
+            }
+        }
+        for (marker, transform) in marker_query.iter() {
+            let (x, y) = to_pixel(transform.translation.truncate());
+            let half = (marker.size / 2) as i32;
+            let rect = PixelRect {
+                left: x - half,
+                top: y - half,
+                width: marker.size as i32,
+                height: marker.size as i32,
+            };
+            overlay.push((rect, color_to_rgba(marker.color)));
+        }
+
+        if !redraw_all && dirty.is_empty() && overlay == minimap.overlay {
+            continue;
+        }
+
+        let width = minimap.width();
+        let height = minimap.height();
+        let texture = if let Some(texture) = textures.get_mut(&minimap.texture) {
+            texture
+        } else {
+            continue;
+        };
+        if redraw_all || texture.data.len() != minimap.pixels.len() {
+            *texture = Texture::new(
+                Extent3d::new(width, height, 1),
+                TextureDimension::D2,
+                minimap.pixels.clone(),
+                TextureFormat::Rgba8UnormSrgb,
+            );
+        } else {
+            // The tiles are put back under the overlay of the last frame and
+            // in the modified chunks before the overlay is drawn again.
+            let restored = minimap.overlay.iter().map(|(rect, _color)| *rect);
+            for rect in restored.chain(dirty) {
+                copy_rect(&mut texture.data, &minimap.pixels, width, rect);
+            }
+        }
+        for (rect, color) in overlay.iter() {
+            if let Some((left, top, rect_width, rect_height)) = rect.clip(width, height) {
+                fill_rect(
+                    &mut texture.data,
+                    width,
+                    left,
+                    top,
+                    rect_width,
+                    rect_height,
+                    *color,
+                );
+            }
+        }
+        minimap.overlay = overlay;
+    }
+}
@@ -0,0 +1,143 @@
+//! Palette swapping for indexed color tilesets.
+//!
+//! An indexed color tileset stores the index of a color in the red channel of
+//! its texture, instead of the color itself. The color is then looked up in a
+//! palette texture, where each row is a palette and each column a color. This
+//! allows a single texture atlas to be recolored at runtime, for seasons, team
+//! colors or damage flashes.
+//!
+//! To draw a layer with a palette:
+//!
+//! 1. Add a [`TilemapPalette`] asset with the palette texture.
+//! 1. Set the pipeline of the layer to [`GridTopology::palette_pipeline`].
+//! 1. Bind the palette to the layer with the [`LayerMaterials`] component on
+//! the tilemap entity.
+//!
+//! The row of the palette each tile uses is set with [`Tile::palette`]. The
+//! whole palette can be cycled by changing [`TilemapPalette::offset`], or
+//! automatically over time with [`TilemapPalette::speed`].
+//!
+//! # Examples
+//! ```no_run
+//! use bevy_asset::prelude::*;
+//! use bevy_ecs::prelude::*;
+//! use bevy_render::{pipeline::PipelineDescriptor, prelude::*};
+//! use bevy_tilemap::{material::LayerMaterials, palette::TilemapPalette, prelude::*};
+//!
+//! fn setup(
+//!     commands: &mut Commands,
+//!     asset_server: Res<AssetServer>,
+//!     mut pipelines: ResMut<Assets<PipelineDescriptor>>,
+//!     mut shaders: ResMut<Assets<Shader>>,
+//!     mut palettes: ResMut<Assets<TilemapPalette>>,
+//!     tilemap_query: Query<Entity, With<Tilemap>>,
+//! ) {
+//!     let texture = asset_server.load("textures/seasons.png");
+//!     let palette = palettes.add(TilemapPalette::new(texture));
+//!     let pipeline = GridTopology::Square.palette_pipeline(&mut shaders);
+//!     let layer = TilemapLayer {
+//!         kind: LayerKind::Dense,
+//!         pipeline: Some(pipelines.add(pipeline)),
+//!         ..Default::default()
+//!     };
+//!
+//!     let mut materials = LayerMaterials::default();
+//!     materials.insert(0, palette);
+//!     if let Some(entity) = tilemap_query.iter().next() {
+//!         commands.insert_one(entity, materials);
+//!     }
+//! }
+//! ```
+//!
+//! [`GridTopology::palette_pipeline`]: crate::chunk::render::GridTopology::palette_pipeline
+//! [`LayerMaterials`]: crate::material::LayerMaterials
+//! [`Tile::palette`]: crate::tile::Tile::palette
+
+use crate::lib::*;
+
+/// The names of the render resources of a palette, in order.
+const PALETTE_RESOURCE_NAMES: [&str; 2] = ["TilemapPalette_offset", "TilemapPalette_texture"];
+
+/// A palette texture to recolor an indexed color layer with.
+///
+/// The palette is only applied by the render pipeline. Everything that draws
+/// a tilemap on the CPU, which is the [`Rasterizer`], the level of detail
+/// textures and the [`Minimap`], does not know the materials of the layers
+/// and draws the indexed colors as they are stored in the texture atlas.
+///
+/// [`Rasterizer`]: crate::raster::Rasterizer
+/// [`Minimap`]: crate::minimap::Minimap
+#[derive(Debug, Clone)]
+pub struct TilemapPalette {
+    /// The palette texture, with a palette in each row and a color in each
+    /// column.
+    pub texture: Handle<Texture>,
+    /// The amount of rows that the palette of every tile is offset by,
+    /// wrapping around the texture.
+    pub offset: f32,
+    /// The amount of rows per second that the offset changes by.
+    pub speed: f32,
+}
+
+impl TypeUuid for TilemapPalette {
+    const TYPE_UUID: Uuid = Uuid::from_u128(307805249664014659998196686890750351732);
+}
+
+impl TilemapPalette {
+    /// Constructs a new palette from a palette texture.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_render::prelude::*;
+    /// use bevy_tilemap::palette::TilemapPalette;
+    ///
+    /// let texture = Handle::weak(HandleId::random::<Texture>());
+    /// let palette = TilemapPalette::new(texture);
+    ///
+    /// assert_eq!(palette.offset, 0.0);
+    /// ```
+    pub fn new(texture: Handle<Texture>) -> TilemapPalette {
+        TilemapPalette {
+            texture,
+            offset: 0.0,
+            speed: 0.0,
+        }
+    }
+}
+
+impl RenderResources for TilemapPalette {
+    fn render_resources_len(&self) -> usize {
+        PALETTE_RESOURCE_NAMES.len()
+    }
+
+    fn get_render_resource(&self, index: usize) -> Option<&dyn RenderResource> {
+        match index {
+            0 => Some(&self.offset),
+            1 => Some(&self.texture),
+            _ => None,
+        }
+    }
+
+    fn get_render_resource_name(&self, index: usize) -> Option<&str> {
+        PALETTE_RESOURCE_NAMES.get(index).copied()
+    }
+
+    fn iter(&self) -> RenderResourceIterator {
+        RenderResourceIterator::new(self)
+    }
+}
+
+/// Cycles the offset of every palette with a speed.
+pub(crate) fn palette_animation(time: Res<Time>, mut palettes: ResMut<Assets<TilemapPalette>>) {
+    let animated: Vec<HandleId> = palettes
+        .iter()
+        .filter(|(_, palette)| palette.speed != 0.0)
+        .map(|(id, _)| id)
+        .collect();
+    for id in animated.into_iter() {
+        if let Some(palette) = palettes.get_mut(id) {
+            palette.offset += palette.speed * time.delta_seconds();
+        }
+    }
+}
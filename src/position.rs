@@ -0,0 +1,329 @@
+//! Entities which are placed on the tiles of a tilemap.
+//!
+//! An entity with a [`TilePosition`] that is a child of a tilemap entity is
+//! tracked by the tilemap's systems:
+//!
+//! * Its `Transform` is kept at the center of its tile for every topology,
+//! keeping the z translation as is. With a [`TileInterpolation`] it moves
+//! there smoothly instead of jumping from tile to tile.
+//! * The tilemap indexes it by its tile point and chunk, so the entities on a
+//! tile are found with [`Tilemap::entities_at`], and those nearby with
+//! [`Tilemap::entities_in_radius`], [`Tilemap::entities_in_rect`] or
+//! [`Tilemap::entities_in_range`] without looking at every entity.
+//! * If the tilemap automatically spawns chunks, it is hidden while its chunk
+//! is not spawned.
+//! * It enters and exits the [regions] of the tilemap.
+//!
+//! Moving the entity is done by changing its tile position, which is far
+//! cheaper than clearing and inserting a tile for it every frame.
+//!
+//! # Examples
+//! ```
+//! use bevy_ecs::prelude::*;
+//! use bevy_tilemap::{position::{TileInterpolation, TilePosition}, prelude::*};
+//! use bevy_transform::prelude::*;
+//!
+//! fn spawn_dwarf(commands: &mut Commands, tilemap_query: Query<Entity, With<Tilemap>>) {
+//!     for tilemap in tilemap_query.iter() {
+//!         let dwarf = commands
+//!             .spawn((
+//!                 Transform::default(),
+//!                 GlobalTransform::default(),
+//!                 TilePosition::new((3, 4)),
+//!                 TileInterpolation::new(0.25),
+//!             ))
+//!             .current_entity();
+//!         if let Some(dwarf) = dwarf {
+//!             commands.push_children(tilemap, &[dwarf]);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! [`Tilemap::entities_at`]: crate::tilemap::Tilemap::entities_at
+//! [`Tilemap::entities_in_radius`]: crate::tilemap::Tilemap::entities_in_radius
+//! [`Tilemap::entities_in_rect`]: crate::tilemap::Tilemap::entities_in_rect
+//! [`Tilemap::entities_in_range`]: crate::tilemap::Tilemap::entities_in_range
+//! [regions]: crate::region
+
+use crate::{lib::*, Tilemap};
+
+/// The tile point of an entity on the tilemap that is its parent.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct TilePosition {
+    /// The tile point the entity is on.
+    pub point: Point2,
+}
+
+impl TilePosition {
+    /// Constructs a new tile position at a tile point.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::position::TilePosition;
+    ///
+    /// let position = TilePosition::new((3, 4));
+    ///
+    /// assert_eq!(position.point.x, 3);
+    /// assert_eq!(position.point.y, 4);
+    /// ```
+    pub fn new<P: Into<Point2>>(point: P) -> TilePosition {
+        TilePosition {
+            point: point.into(),
+        }
+    }
+}
+
+/// Moves the transform of an entity with a [`TilePosition`] smoothly to the
+/// center of its tile when the tile position changes.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TileInterpolation {
+    /// The seconds it takes to move to the next tile.
+    pub duration: f32,
+    /// The translation that the entity is moving from.
+    from: Vec2,
+    /// The translation that the entity is moving to, once it has been placed.
+    to: Option<Vec2>,
+    /// The seconds since the entity started moving.
+    elapsed: f32,
+}
+
+impl TileInterpolation {
+    /// Constructs a new interpolation which takes a duration in seconds to
+    /// move to the next tile.
+    pub fn new(duration: f32) -> TileInterpolation {
+        TileInterpolation {
+            duration,
+            from: Vec2::zero(),
+            to: None,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Returns `true` if the entity is moving between tiles.
+    pub fn is_moving(&self) -> bool {
+        self.to.is_some() && self.elapsed < self.duration
+    }
+
+    /// Returns the translation of the entity after some seconds passed, moving
+    /// towards a target translation.
+    ///
+    /// The first target that is set is jumped to right away.
+    fn step(&mut self, current: Vec2, target: Vec2, delta: f32) -> Vec2 {
+        match self.to {
+            Some(to) if to == target => {}
+            Some(_) => {
+                self.from = current;
+                self.to = Some(target);
+                self.elapsed = 0.0;
+            }
+            None => {
+                self.to = Some(target);
+                self.elapsed = self.duration;
+                return target;
+            }
+        }
+        self.elapsed = (self.elapsed + delta).min(self.duration.max(0.0));
+        if self.duration <= 0.0 {
+            return target;
+        }
+        let progress = self.elapsed / self.duration;
+        self.from + (target - self.from) * progress
+    }
+}
+
+/// Removes an entity from the bucket of a key, removing the bucket once it is
+/// empty.
+fn remove_from_bucket(buckets: &mut HashMap<Point2, Vec<Entity>>, key: Point2, entity: Entity) {
+    let is_empty = if let Some(entities) = buckets.get_mut(&key) {
+        entities.retain(|other| *other != entity);
+        entities.is_empty()
+    } else {
+        false
+    };
+    if is_empty {
+        buckets.remove(&key);
+    }
+}
+
+/// The entities on each tile and in each chunk of a tilemap.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub(crate) struct TileEntityIndex {
+    /// The entities at each tile point.
+    entities: HashMap<Point2, Vec<Entity>>,
+    /// The entities in each chunk point.
+    chunks: HashMap<Point2, Vec<Entity>>,
+    /// The tile point and chunk point of each entity.
+    points: HashMap<Entity, (Point2, Point2)>,
+}
+
+impl TileEntityIndex {
+    /// Inserts an entity at a tile point in a chunk, moving it if it was
+    /// already indexed.
+    pub(crate) fn insert(&mut self, entity: Entity, point: Point2, chunk_point: Point2) {
+        if self.points.get(&entity) == Some(&(point, chunk_point)) {
+            return;
+        }
+        self.remove(entity);
+        self.points.insert(entity, (point, chunk_point));
+        self.entities.entry(point).or_default().push(entity);
+        self.chunks.entry(chunk_point).or_default().push(entity);
+    }
+
+    /// Removes an entity, returning the tile point it was at.
+    pub(crate) fn remove(&mut self, entity: Entity) -> Option<Point2> {
+        let (point, chunk_point) = self.points.remove(&entity)?;
+        remove_from_bucket(&mut self.entities, point, entity);
+        remove_from_bucket(&mut self.chunks, chunk_point, entity);
+        Some(point)
+    }
+
+    /// Returns the entities at a tile point.
+    pub(crate) fn at(&self, point: Point2) -> &[Entity] {
+        self.entities
+            .get(&point)
+            .map_or(&[], |entities| entities.as_slice())
+    }
+
+    /// Returns the entities in a chunk.
+    pub(crate) fn in_chunk(&self, chunk_point: Point2) -> &[Entity] {
+        self.chunks
+            .get(&chunk_point)
+            .map_or(&[], |entities| entities.as_slice())
+    }
+
+    /// Returns the tile point of an entity, if it is indexed.
+    pub(crate) fn point(&self, entity: Entity) -> Option<Point2> {
+        self.points.get(&entity).map(|(point, _)| *point)
+    }
+}
+
+/// Keeps the translation of entities with a tile position at the center of
+/// their tile, moving them smoothly if they have an interpolation.
+pub(crate) fn tile_position_transforms(
+    time: Res<Time>,
+    tilemap_query: Query<&Tilemap>,
+    mut position_query: Query<(
+        &Parent,
+        &TilePosition,
+        &mut Transform,
+        Option<&mut TileInterpolation>,
+    )>,
+) {
+    let delta = time.delta_seconds();
+    for (parent, position, mut transform, interpolation) in position_query.iter_mut() {
+        let tilemap = if let Ok(tilemap) = tilemap_query.get(**parent) {
+            tilemap
+        } else {
+            continue;
+        };
+        let target = tilemap.tile_translation(position.point);
+        let current = transform.translation.truncate();
+        let translation = if let Some(mut interpolation) = interpolation {
+            interpolation.step(current, target, delta)
+        } else {
+            target
+        };
+        if translation != current {
+            transform.translation.x = translation.x;
+            transform.translation.y = translation.y;
+        }
+    }
+}
+
+/// Indexes the entities with a tile position by their tile point in the
+/// tilemap that is their parent.
+///
+/// Entities are removed from the index once they lose their tile position or
+/// their parent, or are despawned.
+pub(crate) fn tile_position_index(
+    mut owners: Local<HashMap<Entity, Entity>>,
+    mut tilemap_query: Query<&mut Tilemap>,
+    position_query: Query<
+        (Entity, &Parent, &TilePosition),
+        Or<(Changed<TilePosition>, Changed<Parent>)>,
+    >,
+) {
+    let removed = position_query
+        .removed::<TilePosition>()
+        .iter()
+        .chain(position_query.removed::<Parent>().iter());
+    for entity in removed {
+        if let Some(owner) = owners.remove(entity) {
+            if let Ok(mut tilemap) = tilemap_query.get_mut(owner) {
+                tilemap.remove_tile_entity(*entity);
+            }
+        }
+    }
+    for (entity, parent, position) in position_query.iter() {
+        let owner = **parent;
+        if let Some(previous) = owners.insert(entity, owner) {
+            if previous != owner {
+                if let Ok(mut tilemap) = tilemap_query.get_mut(previous) {
+                    tilemap.remove_tile_entity(entity);
+                }
+            }
+        }
+        if let Ok(mut tilemap) = tilemap_query.get_mut(owner) {
+            tilemap.insert_tile_entity(entity, position.point);
+        } else {
+            owners.remove(&entity);
+        }
+    }
+}
+
+/// Hides the entities with a tile position that are in chunks which are not
+/// spawned, for tilemaps that automatically spawn their chunks.
+///
+/// The entities are shown again once their chunk is spawned. Entities that
+/// move into another chunk are shown or hidden by whether it is spawned.
+pub(crate) fn tile_position_streaming(
+    mut spawned_chunks: Local<HashMap<Entity, HashSet<(i32, i32)>>>,
+    tilemap_query: Query<(Entity, &Tilemap)>,
+    moved_query: Query<(Entity, &Parent, &TilePosition), Changed<TilePosition>>,
+    mut visible_query: Query<&mut Visible, With<TilePosition>>,
+) {
+    let mut set_visible = |entity: Entity, is_visible: bool| {
+        if let Ok(mut visible) = visible_query.get_mut(entity) {
+            if visible.is_visible != is_visible {
+                visible.is_visible = is_visible;
+            }
+        }
+    };
+
+    let mut streamed: HashSet<Entity> = HashSet::default();
+    for (tilemap_entity, tilemap) in tilemap_query.iter() {
+        if tilemap.auto_spawn().is_none() {
+            continue;
+        }
+        streamed.insert(tilemap_entity);
+        let current = tilemap.spawned_chunks();
+        let previous = spawned_chunks.entry(tilemap_entity).or_default();
+        if previous == current {
+            continue;
+        }
+        for (x, y) in previous.difference(current) {
+            for entity in tilemap.entities_in_chunk((*x, *y)) {
+                set_visible(*entity, false);
+            }
+        }
+        for (x, y) in current.difference(previous) {
+            for entity in tilemap.entities_in_chunk((*x, *y)) {
+                set_visible(*entity, true);
+            }
+        }
+        *previous = current.clone();
+    }
+    spawned_chunks.retain(|tilemap_entity, _| streamed.contains(tilemap_entity));
+
+    for (entity, parent, position) in moved_query.iter() {
+        if !streamed.contains(&**parent) {
+            continue;
+        }
+        if let Ok((_, tilemap)) = tilemap_query.get(**parent) {
+            let chunk_point = tilemap.point_to_chunk_point(position.point);
+            set_visible(entity, tilemap.spawned_chunks().contains(&chunk_point));
+        }
+    }
+}
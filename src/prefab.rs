@@ -0,0 +1,253 @@
+//! Entities which are spawned for tiles by their sprite index.
+//!
+//! Doors, chests and torches are often placed as tiles in an editor, but need
+//! to be entities with components in the game. The [`TilePrefabs`] component
+//! on a tilemap entity maps sprite indices to functions which spawn an entity
+//! for each tile with that sprite index:
+//!
+//! * When a chunk is spawned, an entity is spawned for each of its tiles with a
+//! prefab, as a child of the chunk layer of the tile.
+//! * When the tiles of a spawned chunk change, the entities of tiles that were
+//! cleared or changed to another sprite index are despawned and entities
+//! are spawned for new tiles.
+//! * When a chunk is despawned, the entities are despawned with it.
+//!
+//! Entities with a `Visible` component are shown and hidden along with their
+//! chunk layer, such as when it is culled, its layer is hidden or the chunk is
+//! drawn with its level of detail sprite instead.
+//!
+//! The entity is placed at the center of its tile by setting its `Transform`
+//! to the translation of [`PrefabTile`], which is relative to the chunk layer.
+//!
+//! # Examples
+//! ```
+//! use bevy_ecs::prelude::*;
+//! use bevy_tilemap::{prefab::{PrefabTile, TilePrefabs}, prelude::*};
+//! use bevy_transform::prelude::*;
+//!
+//! struct Door {
+//!     open: bool,
+//! }
+//!
+//! fn setup(commands: &mut Commands, tilemap_query: Query<Entity, With<Tilemap>>) {
+//!     let mut prefabs = TilePrefabs::default();
+//!     prefabs.insert(7, |commands: &mut Commands, tile: &PrefabTile| {
+//!         commands
+//!             .spawn((
+//!                 Transform::from_translation(tile.translation),
+//!                 GlobalTransform::default(),
+//!                 Door { open: false },
+//!             ))
+//!             .current_entity()
+//!     });
+//!     if let Some(entity) = tilemap_query.iter().next() {
+//!         commands.insert_one(entity, prefabs);
+//!     }
+//! }
+//! ```
+
+use crate::{event::TilemapChunkEvent, lib::*, Tilemap};
+
+/// The tile that a prefab is spawned for.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PrefabTile {
+    /// The tile point of the tile.
+    pub point: Point2,
+    /// The z order of the layer of the tile.
+    pub z_order: usize,
+    /// The sprite index of the tile.
+    pub sprite_index: usize,
+    /// The translation of the center of the tile relative to its chunk layer,
+    /// which the spawned entity is a child of.
+    pub translation: Vec3,
+}
+
+/// A function which spawns an entity for a tile, returning the entity.
+type PrefabFn = Box<dyn Fn(&mut Commands, &PrefabTile) -> Option<Entity> + Send + Sync>;
+
+/// The prefab entity of each tile in a chunk, with the sprite index it was
+/// spawned for, by z order and tile index.
+type ChunkPrefabs = HashMap<(usize, usize), (usize, Entity)>;
+
+/// A component for tilemap entities which spawns entities for the tiles of
+/// spawned chunks by their sprite index.
+///
+/// Prefabs that are inserted or removed only apply to chunks that are spawned
+/// or modified afterwards.
+#[derive(Default)]
+pub struct TilePrefabs {
+    /// The prefab functions by sprite index.
+    prefabs: HashMap<usize, PrefabFn>,
+    /// The spawned prefab entities by chunk point.
+    spawned: HashMap<Point2, ChunkPrefabs>,
+}
+
+impl TilePrefabs {
+    /// Sets the prefab of a sprite index, replacing the one it had before.
+    pub fn insert<F>(&mut self, sprite_index: usize, prefab: F)
+    where
+        F: Fn(&mut Commands, &PrefabTile) -> Option<Entity> + Send + Sync + 'static,
+    {
+        self.prefabs.insert(sprite_index, Box::new(prefab));
+    }
+
+    /// Removes the prefab of a sprite index, returning `true` if it had one.
+    pub fn remove(&mut self, sprite_index: usize) -> bool {
+        self.prefabs.remove(&sprite_index).is_some()
+    }
+
+    /// Returns `true` if a sprite index has a prefab.
+    pub fn contains(&self, sprite_index: usize) -> bool {
+        self.prefabs.contains_key(&sprite_index)
+    }
+
+    /// Returns the prefab entity spawned for a tile of a layer, if any.
+    pub fn get_entity<P: Into<Point2>>(
+        &self,
+        tilemap: &Tilemap,
+        point: P,
+        z_order: usize,
+    ) -> Option<Entity> {
+        let point: Point2 = point.into();
+        let chunk_point: Point2 = tilemap.point_to_chunk_point(point).into();
+        let tile_point = tilemap.point_to_tile_point(point);
+        let index = tilemap
+            .chunk_dimensions()
+            .encode_point_unchecked(tile_point);
+        self.spawned
+            .get(&chunk_point)?
+            .get(&(z_order, index))
+            .map(|(_, entity)| *entity)
+    }
+
+    /// Spawns and despawns the prefab entities of a spawned chunk so that they
+    /// match its tiles.
+    fn sync_chunk(&mut self, commands: &mut Commands, tilemap: &Tilemap, chunk_point: Point2) {
+        let prefabs = &self.prefabs;
+        let records = self.spawned.entry(chunk_point).or_default();
+        let chunk = if let Some(chunk) = tilemap.get_chunk(&chunk_point) {
+            chunk
+        } else {
+            for (_, entity) in records.values() {
+                commands.despawn_recursive(*entity);
+            }
+            records.clear();
+            return;
+        };
+
+        let chunk_dimensions = tilemap.chunk_dimensions();
+        let mut wanted: HashMap<(usize, usize), usize> = HashMap::default();
+        for z_order in 0..tilemap.layers().len() {
+            for index in 0..chunk_dimensions.area() as usize {
+                if let Some(tile) = chunk.get_tile(z_order, index) {
+                    if tile.color.a() > 0.0 && prefabs.contains_key(&tile.index) {
+                        wanted.insert((z_order, index), tile.index);
+                    }
+                }
+            }
+        }
+
+        records.retain(|key, (sprite_index, entity)| {
+            if wanted.get(key) == Some(sprite_index) {
+                true
+            } else {
+                commands.despawn_recursive(*entity);
+                false
+            }
+        });
+
+        let width = chunk_dimensions.width as i32;
+        let height = chunk_dimensions.height as i32;
+        let chunk_translation = tilemap.chunk_translation(chunk_point);
+        for ((z_order, index), sprite_index) in wanted.into_iter() {
+            if records.contains_key(&(z_order, index)) {
+                continue;
+            }
+            let (parent, prefab) = match (chunk.get_entity(z_order), prefabs.get(&sprite_index)) {
+                (Some(parent), Some(prefab)) => (parent, prefab),
+                _ => continue,
+            };
+            let tile_point = chunk_dimensions.decode_point_unchecked(index);
+            let point = Point2::new(
+                tile_point.x + width * chunk_point.x - width / 2,
+                tile_point.y + height * chunk_point.y - height / 2,
+            );
+            let translation = tilemap.tile_translation(point) - chunk_translation;
+            let tile = PrefabTile {
+                point,
+                z_order,
+                sprite_index,
+                translation: translation.extend(0.0),
+            };
+            if let Some(entity) = prefab(commands, &tile) {
+                commands.push_children(parent, &[entity]);
+                records.insert((z_order, index), (sprite_index, entity));
+            }
+        }
+    }
+}
+
+/// Spawns the prefab entities of newly spawned chunks and keeps them in sync
+/// with the tiles of modified chunks.
+///
+/// The entities of despawned chunks are despawned along with the chunk layers
+/// they are children of, so they only need to be forgotten.
+pub(crate) fn tile_prefabs(
+    commands: &mut Commands,
+    mut reader: Local<EventReader<TilemapChunkEvent>>,
+    chunk_events: Res<Events<TilemapChunkEvent>>,
+    mut tilemap_query: Query<(Entity, &Tilemap, &mut TilePrefabs)>,
+) {
+    let mut modified_chunks: HashMap<Entity, HashSet<Point2>> = HashMap::default();
+    for event in reader.iter(&chunk_events) {
+        if let TilemapChunkEvent::Modified { tilemap, point, .. } = event {
+            modified_chunks.entry(*tilemap).or_default().insert(*point);
+        }
+    }
+
+    for (tilemap_entity, tilemap, mut prefabs) in tilemap_query.iter_mut() {
+        let modified = modified_chunks.remove(&tilemap_entity).unwrap_or_default();
+        let spawned = tilemap.spawned_chunks();
+        prefabs
+            .spawned
+            .retain(|point, _| spawned.contains(&(point.x, point.y)));
+        for (x, y) in spawned.iter() {
+            let point = Point2::new(*x, *y);
+            if !prefabs.spawned.contains_key(&point) || modified.contains(&point) {
+                prefabs.sync_chunk(commands, tilemap, point);
+            }
+        }
+    }
+}
+
+/// Shows and hides the prefab entities along with the chunk layers they are
+/// children of, as `Visible` is not inherited by children.
+pub(crate) fn prefab_visibility(
+    tilemap_query: Query<&TilePrefabs>,
+    parent_query: Query<&Parent>,
+    mut visible_query: Query<&mut Visible>,
+) {
+    for prefabs in tilemap_query.iter() {
+        for (_, entity) in prefabs
+            .spawned
+            .values()
+            .flat_map(|records| records.values())
+        {
+            let parent = if let Ok(parent) = parent_query.get(*entity) {
+                **parent
+            } else {
+                continue;
+            };
+            let is_visible = if let Ok(visible) = visible_query.get_mut(parent) {
+                visible.is_visible
+            } else {
+                continue;
+            };
+            if let Ok(mut visible) = visible_query.get_mut(*entity) {
+                if visible.is_visible != is_visible {
+                    visible.is_visible = is_visible;
+                }
+            }
+        }
+    }
+}
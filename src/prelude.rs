@@ -5,12 +5,18 @@
 //! The current version of this prelude (version 0) is located in
 //! [`bevy_tilemap::prelude::v0`], and re-exports the following.
 //!
-//! * [`bevy_tilemap::chunk`]::[`LayerKind`], the only public part
-//! of `chunk` module is the kind of layer you need to specify to create.
+//! * [`bevy_tilemap::camera`]::[`TilemapCamera`], a marker for cameras that
+//! tilemaps are spawned and culled for.
+//! * [`bevy_tilemap::chunk`]::[`LayerKind`], the kind of layer you need to
+//! specify to create.
+//! * [`GridTopology`], [`LayerBlend`] and [`LayerDepth`], the grid of the
+//! tilemap and how a layer is blended and depth tested.
 //! * [`bevy_tilemap::default_plugin`]::[`TilemapDefaultPlugins`], the
 //! default plugins for the library.
 //! * [`bevy_tilemap::entity`]::[`TilemapBundle`], the component bundle
 //! for spawning with a Tilemap.
+//! * [`bevy_tilemap::minimap`]::{[`Minimap`], [`MinimapMarker`]}, components
+//! to draw an overview of a tilemap into a texture.
 //! * [`bevy_tilemap::tile`]::[`Tile`], a sprite tile which
 //! holds minimal amount of data for small data sizes.
 //! * [`bevy_tilemap::tilemap`]::{[`Tilemap`], [`TilemapBuilder`]},
@@ -19,9 +25,11 @@
 //! a collection of systems, components and assets to be used in a Bevy app.
 //!
 //! [`bevy_tilemap::prelude::v0`]: crate::prelude::v0
+//! [`bevy_tilemap::camera`]: crate::camera
 //! [`bevy_tilemap::default_plugin`]: crate::default_plugin
 //! [`bevy_tilemap::chunk`]: crate::chunk
 //! [`bevy_tilemap::entity`]: crate::entity
+//! [`bevy_tilemap::minimap`]: crate::minimap
 //! [`bevy_tilemap::tile`]: crate::tile
 //! [`bevy_tilemap::tilemap`]: crate::tilemap
 //! [`bevy_tilemap`]: crate
@@ -29,9 +37,14 @@
 /// Version 0 prelude.
 pub mod v0 {
     pub use crate::{
-        chunk::{render::GridTopology, LayerKind},
+        camera::TilemapCamera,
+        chunk::{
+            render::{GridTopology, LayerBlend, LayerDepth},
+            LayerKind,
+        },
         default_plugin::TilemapDefaultPlugins,
         entity::TilemapBundle,
+        minimap::{Minimap, MinimapMarker},
         tile::Tile,
         tilemap::{Tilemap, TilemapBuilder, TilemapLayer},
         Tilemap2DPlugin,
@@ -0,0 +1,264 @@
+//! Drawing tilemaps into images on the CPU.
+//!
+//! This does not need a GPU or even a running app, which makes it useful for
+//! thumbnails of save slots, minimaps and golden image tests. The tiles are
+//! drawn the same way that the renderer draws them, from the lowest layer to
+//! the highest, tinted, and laid out for every [`GridTopology`].
+//!
+//! The source texture of the texture atlas must be RGBA with 8 bits per
+//! channel, which is what the `TextureAtlasBuilder` creates.
+//!
+//! [`GridTopology`]: crate::chunk::render::GridTopology
+
+use crate::{
+    camera::WorldRect,
+    chunk::raster::{is_supported_texture, Canvas},
+    lib::*,
+    tilemap::{ErrorKind, TilemapResult},
+    Tilemap,
+};
+
+/// An RGBA image with 8 bits per channel of a drawn tilemap.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TilemapImage {
+    /// The width in pixels.
+    width: u32,
+    /// The height in pixels.
+    height: u32,
+    /// The RGBA pixels, from the top row down.
+    pixels: Vec<u8>,
+}
+
+impl TilemapImage {
+    /// The width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the RGBA pixels, from the top row down.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Consumes the image and returns its RGBA pixels, from the top row down.
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    /// Returns the RGBA color of a pixel, counted from the top left corner, if
+    /// it is within the image.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        match self.pixels.get(index..index + 4) {
+            Some([r, g, b, a]) => Some([*r, *g, *b, *a]),
+            _ => None,
+        }
+    }
+
+    /// Converts the image into a texture, such as to show it in the UI.
+    pub fn into_texture(self) -> Texture {
+        Texture::new(
+            Extent3d::new(self.width, self.height, 1),
+            TextureDimension::D2,
+            self.pixels,
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    /// Encodes the image as a PNG into a writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image could not be encoded or written.
+    #[cfg(feature = "png")]
+    pub fn write_png<W: Write>(&self, writer: W) -> TilemapResult<()> {
+        PngEncoder::new(writer)
+            .encode(&self.pixels, self.width, self.height, ColorType::Rgba8)
+            .map_err(|err| ErrorKind::ImageError(err.to_string()).into())
+    }
+
+    /// Saves the image as a PNG file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be created or the image could
+    /// not be encoded.
+    #[cfg(feature = "png")]
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> TilemapResult<()> {
+        let file = File::create(path).map_err(|err| ErrorKind::ImageError(err.to_string()))?;
+        self.write_png(file)
+    }
+}
+
+/// Draws a region of a tilemap into an image on the CPU.
+///
+/// By default every chunk and layer is drawn at one pixel per tile pixel.
+/// The region is in the space of the tilemap, before its transform, which is
+/// the same space as [`Tilemap::chunk_bounds`].
+///
+/// Layers drawn with a [`TilemapPalette`] are drawn without it.
+///
+/// # Examples
+/// ```
+/// use bevy_asset::{prelude::*, HandleId};
+/// use bevy_math::Vec2;
+/// use bevy_render::texture::{Extent3d, Texture, TextureDimension, TextureFormat};
+/// use bevy_sprite::prelude::*;
+/// use bevy_tilemap::{prelude::*, raster::Rasterizer};
+///
+/// // A single red sprite.
+/// let texture = Texture::new_fill(
+///     Extent3d::new(32, 32, 1),
+///     TextureDimension::D2,
+///     &[255, 0, 0, 255],
+///     TextureFormat::Rgba8UnormSrgb,
+/// );
+/// let texture_handle = Handle::weak(HandleId::random::<Texture>());
+/// let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(32.0, 32.0), 1, 1);
+///
+/// // In production use a strong handle from an actual source.
+/// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+///
+/// let mut tilemap = TilemapBuilder::new()
+///     .texture_atlas(texture_atlas_handle)
+///     .dimensions(1, 1)
+///     .chunk_dimensions(2, 2)
+///     .tile_dimensions(32, 32)
+///     .finish()
+///     .unwrap();
+///
+/// tilemap.insert_chunk((0, 0)).unwrap();
+/// tilemap.insert_tiles(vec![Tile { point: (0, 0), sprite_index: 0, ..Default::default() }]).unwrap();
+///
+/// let image = Rasterizer::new(&tilemap, &texture_atlas, &texture)
+///     .scale(0.5)
+///     .rasterize()
+///     .unwrap();
+///
+/// assert_eq!(image.width(), 32);
+/// assert_eq!(image.height(), 32);
+/// // The tile is in the top right quarter.
+/// assert_eq!(image.get_pixel(24, 8), Some([255, 0, 0, 255]));
+/// assert_eq!(image.get_pixel(8, 24), Some([0, 0, 0, 0]));
+/// ```
+///
+/// [`TilemapPalette`]: crate::palette::TilemapPalette
+#[derive(Clone, Debug)]
+pub struct Rasterizer<'a> {
+    /// The tilemap to draw.
+    tilemap: &'a Tilemap,
+    /// The texture atlas of the tilemap.
+    texture_atlas: &'a TextureAtlas,
+    /// The source texture of the texture atlas.
+    texture: &'a Texture,
+    /// The region to draw, or every chunk if not set.
+    region: Option<WorldRect>,
+    /// The z orders of the layers to draw, or every layer if not set.
+    layers: Option<Vec<usize>>,
+    /// The amount of pixels per tile pixel.
+    scale: f32,
+}
+
+impl<'a> Rasterizer<'a> {
+    /// Constructs a new rasterizer for a tilemap with its texture atlas and
+    /// the atlas' source texture.
+    pub fn new(
+        tilemap: &'a Tilemap,
+        texture_atlas: &'a TextureAtlas,
+        texture: &'a Texture,
+    ) -> Rasterizer<'a> {
+        Rasterizer {
+            tilemap,
+            texture_atlas,
+            texture,
+            region: None,
+            layers: None,
+            scale: 1.0,
+        }
+    }
+
+    /// Sets the region of the tilemap to draw.
+    pub fn region(mut self, region: WorldRect) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    /// Sets the layers to draw by their z order. They are always drawn from
+    /// the lowest z order to the highest.
+    pub fn layers(mut self, z_orders: &[usize]) -> Self {
+        let mut layers = z_orders.to_vec();
+        layers.sort_unstable();
+        layers.dedup();
+        self.layers = Some(layers);
+        self
+    }
+
+    /// Sets the amount of pixels in the image per tile pixel, such as `0.25`
+    /// for a thumbnail a quarter of the size.
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Consumes the rasterizer and draws the image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the texture is not RGBA with 8 bits per channel, if
+    /// no region was set and the tilemap has no chunks, or if the image would
+    /// be too large at the scale.
+    pub fn rasterize(self) -> TilemapResult<TilemapImage> {
+        if !is_supported_texture(self.texture) {
+            return Err(ErrorKind::UnsupportedTextureFormat.into());
+        }
+        let tilemap = self.tilemap;
+        let region = if let Some(region) = self.region {
+            region
+        } else {
+            tilemap
+                .chunks()
+                .keys()
+                .map(|point| tilemap.chunk_bounds(*point))
+                .fold(None, |region: Option<WorldRect>, bounds| {
+                    Some(region.map_or(bounds, |region| region.union(&bounds)))
+                })
+                .ok_or(ErrorKind::MissingChunk)?
+        };
+        let layers = self
+            .layers
+            .unwrap_or_else(|| (0..tilemap.layers().len()).collect());
+
+        let mut canvas = Canvas::new(region, self.scale)?;
+        // Layers are drawn across every chunk before the next so that the
+        // layers of neighbouring chunks overlap as they do when rendered.
+        for z_order in layers {
+            for (point, chunk) in tilemap.chunks() {
+                if !tilemap.chunk_bounds(*point).intersects(&region) {
+                    continue;
+                }
+                canvas.draw_chunk_layer(
+                    tilemap,
+                    chunk,
+                    z_order,
+                    tilemap.chunk_translation(*point),
+                    self.texture_atlas,
+                    self.texture,
+                );
+            }
+        }
+
+        Ok(TilemapImage {
+            width: canvas.width(),
+            height: canvas.height(),
+            pixels: canvas.into_pixels(),
+        })
+    }
+}
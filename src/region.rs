@@ -0,0 +1,278 @@
+//! Named regions of tiles with properties, such as spawn areas, damage zones
+//! or the bounds of rooms.
+//!
+//! A [`Region`] is either a rectangle or a set of tile points, along with a
+//! map of properties for the game to interpret. Regions are inserted into a
+//! tilemap by name and every tile point of them is indexed, so that finding
+//! the regions at a point with [`Tilemap::regions_at`] does not depend on how
+//! many regions there are. Regions may overlap.
+//!
+//! Entities with a [`TilePosition`] that are children of a tilemap entity
+//! send a [`RegionEvent`] whenever they enter or exit one of its regions.
+//!
+//! # Examples
+//! ```
+//! use bevy_asset::{prelude::*, HandleId};
+//! use bevy_sprite::prelude::*;
+//! use bevy_tilemap::{prelude::*, region::Region};
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+//!
+//! let lava = Region::tiles(vec![(4, 4), (4, 5), (5, 5)]).with_property("damage", "10");
+//! tilemap.insert_region("lava", lava);
+//! tilemap.insert_region("cave", Region::rect((0, 0), (9, 9)));
+//!
+//! assert_eq!(tilemap.regions_at((4, 5)), ["lava", "cave"]);
+//! assert_eq!(tilemap.regions_at((1, 1)), ["cave"]);
+//! assert!(tilemap.regions_at((10, 10)).is_empty());
+//! ```
+//!
+//! [`Tilemap::regions_at`]: crate::tilemap::Tilemap::regions_at
+//! [`TilePosition`]: crate::position::TilePosition
+//! [`RegionEvent`]: crate::event::RegionEvent
+
+use crate::{event::RegionEvent, lib::*, position::TilePosition, Tilemap};
+
+/// The tile points that a region covers.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Debug)]
+pub enum RegionArea {
+    /// Every tile point in a rectangle, including both corners.
+    Rect {
+        /// The corner with the lowest coordinates.
+        min: Point2,
+        /// The corner with the highest coordinates.
+        max: Point2,
+    },
+    /// A set of tile points, which do not need to be connected.
+    Tiles(HashSet<Point2>),
+}
+
+impl RegionArea {
+    /// Returns `true` if the area covers a tile point.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::region::Region;
+    ///
+    /// let region = Region::rect((0, 0), (2, 2));
+    ///
+    /// assert!(region.area.contains((2, 0)));
+    /// assert!(!region.area.contains((3, 0)));
+    /// ```
+    pub fn contains<P: Into<Point2>>(&self, point: P) -> bool {
+        let point: Point2 = point.into();
+        match self {
+            RegionArea::Rect { min, max } => {
+                point.x >= min.x && point.x <= max.x && point.y >= min.y && point.y <= max.y
+            }
+            RegionArea::Tiles(points) => points.contains(&point),
+        }
+    }
+
+    /// Returns every tile point that the area covers.
+    fn points(&self) -> Vec<Point2> {
+        match self {
+            RegionArea::Rect { min, max } => (min.y..=max.y)
+                .flat_map(|y| (min.x..=max.x).map(move |x| Point2::new(x, y)))
+                .collect(),
+            RegionArea::Tiles(points) => points.iter().copied().collect(),
+        }
+    }
+}
+
+/// A named area of a tilemap with properties.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct Region {
+    /// The tile points that the region covers.
+    pub area: RegionArea,
+    /// The properties of the region, such as the amount of damage a zone does.
+    pub properties: HashMap<String, String>,
+}
+
+impl Region {
+    /// Constructs a new region of every tile point in a rectangle, including
+    /// both corners.
+    ///
+    /// The corners are sorted, so any two opposite corners may be given.
+    pub fn rect<P: Into<Point2>>(corner: P, opposite: P) -> Region {
+        let corner: Point2 = corner.into();
+        let opposite: Point2 = opposite.into();
+        Region {
+            area: RegionArea::Rect {
+                min: Point2::new(corner.x.min(opposite.x), corner.y.min(opposite.y)),
+                max: Point2::new(corner.x.max(opposite.x), corner.y.max(opposite.y)),
+            },
+            properties: HashMap::default(),
+        }
+    }
+
+    /// Constructs a new region of a set of tile points.
+    pub fn tiles<P, I>(points: I) -> Region
+    where
+        P: Into<Point2>,
+        I: IntoIterator<Item = P>,
+    {
+        Region {
+            area: RegionArea::Tiles(points.into_iter().map(|point| point.into()).collect()),
+            properties: HashMap::default(),
+        }
+    }
+
+    /// Sets a property of the region and returns it.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::region::Region;
+    ///
+    /// let region = Region::rect((0, 0), (4, 4)).with_property("spawn", "goblin");
+    ///
+    /// assert_eq!(region.property("spawn"), Some("goblin"));
+    /// assert_eq!(region.property("damage"), None);
+    /// ```
+    pub fn with_property<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Region {
+        self.properties.insert(key.into(), value.into());
+        self
+    }
+
+    /// Returns the value of a property, if it is set.
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(|value| value.as_str())
+    }
+}
+
+/// The regions of a tilemap, indexed by every tile point they cover.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Debug, Default)]
+pub(crate) struct TilemapRegions {
+    /// The regions by their names.
+    regions: HashMap<String, Region>,
+    /// The names of the regions at each tile point, in order of insertion.
+    index: HashMap<Point2, Vec<String>>,
+}
+
+impl TilemapRegions {
+    /// Inserts a region, returning the region of the same name that it
+    /// replaced, if any.
+    pub(crate) fn insert(&mut self, name: String, region: Region) -> Option<Region> {
+        let replaced = self.remove(&name);
+        for point in region.area.points() {
+            self.index.entry(point).or_default().push(name.clone());
+        }
+        self.regions.insert(name, region);
+        replaced
+    }
+
+    /// Removes a region by its name, returning it if it existed.
+    pub(crate) fn remove(&mut self, name: &str) -> Option<Region> {
+        let region = self.regions.remove(name)?;
+        for point in region.area.points() {
+            let is_empty = if let Some(names) = self.index.get_mut(&point) {
+                names.retain(|other| other != name);
+                names.is_empty()
+            } else {
+                false
+            };
+            if is_empty {
+                self.index.remove(&point);
+            }
+        }
+        Some(region)
+    }
+
+    /// Returns a region by its name.
+    pub(crate) fn get(&self, name: &str) -> Option<&Region> {
+        self.regions.get(name)
+    }
+
+    /// Returns an iterator over every region and its name.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, &Region)> {
+        self.regions
+            .iter()
+            .map(|(name, region)| (name.as_str(), region))
+    }
+
+    /// Returns the names of the regions at a tile point.
+    pub(crate) fn at(&self, point: Point2) -> &[String] {
+        self.index.get(&point).map_or(&[], |names| names.as_slice())
+    }
+}
+
+/// The tilemap and the regions that each tracked entity was last in.
+type RegionOccupants = HashMap<Entity, (Entity, Vec<String>)>;
+
+/// Sends the events of entities with a tile position entering and exiting the
+/// regions of their tilemap.
+///
+/// Entities that lose their tile position or are despawned exit every region
+/// they were in.
+pub(crate) fn region_events(
+    mut events: ResMut<Events<RegionEvent>>,
+    mut occupants: Local<RegionOccupants>,
+    tilemap_query: Query<&Tilemap>,
+    position_query: Query<(Entity, &Parent, &TilePosition)>,
+) {
+    let mut tracked: HashSet<Entity> = HashSet::default();
+    for (entity, parent, position) in position_query.iter() {
+        let tilemap_entity = **parent;
+        let tilemap = if let Ok(tilemap) = tilemap_query.get(tilemap_entity) {
+            tilemap
+        } else {
+            continue;
+        };
+        tracked.insert(entity);
+        let current = tilemap.regions_at(position.point);
+        let (previous_tilemap, previous) = occupants
+            .remove(&entity)
+            .unwrap_or_else(|| (tilemap_entity, Vec::new()));
+        if previous_tilemap == tilemap_entity && previous.as_slice() == current {
+            occupants.insert(entity, (tilemap_entity, previous));
+            continue;
+        }
+
+        let same_tilemap = previous_tilemap == tilemap_entity;
+        let entered: Vec<String> = current
+            .iter()
+            .filter(|region| !same_tilemap || !previous.contains(region))
+            .cloned()
+            .collect();
+        for region in previous.into_iter() {
+            if !same_tilemap || !current.contains(&region) {
+                events.send(RegionEvent::Exited {
+                    tilemap: previous_tilemap,
+                    entity,
+                    region,
+                });
+            }
+        }
+        for region in entered.into_iter() {
+            events.send(RegionEvent::Entered {
+                tilemap: tilemap_entity,
+                entity,
+                region,
+            });
+        }
+        occupants.insert(entity, (tilemap_entity, current.to_vec()));
+    }
+
+    let stale: Vec<Entity> = occupants
+        .keys()
+        .filter(|entity| !tracked.contains(entity))
+        .copied()
+        .collect();
+    for entity in stale.into_iter() {
+        if let Some((tilemap, previous)) = occupants.remove(&entity) {
+            for region in previous.into_iter() {
+                events.send(RegionEvent::Exited {
+                    tilemap,
+                    entity,
+                    region,
+                });
+            }
+        }
+    }
+}
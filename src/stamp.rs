@@ -0,0 +1,447 @@
+//! Copying, pasting and drawing tiles in bulk.
+//!
+//! A [`TileStamp`] is a group of tiles across one or more layers, with points
+//! relative to its bottom left corner. Stamps are copied out of a tilemap with
+//! [`Tilemap::copy_rect`] or [`Tilemap::copy_tiles`], can be rotated and
+//! flipped and are pasted back with [`Tilemap::paste`] at any offset.
+//!
+//! Along with those, tiles are drawn in lines, rectangles and circles with
+//! [`Tilemap::draw_line`], [`Tilemap::draw_rect`] and [`Tilemap::draw_circle`]
+//! and contiguous areas are filled with [`Tilemap::flood_fill`]. Every one of
+//! these reads and writes the tiles chunk by chunk, sending a single event per
+//! modified chunk.
+//!
+//! Stamps are also assets, so that rooms and other templates made by hand can
+//! be reused across maps. With the `ron-stamps` feature they are loaded from
+//! RON files by the `TileStampLoader`. Before pasting a stamp,
+//! [`Tilemap::stamp_fits`] checks that it does not overlap any tiles.
+//!
+//! # Examples
+//! ```
+//! use bevy_asset::{prelude::*, HandleId};
+//! use bevy_render::prelude::*;
+//! use bevy_sprite::prelude::*;
+//! use bevy_tilemap::{chunk::RawTile, prelude::*};
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! let mut tilemap = TilemapBuilder::new()
+//!     .texture_atlas(texture_atlas_handle)
+//!     .tile_dimensions(32, 32)
+//!     .auto_chunk()
+//!     .finish()
+//!     .unwrap();
+//!
+//! let wall = RawTile { index: 1, color: Color::WHITE, palette: 0 };
+//! tilemap.draw_rect((0, 0), (4, 2), 0, wall, false).unwrap();
+//!
+//! let room = tilemap.copy_rect((0, 0), (4, 2), &[0]);
+//! assert_eq!(room.len(), 12);
+//!
+//! // Paste the room turned on its side next to the first one.
+//! tilemap.paste(&room.rotated(1), (10, 0)).unwrap();
+//! assert_eq!(tilemap.get_tile((12, 4), 0), Some(&wall));
+//! assert_eq!(tilemap.get_tile((11, 1), 0), None);
+//! ```
+//!
+//! [`Tilemap::copy_rect`]: crate::tilemap::Tilemap::copy_rect
+//! [`Tilemap::copy_tiles`]: crate::tilemap::Tilemap::copy_tiles
+//! [`Tilemap::paste`]: crate::tilemap::Tilemap::paste
+//! [`Tilemap::draw_line`]: crate::tilemap::Tilemap::draw_line
+//! [`Tilemap::draw_rect`]: crate::tilemap::Tilemap::draw_rect
+//! [`Tilemap::draw_circle`]: crate::tilemap::Tilemap::draw_circle
+//! [`Tilemap::flood_fill`]: crate::tilemap::Tilemap::flood_fill
+//! [`Tilemap::stamp_fits`]: crate::tilemap::Tilemap::stamp_fits
+
+use crate::{chunk::RawTile, lib::*, tile::Tile};
+
+/// A tile of a stamp.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct StampTile {
+    /// The point of the tile relative to the bottom left corner of the stamp.
+    pub point: Point2,
+    /// The z order of the layer of the tile.
+    pub z_order: usize,
+    /// The tile.
+    pub tile: RawTile,
+}
+
+/// A group of tiles across layers which can be pasted into a tilemap.
+///
+/// Besides its tiles, a stamp may have data layers of values at its points,
+/// such as where monsters spawn, and named anchor points, such as its doors.
+/// Data layers are not pasted into the tilemap and are read with the offset
+/// that the stamp was pasted at.
+///
+/// Rotating and flipping a stamp moves it back so that the bottom left corner
+/// of its tiles is at the origin again, along with its data and anchors. These
+/// are done on the tile points as is, so they are only true to the shape of
+/// the tiles for square topologies and rotated or flipped stamps can not be
+/// pasted into hex tilemaps.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct TileStamp {
+    /// The tiles of the stamp.
+    tiles: Vec<StampTile>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    /// The values at points of each data layer by name.
+    data: HashMap<String, HashMap<Point2, String>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    /// The anchor points by name.
+    anchors: HashMap<String, Point2>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    /// If the stamp was rotated or flipped.
+    transformed: bool,
+}
+
+impl TypeUuid for TileStamp {
+    const TYPE_UUID: Uuid = Uuid::from_u128(323659512495311727420188182973743643732);
+}
+
+impl TileStamp {
+    /// Constructs a new stamp from tiles, moving them so that the bottom left
+    /// corner of the stamp is at the origin.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_render::prelude::*;
+    /// use bevy_tilemap::{chunk::RawTile, stamp::{StampTile, TileStamp}};
+    /// use bevy_tilemap_types::point::Point2;
+    ///
+    /// let tile = RawTile { index: 2, color: Color::WHITE, palette: 0 };
+    /// let stamp = TileStamp::new(vec![
+    ///     StampTile { point: Point2::new(5, 5), z_order: 0, tile },
+    ///     StampTile { point: Point2::new(6, 7), z_order: 0, tile },
+    /// ]);
+    ///
+    /// assert_eq!(stamp.tiles()[0].point, Point2::new(0, 0));
+    /// assert_eq!(stamp.tiles()[1].point, Point2::new(1, 2));
+    /// ```
+    pub fn new<I: IntoIterator<Item = StampTile>>(tiles: I) -> TileStamp {
+        let mut stamp = TileStamp {
+            tiles: tiles.into_iter().collect(),
+            ..Default::default()
+        };
+        stamp.normalize();
+        stamp
+    }
+
+    /// Constructs a new stamp from tiles which are already relative to its
+    /// bottom left corner.
+    pub(crate) fn from_tiles(tiles: Vec<StampTile>) -> TileStamp {
+        TileStamp {
+            tiles,
+            ..Default::default()
+        }
+    }
+
+    /// Sets a named anchor point relative to the bottom left corner of the
+    /// stamp and returns it.
+    pub fn with_anchor<S: Into<String>, P: Into<Point2>>(mut self, name: S, point: P) -> TileStamp {
+        self.anchors.insert(name.into(), point.into());
+        self
+    }
+
+    /// Sets a value at a point of a data layer and returns the stamp.
+    pub fn with_data<S, P, V>(mut self, layer: S, point: P, value: V) -> TileStamp
+    where
+        S: Into<String>,
+        P: Into<Point2>,
+        V: Into<String>,
+    {
+        self.data
+            .entry(layer.into())
+            .or_default()
+            .insert(point.into(), value.into());
+        self
+    }
+
+    /// Returns an anchor point by its name.
+    pub fn anchor(&self, name: &str) -> Option<Point2> {
+        self.anchors.get(name).copied()
+    }
+
+    /// Returns the offset to paste the stamp at so that an anchor lands on a
+    /// tile point.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::stamp::TileStamp;
+    /// use bevy_tilemap_types::point::Point2;
+    ///
+    /// let room = TileStamp::default().with_anchor("door", (2, 0));
+    ///
+    /// assert_eq!(room.anchor_offset("door", (10, 5)), Some(Point2::new(8, 5)));
+    /// assert_eq!(room.anchor_offset("window", (10, 5)), None);
+    /// ```
+    pub fn anchor_offset<P: Into<Point2>>(&self, name: &str, point: P) -> Option<Point2> {
+        self.anchor(name).map(|anchor| point.into() - anchor)
+    }
+
+    /// Returns the values at the points of a data layer, if the stamp has it.
+    pub fn data_layer(&self, layer: &str) -> Option<&HashMap<Point2, String>> {
+        self.data.get(layer)
+    }
+
+    /// Returns the value at a point of a data layer, if any.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::stamp::TileStamp;
+    ///
+    /// let room = TileStamp::default().with_data("spawns", (1, 1), "goblin");
+    ///
+    /// assert_eq!(room.data("spawns", (1, 1)), Some("goblin"));
+    /// assert_eq!(room.data("spawns", (2, 1)), None);
+    /// assert_eq!(room.data("loot", (1, 1)), None);
+    /// ```
+    pub fn data<P: Into<Point2>>(&self, layer: &str, point: P) -> Option<&str> {
+        self.data
+            .get(layer)?
+            .get(&point.into())
+            .map(|value| value.as_str())
+    }
+
+    /// Returns the tiles of the stamp.
+    pub fn tiles(&self) -> &[StampTile] {
+        &self.tiles
+    }
+
+    /// Returns the amount of tiles in the stamp.
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns `true` if the stamp has no tiles.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Returns `true` if the stamp was rotated or flipped, which keeps the
+    /// shape of its tiles only for square topologies.
+    pub fn is_transformed(&self) -> bool {
+        self.transformed
+    }
+
+    /// Returns the width and height of the stamp in tiles.
+    pub fn dimensions(&self) -> Dimension2 {
+        let max = self.tiles.iter().fold(Point2::new(-1, -1), |max, tile| {
+            Point2::new(max.x.max(tile.point.x), max.y.max(tile.point.y))
+        });
+        Dimension2::new((max.x + 1) as u32, (max.y + 1) as u32)
+    }
+
+    /// Returns the stamp rotated counterclockwise by a number of quarter
+    /// turns.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_render::prelude::*;
+    /// use bevy_tilemap::{chunk::RawTile, stamp::{StampTile, TileStamp}};
+    /// use bevy_tilemap_types::point::Point2;
+    ///
+    /// let tile = RawTile { index: 2, color: Color::WHITE, palette: 0 };
+    /// let stamp = TileStamp::new(vec![
+    ///     StampTile { point: Point2::new(0, 0), z_order: 0, tile },
+    ///     StampTile { point: Point2::new(2, 0), z_order: 0, tile },
+    /// ]);
+    ///
+    /// let rotated = stamp.rotated(1);
+    /// assert_eq!(rotated.tiles()[0].point, Point2::new(0, 0));
+    /// assert_eq!(rotated.tiles()[1].point, Point2::new(0, 2));
+    /// ```
+    pub fn rotated(self, quarter_turns: i32) -> TileStamp {
+        let quarter_turns = quarter_turns.rem_euclid(4);
+        if quarter_turns == 0 {
+            return self;
+        }
+        self.map_points(|point| match quarter_turns {
+            1 => Point2::new(-point.y, point.x),
+            2 => Point2::new(-point.x, -point.y),
+            3 => Point2::new(point.y, -point.x),
+            _ => point,
+        })
+    }
+
+    /// Returns the stamp mirrored from left to right.
+    pub fn flipped_x(self) -> TileStamp {
+        self.map_points(|point| Point2::new(-point.x, point.y))
+    }
+
+    /// Returns the stamp mirrored from top to bottom.
+    pub fn flipped_y(self) -> TileStamp {
+        self.map_points(|point| Point2::new(point.x, -point.y))
+    }
+
+    /// Moves every tile, data and anchor point of the stamp and normalizes it
+    /// again.
+    fn map_points<F: Fn(Point2) -> Point2>(mut self, map: F) -> TileStamp {
+        self.transformed = true;
+        for tile in self.tiles.iter_mut() {
+            tile.point = map(tile.point);
+        }
+        for values in self.data.values_mut() {
+            *values = values
+                .drain()
+                .map(|(point, value)| (map(point), value))
+                .collect();
+        }
+        for anchor in self.anchors.values_mut() {
+            *anchor = map(*anchor);
+        }
+        self.normalize();
+        self
+    }
+
+    /// Returns the tiles of the stamp with their bottom left corner at an
+    /// offset.
+    pub(crate) fn to_tiles(&self, offset: Point2) -> Vec<Tile<Point2>> {
+        self.tiles
+            .iter()
+            .map(|tile| Tile {
+                point: tile.point + offset,
+                z_order: tile.z_order,
+                sprite_index: tile.tile.index,
+                tint: tile.tile.color,
+                palette: tile.tile.palette,
+            })
+            .collect()
+    }
+
+    /// Moves the tiles so that the bottom left corner is at the origin.
+    fn normalize(&mut self) {
+        let min = self.tiles.iter().fold(None, |min: Option<Point2>, tile| {
+            Some(min.map_or(tile.point, |min| {
+                Point2::new(min.x.min(tile.point.x), min.y.min(tile.point.y))
+            }))
+        });
+        if let Some(min) = min {
+            for tile in self.tiles.iter_mut() {
+                tile.point -= min;
+            }
+            for values in self.data.values_mut() {
+                *values = values
+                    .drain()
+                    .map(|(point, value)| (point - min, value))
+                    .collect();
+            }
+            for anchor in self.anchors.values_mut() {
+                *anchor -= min;
+            }
+        }
+    }
+}
+
+/// Loads [`TileStamp`] assets from RON files with the `stamp` extension.
+///
+/// The data layers and anchors may be left out of the file.
+///
+/// ```ron
+/// (
+///     tiles: [
+///         (
+///             point: (x: 0, y: 0),
+///             z_order: 0,
+///             tile: (
+///                 index: 1,
+///                 color: (red: 1.0, green: 1.0, blue: 1.0, alpha: 1.0),
+///                 palette: 0,
+///             ),
+///         ),
+///     ],
+///     data: {
+///         "spawns": { (x: 0, y: 0): "goblin" },
+///     },
+///     anchors: {
+///         "door": (x: 0, y: 0),
+///     },
+/// )
+/// ```
+#[cfg(feature = "ron-stamps")]
+#[derive(Default)]
+pub struct TileStampLoader;
+
+#[cfg(feature = "ron-stamps")]
+impl AssetLoader for TileStampLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), AnyhowError>> {
+        Box::pin(async move {
+            let stamp: TileStamp = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(stamp));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["stamp"]
+    }
+}
+
+/// Returns the points of a line between two points, including both.
+pub(crate) fn line_points(from: Point2, to: Point2) -> Vec<Point2> {
+    let dx = (to.x - from.x).abs();
+    let dy = -(to.y - from.y).abs();
+    let step_x = if from.x < to.x { 1 } else { -1 };
+    let step_y = if from.y < to.y { 1 } else { -1 };
+    let mut error = dx + dy;
+    let mut point = from;
+    let mut points = Vec::with_capacity(dx.max(-dy) as usize + 1);
+    loop {
+        points.push(point);
+        if point == to {
+            return points;
+        }
+        let doubled = error * 2;
+        if doubled >= dy {
+            error += dy;
+            point.x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            point.y += step_y;
+        }
+    }
+}
+
+/// Returns the points of a rectangle between two opposite corners, either
+/// only its outline or filled.
+pub(crate) fn rect_points(corner: Point2, opposite: Point2, filled: bool) -> Vec<Point2> {
+    let min = Point2::new(corner.x.min(opposite.x), corner.y.min(opposite.y));
+    let max = Point2::new(corner.x.max(opposite.x), corner.y.max(opposite.y));
+    (min.y..=max.y)
+        .flat_map(|y| (min.x..=max.x).map(move |x| Point2::new(x, y)))
+        .filter(|point| {
+            filled || point.x == min.x || point.x == max.x || point.y == min.y || point.y == max.y
+        })
+        .collect()
+}
+
+/// Returns the points of a circle around a center, either only its outline or
+/// filled.
+pub(crate) fn circle_points(center: Point2, radius: u32, filled: bool) -> Vec<Point2> {
+    let reach = radius as i32;
+    // Half a tile is added so that the edges are not a single lone tile.
+    let limit = (radius as f32 + 0.5) * (radius as f32 + 0.5);
+    let is_inside = |x: i32, y: i32| (x * x + y * y) as f32 <= limit;
+    let mut points = Vec::new();
+    for y in -reach..=reach {
+        for x in -reach..=reach {
+            if !is_inside(x, y) {
+                continue;
+            }
+            let is_edge = !is_inside(x + 1, y)
+                || !is_inside(x - 1, y)
+                || !is_inside(x, y + 1)
+                || !is_inside(x, y - 1);
+            if filled || is_edge {
+                points.push(Point2::new(center.x + x, center.y + y));
+            }
+        }
+    }
+    points
+}
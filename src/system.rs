@@ -1,56 +1,124 @@
 //! The tilemap systems.
 
 #[cfg(feature = "bevy_rapier2d")]
-use crate::{chunk::Chunk, TilemapLayer};
+use crate::chunk::{render::GridTopology, Chunk};
 use crate::{
     chunk::{
-        entity::{ChunkBundle, ModifiedLayer, ZOrder},
+        entity::{ChunkBundle, ChunkLayerUniform, ChunkMirrorBundle, ModifiedLayer, ZOrder},
         mesh::ChunkMesh,
-        render::GridTopology,
+        render::{layer_blend_pipeline, LayerBlend, LayerDepth},
     },
+    event::{ChunkRequest, TileChanged, TilemapChunkEvent},
     lib::*,
-    Tilemap,
+    Tilemap, TilemapLayer,
 };
 
+/// Chunk pipelines with the blend and depth modes of a layer applied, by the
+/// pipeline they were derived from.
+pub(crate) type LayerPipelines =
+    HashMap<(HandleId, LayerBlend, LayerDepth), Handle<PipelineDescriptor>>;
+
+/// Returns the pipeline to draw a layer with, deriving one from the base
+/// pipeline if the layer does not use the default blend and depth modes.
+fn layer_pipeline(
+    base: Handle<PipelineDescriptor>,
+    layer: &TilemapLayer,
+    pipelines: &mut Assets<PipelineDescriptor>,
+    layer_pipelines: &mut LayerPipelines,
+) -> Handle<PipelineDescriptor> {
+    if layer.blend == LayerBlend::default() && layer.depth == LayerDepth::default() {
+        return base;
+    }
+    let key = (base.id, layer.blend, layer.depth);
+    if let Some(handle) = layer_pipelines.get(&key) {
+        return handle.clone_weak();
+    }
+    let pipeline = if let Some(pipeline) = pipelines.get(&base) {
+        layer_blend_pipeline(pipeline, layer.blend, layer.depth)
+    } else {
+        warn!("Chunk pipeline is missing, using it without blend and depth modes");
+        return base;
+    };
+    let handle = pipelines.add(pipeline);
+    let weak = handle.clone_weak();
+    layer_pipelines.insert(key, handle);
+    weak
+}
+
 /// The event handling system for the tilemap.
 ///
 /// There are a few things that happen in this function which are outlined in
 /// order of operation here. It was done in this order that made the most sense
 /// at the time of creation.
 ///
-/// 1. Spawn chunks
+/// 1. Spawn chunks, within the tilemap's spawn and mesh budgets
+/// 1. Spawn mirrored chunks of wrapping tilemaps
 /// 1. Modify chunks
-/// 1. Despawn chunks
+/// 1. Despawn chunks and mirrored chunks
+///
+/// Each of these is sent as a [`TilemapChunkEvent`] along with the chunks that
+/// were loaded and unloaded.
 pub(crate) fn tilemap_events(
     commands: &mut Commands,
+    mut chunk_events: ResMut<Events<TilemapChunkEvent>>,
     mut meshes: ResMut<Assets<Mesh>>,
+    mut pipelines: ResMut<Assets<PipelineDescriptor>>,
+    mut layer_pipelines: Local<LayerPipelines>,
     mut tilemap_query: Query<(Entity, &mut Tilemap)>,
     mut layer_query: Query<&mut ModifiedLayer>,
+    chunk_layer_query: Query<(&Handle<Mesh>, &RenderPipelines, &Transform)>,
 ) {
     for (map_entity, mut tilemap) in tilemap_query.iter_mut() {
-        tilemap.chunk_events_update();
         let mut modified_chunks = Vec::new();
-        let mut spawned_chunks = Vec::new();
+        let mut queued_chunks = Vec::new();
         let mut despawned_chunks = Vec::new();
-        let mut reader = tilemap.chunk_events().get_reader();
-        for event in reader.iter(&tilemap.chunk_events()) {
-            use crate::TilemapChunkEvent::*;
-            match event {
-                Modified { ref layers } => {
-                    modified_chunks.push(layers.clone());
+        for request in tilemap.take_chunk_requests().into_iter() {
+            match request {
+                ChunkRequest::Load { point } => chunk_events.send(TilemapChunkEvent::Loaded {
+                    tilemap: map_entity,
+                    point,
+                }),
+                ChunkRequest::Unload { point } => chunk_events.send(TilemapChunkEvent::Unloaded {
+                    tilemap: map_entity,
+                    point,
+                }),
+                ChunkRequest::Modify { point, layers } => {
+                    // The layers of chunks that are spawned later are built
+                    // with the modified tiles already.
+                    if let Some(chunk) = tilemap.get_chunk(&point) {
+                        modified_chunks.extend(
+                            layers
+                                .iter()
+                                .filter_map(|z_order| chunk.get_entity(*z_order)),
+                        );
+                    }
+                    chunk_events.send(TilemapChunkEvent::Modified {
+                        tilemap: map_entity,
+                        point,
+                        layers,
+                    });
                 }
-                Spawned { ref point } => {
-                    spawned_chunks.push(*point);
+                ChunkRequest::Spawn { point } => {
+                    queued_chunks.push((point, true));
                 }
-                Despawned {
-                    ref entities,
-                    ref point,
-                } => {
-                    despawned_chunks.push((entities.clone(), *point));
+                ChunkRequest::Despawn { entities, point } => {
+                    queued_chunks.push((point, false));
+                    despawned_chunks.push((entities, point));
                 }
             }
         }
 
+        // Chunks are queued in the order of the events so that a despawn after
+        // a spawn cancels it if the chunk had not been spawned yet.
+        for (point, spawn) in queued_chunks.into_iter() {
+            if spawn {
+                tilemap.queue_chunk_spawn(point);
+            } else {
+                tilemap.dequeue_chunk_spawn(point);
+            }
+        }
+        let spawned_chunks = tilemap.next_spawn_batch();
+
         let capacity = spawned_chunks.len();
         for point in spawned_chunks.into_iter() {
             if tilemap.spawned_chunks().contains(&(point.x, point.y)) {
@@ -62,10 +130,14 @@ pub(crate) fn tilemap_events(
             let layers = tilemap.layers();
             let layers_len = tilemap.layers().len();
             let chunk_dimensions = tilemap.chunk_dimensions();
-            let tile_dimensions = tilemap.tile_dimensions();
+            let layer_translations: Vec<Vec2> = (0..layers_len)
+                .map(|z_order| tilemap.layer_chunk_translation(point, z_order))
+                .collect();
+            let layer_colors: Vec<Color> = (0..layers_len)
+                .map(|z_order| tilemap.layer_color(z_order))
+                .collect();
             let texture_atlas = tilemap.texture_atlas().clone_weak();
             let pipeline_handle = tilemap.topology().to_pipeline_handle();
-            let topology = tilemap.topology();
             let chunk = if let Some(chunk) = tilemap.chunks_mut().get_mut(&point) {
                 chunk
             } else {
@@ -74,11 +146,13 @@ pub(crate) fn tilemap_events(
             };
             let mut entities = Vec::with_capacity(capacity);
             for z_order in 0..layers_len {
-                if layers.get(z_order).is_none() {
+                let layer = if let Some(Some(layer)) = layers.get(z_order) {
+                    layer
+                } else {
                     continue;
-                }
+                };
                 let mut mesh = Mesh::from(&ChunkMesh::new(chunk_dimensions));
-                let (indexes, colors) =
+                let (indexes, colors, palettes) =
                     if let Some(parts) = chunk.tiles_to_renderer_parts(z_order, chunk_dimensions) {
                         parts
                     } else {
@@ -87,48 +161,30 @@ pub(crate) fn tilemap_events(
                     };
                 mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_INDEX, indexes);
                 mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_COLOR, colors);
+                mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_PALETTE, palettes);
+                mesh.set_attribute(
+                    ChunkMesh::ATTRIBUTE_TILE_LIGHT,
+                    chunk.lights_to_attributes(chunk_dimensions),
+                );
                 let mesh_handle = meshes.add(mesh);
                 chunk.set_mesh(z_order, mesh_handle.clone());
 
-                use GridTopology::*;
-                let translation_x = match topology {
-                    HexX | HexEvenCols | HexOddCols => {
-                        (((chunk.point().x * tile_dimensions.width as i32) as f32 * 0.75) as i32
-                            * chunk_dimensions.width as i32) as f32
-                    }
-                    HexY => {
-                        (chunk.point().x
-                            * tile_dimensions.width as i32
-                            * chunk_dimensions.width as i32) as f32
-                            + (chunk.point().y as f32 * chunk_dimensions.height as f32 * 0.5)
-                                * tile_dimensions.width as f32
-                    }
-                    Square | HexEvenRows | HexOddRows => {
-                        (chunk.point().x
-                            * tile_dimensions.width as i32
-                            * chunk_dimensions.width as i32) as f32
-                    }
-                };
-                let translation_y = match topology {
-                    HexX => {
-                        (chunk.point().y
-                            * tile_dimensions.height as i32
-                            * chunk_dimensions.height as i32) as f32
-                            + (chunk.point().x as f32 * chunk_dimensions.width as f32 * 0.5)
-                                * tile_dimensions.height as f32
-                    }
-                    HexY | HexEvenRows | HexOddRows => {
-                        (((chunk.point().y * tile_dimensions.height as i32) as f32 * 0.75) as i32
-                            * chunk_dimensions.height as i32) as f32
-                    }
-                    Square | HexEvenCols | HexOddCols => {
-                        (chunk.point().y
-                            * tile_dimensions.height as i32
-                            * chunk_dimensions.height as i32) as f32
-                    }
+                let translation = layer_translations
+                    .get(z_order)
+                    .copied()
+                    .unwrap_or_else(Vec2::zero)
+                    .extend(z_order as f32 + layer.z_offset);
+                let base = if let Some(handle) = layer.pipeline.as_ref() {
+                    handle.clone()
+                } else {
+                    pipeline_handle.clone_weak().typed()
                 };
-                let translation = Vec3::new(translation_x, translation_y, z_order as f32);
-                let pipeline = RenderPipeline::new(pipeline_handle.clone_weak().typed());
+                let pipeline = RenderPipeline::new(layer_pipeline(
+                    base,
+                    layer,
+                    &mut pipelines,
+                    &mut layer_pipelines,
+                ));
                 let entity = if let Some(entity) = commands
                     .spawn(ChunkBundle {
                         point,
@@ -147,6 +203,9 @@ pub(crate) fn tilemap_events(
                         main_pass: MainPass,
                         global_transform: Default::default(),
                         modified_layer: Default::default(),
+                        layer_uniform: ChunkLayerUniform {
+                            color: layer_colors.get(z_order).copied().unwrap_or(Color::WHITE),
+                        },
                     })
                     .current_entity()
                 {
@@ -164,26 +223,118 @@ pub(crate) fn tilemap_events(
                 entities.push(entity);
             }
             commands.push_children(map_entity, &entities);
+            chunk_events.send(TilemapChunkEvent::Spawned {
+                tilemap: map_entity,
+                point,
+            });
         }
 
-        for layers in modified_chunks.into_iter() {
-            for (_layer, entity) in layers.into_iter() {
-                let mut modified_layer = if let Ok(layer) = layer_query.get_mut(entity) {
+        // Mirrored chunks are spawned once the layers of the chunk they show
+        // exist, which may be a frame after the chunk was spawned.
+        for point in tilemap.mirrors_to_spawn().into_iter() {
+            let wrapped = tilemap.wrap_chunk_point_of_tilemap(point);
+            if !tilemap.spawned_chunks().contains(&(wrapped.x, wrapped.y)) {
+                continue;
+            }
+            let chunk = if let Some(chunk) = tilemap.get_chunk(&wrapped) {
+                chunk
+            } else {
+                continue;
+            };
+            let mut layers = Vec::new();
+            for z_order in 0..tilemap.layers().len() {
+                if let Some(entity) = chunk.get_entity(z_order) {
+                    layers.push((z_order, chunk_layer_query.get(entity)));
+                }
+            }
+            if layers.is_empty() || layers.iter().any(|(_, layer)| layer.is_err()) {
+                continue;
+            }
+            let mut entities = Vec::with_capacity(layers.len());
+            for (z_order, layer) in layers.into_iter() {
+                let (mesh, render_pipelines, transform) = if let Ok(layer) = layer {
                     layer
                 } else {
-                    warn!("Chunk layer does not exist, skipping");
                     continue;
                 };
-                modified_layer.0 += 1;
+                let translation = tilemap
+                    .layer_chunk_translation(point, z_order)
+                    .extend(transform.translation.z);
+                let entity = commands
+                    .spawn(ChunkMirrorBundle {
+                        point,
+                        z_order: ZOrder(z_order),
+                        texture_atlas: tilemap.texture_atlas().clone_weak(),
+                        mesh: mesh.clone_weak(),
+                        transform: Transform::from_translation(translation),
+                        render_pipelines: render_pipelines.clone(),
+                        draw: Default::default(),
+                        visible: Visible {
+                            is_visible: true,
+                            is_transparent: true,
+                        },
+                        main_pass: MainPass,
+                        global_transform: Default::default(),
+                        layer_uniform: ChunkLayerUniform {
+                            color: tilemap.layer_color(z_order),
+                        },
+                    })
+                    .current_entity();
+                if let Some(entity) = entity {
+                    entities.push(entity);
+                }
             }
+            commands.push_children(map_entity, &entities);
+            tilemap.insert_mirror(point, entities);
+        }
+
+        for entity in modified_chunks.into_iter() {
+            let mut modified_layer = if let Ok(layer) = layer_query.get_mut(entity) {
+                layer
+            } else {
+                warn!("Chunk layer does not exist, skipping");
+                continue;
+            };
+            modified_layer.0 += 1;
         }
 
         for (entities, point) in despawned_chunks.into_iter() {
+            // Chunks that were still waiting to be spawned have no entities.
+            if entities.is_empty() {
+                continue;
+            }
             for entity in entities.into_iter() {
                 commands.despawn_recursive(entity);
             }
+            chunk_events.send(TilemapChunkEvent::Despawned {
+                tilemap: map_entity,
+                point,
+            });
             info!("Chunk {} despawned", point);
         }
+
+        for entity in tilemap.take_stale_mirrors().into_iter() {
+            commands.despawn_recursive(entity);
+        }
+    }
+}
+
+/// Sends the tiles that changed in each tilemap since the last frame as
+/// [`TileChanged`] events.
+pub(crate) fn tile_change_events(
+    mut events: ResMut<Events<TileChanged>>,
+    mut tilemap_query: Query<(Entity, &mut Tilemap)>,
+) {
+    for (tilemap_entity, mut tilemap) in tilemap_query.iter_mut() {
+        for (point, z_order, old, new) in tilemap.take_tile_changes().into_iter() {
+            events.send(TileChanged {
+                tilemap: tilemap_entity,
+                point,
+                z_order,
+                old,
+                new,
+            });
+        }
     }
 }
 
@@ -258,7 +409,7 @@ fn spawn_collisions(
 
             let collision_groups = layers
                 .get(z_order)
-                .and_then(|layer_opt| layer_opt.and_then(|layer| Some(layer.interaction_groups)));
+                .and_then(|layer_opt| layer_opt.as_ref().map(|layer| layer.interaction_groups));
             if let Some(collision_groups) = collision_groups {
                 if collision_groups.with_mask(0).0 != 0 {
                     let mut collider = ColliderBuilder::cuboid(
@@ -300,25 +451,23 @@ fn spawn_collisions(
 #[cfg(feature = "bevy_rapier2d")]
 pub(crate) fn tilemap_collision_events(
     commands: &mut Commands,
-    mut tilemap_query: Query<(&mut Tilemap, &Transform)>,
+    mut reader: Local<EventReader<TilemapChunkEvent>>,
+    chunk_events: Res<Events<TilemapChunkEvent>>,
+    mut tilemap_query: Query<(Entity, &mut Tilemap, &Transform)>,
 ) {
-    for (mut tilemap, transform) in tilemap_query.iter_mut() {
+    let mut spawned: HashMap<Entity, Vec<Point2>> = HashMap::default();
+    for event in reader.iter(&chunk_events) {
+        if let TilemapChunkEvent::Spawned { tilemap, point } = event {
+            spawned.entry(*tilemap).or_default().push(*point);
+        }
+    }
+
+    for (tilemap_entity, mut tilemap, transform) in tilemap_query.iter_mut() {
         if tilemap.topology() != GridTopology::Square {
             error!("collision physics are not supported on hex tiles yet");
             continue;
         }
-        tilemap.collision_events_update();
-        let mut spawned_chunks = Vec::new();
-        let mut reader = tilemap.chunk_events().get_reader();
-        for event in reader.iter(&tilemap.chunk_events()) {
-            use crate::TilemapChunkEvent::*;
-            match event {
-                Spawned { ref point } => {
-                    spawned_chunks.push(*point);
-                }
-                _ => continue,
-            };
-        }
+        let spawned_chunks = spawned.remove(&tilemap_entity).unwrap_or_default();
 
         for point in spawned_chunks.into_iter() {
             let layers = tilemap.layers();
@@ -351,21 +500,14 @@ pub(crate) fn tilemap_collision_events(
 
         let mut spawned_collisions = Vec::new();
         let mut despawned_collisions = Vec::new();
-        let mut reader = tilemap.collision_events().get_reader();
-        for event in reader.iter(&tilemap.collision_events()) {
+        for event in tilemap.take_collision_events().into_iter() {
             use crate::event::TilemapCollisionEvent::*;
             match event {
-                Spawned {
-                    ref chunk_point,
-                    ref tiles,
-                } => {
-                    spawned_collisions.push((*chunk_point, tiles.clone()));
+                Spawned { chunk_point, tiles } => {
+                    spawned_collisions.push((chunk_point, tiles));
                 }
-                Despawned {
-                    ref chunk_point,
-                    ref tiles,
-                } => {
-                    despawned_collisions.push((*chunk_point, tiles.clone()));
+                Despawned { chunk_point, tiles } => {
+                    despawned_collisions.push((chunk_point, tiles));
                 }
             };
         }
@@ -14,6 +14,12 @@ pub struct Tile<P: Into<Point2>> {
     pub sprite_index: usize,
     /// The desired tint and alpha of the tile. White means no change.
     pub tint: Color,
+    /// The row of the palette to color the tile with, for layers drawn with a
+    /// [`TilemapPalette`].
+    ///
+    /// [`TilemapPalette`]: crate::palette::TilemapPalette
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub palette: u8,
 }
 
 impl<P: Into<Point2> + Default> Default for Tile<P> {
@@ -23,6 +29,7 @@ impl<P: Into<Point2> + Default> Default for Tile<P> {
             z_order: 0,
             sprite_index: 0,
             tint: Color::WHITE,
+            palette: 0,
         }
     }
 }
@@ -96,10 +96,19 @@
 #[cfg(feature = "bevy_rapier2d")]
 use crate::event::TilemapCollisionEvent;
 use crate::{
-    chunk::{Chunk, LayerKind, RawTile},
-    event::TilemapChunkEvent,
+    camera::WorldRect,
+    chunk::{
+        render::{LayerBlend, LayerDepth},
+        Chunk, LayerKind, RawTile,
+    },
+    event::{ChunkRequest, TileChanges},
+    history::{TileEdit, TilemapHistory, Transaction},
     lib::*,
+    light::{light_reach, TileLight, TilemapLighting},
+    position::TileEntityIndex,
     prelude::GridTopology,
+    region::{Region, TilemapRegions},
+    stamp::{circle_points, line_points, rect_points, StampTile, TileStamp},
     tile::Tile,
 };
 
@@ -120,6 +129,18 @@ pub enum ErrorKind {
     MissingChunk,
     /// The chunk already exists.
     ChunkAlreadyExists(Point2),
+    /// The texture is not RGBA with 8 bits per channel.
+    UnsupportedTextureFormat,
+    /// An image could not be encoded or written.
+    ImageError(String),
+    /// An image would have too many pixels to be drawn.
+    ImageTooLarge,
+    /// A rotated or flipped stamp was pasted into a tilemap whose topology is
+    /// not square.
+    UnsupportedStampTransform,
+    /// Generation ran into a contradiction on every one of an amount of
+    /// attempts.
+    GenerationFailed(usize),
 }
 
 impl Display for ErrorKind {
@@ -146,6 +167,24 @@ impl Display for ErrorKind {
                 "the chunk {} already exists, if this was intentional run `remove_chunk` first",
                 p
             ),
+            UnsupportedTextureFormat => write!(
+                f,
+                "the texture format is not supported, it must be RGBA with 8 bits per channel"
+            ),
+            ImageError(err) => write!(f, "the image could not be written: {}", err),
+            ImageTooLarge => write!(
+                f,
+                "the image is too large to draw, try a smaller region or scale"
+            ),
+            UnsupportedStampTransform => write!(
+                f,
+                "rotated or flipped stamps can only be pasted into square tilemaps"
+            ),
+            GenerationFailed(n) => write!(
+                f,
+                "generation failed after {} attempts, the constraints may contradict each other",
+                n
+            ),
         }
     }
 }
@@ -199,6 +238,10 @@ const DEFAULT_TEXTURE_DIMENSIONS: Dimension2 = Dimension2::new(32, 32);
 const DEFAULT_CHUNK_DIMENSIONS: Dimension2 = Dimension2::new(32, 32);
 /// The default z layers.
 const DEFAULT_Z_LAYERS: usize = 5;
+/// The default extra chunks that are kept spawned after leaving a view.
+const DEFAULT_AUTO_SPAWN_HYSTERESIS: u32 = 1;
+/// The default amount that level of detail textures are downsampled by.
+const DEFAULT_LOD_DOWNSAMPLE: u32 = 4;
 
 impl Default for AutoFlags {
     fn default() -> Self {
@@ -208,10 +251,58 @@ impl Default for AutoFlags {
 
 /// A layer configuration for a tilemap.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct TilemapLayer {
     /// The kind of layer to create.
     pub kind: LayerKind,
+    /// A custom render pipeline to draw the layer with, if any.
+    ///
+    /// Use [`GridTopology::layer_pipeline`] to build one with a custom
+    /// fragment shader.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub pipeline: Option<Handle<PipelineDescriptor>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    /// How the tiles are blended with what is drawn below the layer.
+    pub blend: LayerBlend,
+    #[cfg_attr(feature = "serde", serde(default))]
+    /// How the tiles are tested against and written to the depth buffer.
+    pub depth: LayerDepth,
+    #[cfg_attr(feature = "serde", serde(default))]
+    /// An offset added to the z order of the layer, relative to the tilemap
+    /// transform.
+    ///
+    /// This can be used to interleave the layer with sprites. For example a
+    /// layer at z order 1 with an offset of `0.5` is drawn above sprites at a
+    /// z of `1.25` relative to the tilemap.
+    pub z_offset: f32,
+    #[cfg_attr(feature = "serde", serde(default = "default_layer_parallax"))]
+    /// How much the layer moves along with the tilemap relative to the
+    /// camera.
+    ///
+    /// At `1.0`, the default, the layer moves with the tilemap. Lower values
+    /// make the layer move slower, such as for distant backgrounds, and at
+    /// `0.0` it stays fixed to the camera.
+    pub parallax: Vec2,
+    #[cfg_attr(feature = "serde", serde(default))]
+    /// The speed in pixels per second that the layer scrolls at.
+    pub scroll: Vec2,
+    #[cfg_attr(feature = "serde", serde(default))]
+    /// If the layer repeats endlessly as it is moved by its parallax or
+    /// scroll.
+    ///
+    /// This requires the tilemap to have dimensions, which are used as the
+    /// period of the layer. The tilemap should be larger than the view of the
+    /// camera by at least a chunk on each axis to not show any seams.
+    pub wrap: bool,
+    #[cfg_attr(feature = "serde", serde(default = "default_layer_visible"))]
+    /// If the layer is drawn.
+    pub visible: bool,
+    #[cfg_attr(feature = "serde", serde(default = "default_layer_opacity"))]
+    /// The opacity of the whole layer, multiplied into the alpha of its tiles.
+    pub opacity: f32,
+    #[cfg_attr(feature = "serde", serde(default = "default_layer_tint"))]
+    /// A tint multiplied into the color of every tile of the layer.
+    pub tint: Color,
     /// The interaction group and its mask.
     #[cfg_attr(feature = "serde", serde(skip))]
     #[cfg(feature = "bevy_rapier2d")]
@@ -222,12 +313,60 @@ impl Default for TilemapLayer {
     fn default() -> TilemapLayer {
         TilemapLayer {
             kind: LayerKind::Dense,
+            pipeline: None,
+            blend: LayerBlend::default(),
+            depth: LayerDepth::default(),
+            z_offset: 0.0,
+            parallax: Vec2::one(),
+            scroll: Vec2::zero(),
+            wrap: false,
+            visible: true,
+            opacity: 1.0,
+            tint: Color::WHITE,
             #[cfg(feature = "bevy_rapier2d")]
             interaction_groups: InteractionGroups::none(),
         }
     }
 }
 
+/// The parallax of layers which do not have one set when deserialized.
+#[cfg(feature = "serde")]
+fn default_layer_parallax() -> Vec2 {
+    Vec2::one()
+}
+
+/// The visibility of layers which do not have one set when deserialized.
+#[cfg(feature = "serde")]
+fn default_layer_visible() -> bool {
+    true
+}
+
+/// The opacity of layers which do not have one set when deserialized.
+#[cfg(feature = "serde")]
+fn default_layer_opacity() -> f32 {
+    1.0
+}
+
+/// The tint of layers which do not have one set when deserialized.
+#[cfg(feature = "serde")]
+fn default_layer_tint() -> Color {
+    Color::WHITE
+}
+
+/// The auto spawn hysteresis of tilemaps which do not have one set when
+/// deserialized.
+#[cfg(feature = "serde")]
+fn default_auto_spawn_hysteresis() -> u32 {
+    DEFAULT_AUTO_SPAWN_HYSTERESIS
+}
+
+/// The level of detail downsampling of tilemaps which do not have one set when
+/// deserialized.
+#[cfg(feature = "serde")]
+fn default_lod_downsample() -> u32 {
+    DEFAULT_LOD_DOWNSAMPLE
+}
+
 /// A Tilemap which maintains chunks and its tiles within.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug)]
@@ -236,6 +375,12 @@ pub struct Tilemap {
     topology: GridTopology,
     /// An optional field which can contain the tilemaps dimensions in chunks.
     dimensions: Option<Dimension2>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    /// If the tilemap wraps around horizontally, if it has dimensions.
+    wrap_x: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    /// If the tilemap wraps around vertically, if it has dimensions.
+    wrap_y: bool,
     /// A chunks dimensions in tiles.
     chunk_dimensions: Dimension2,
     /// A tiles dimensions in pixels.
@@ -247,6 +392,28 @@ pub struct Tilemap {
     auto_flags: AutoFlags,
     /// Dimensions of chunks to spawn from camera transform.
     auto_spawn: Option<Dimension2>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    /// Extra chunks to spawn around the view of each camera.
+    auto_spawn_margin: u32,
+    #[cfg_attr(feature = "serde", serde(default = "default_auto_spawn_hysteresis"))]
+    /// Extra chunks past the margin that stay spawned before despawning.
+    auto_spawn_hysteresis: u32,
+    /// The maximum amount of chunks to spawn per frame.
+    spawn_budget: Option<usize>,
+    /// The maximum amount of chunk layer meshes to build per frame.
+    mesh_budget: Option<usize>,
+    /// The camera zoom at which chunks are drawn as a single texture.
+    lod_threshold: Option<f32>,
+    #[cfg_attr(feature = "serde", serde(default = "default_lod_downsample"))]
+    /// The amount that the level of detail textures are downsampled by.
+    lod_downsample: u32,
+    /// The maximum amount of level of detail textures to bake per frame.
+    lod_bake_budget: Option<usize>,
+    /// The settings of the lighting, if the tilemap is lit.
+    lighting: Option<TilemapLighting>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    /// The undo and redo history of tile edits, if it is recorded.
+    history: Option<TilemapHistory>,
     /// Rapier physics scale for colliders and rigid bodies created
     /// for layers with colliders.
     #[cfg(feature = "bevy_rapier2d")]
@@ -262,14 +429,54 @@ pub struct Tilemap {
     /// A map of all currently spawned entities.
     entities: HashMap<usize, Vec<Entity>>,
     #[cfg_attr(feature = "serde", serde(skip))]
-    /// The events of the tilemap.
-    chunk_events: Events<TilemapChunkEvent>,
+    /// The changes to chunks that the tilemap system still needs to handle.
+    chunk_requests: Vec<ChunkRequest>,
     #[cfg(feature = "bevy_rapier2d")]
     #[cfg_attr(feature = "serde", serde(skip))]
-    /// The collision events of the tilemap.
-    collision_events: Events<TilemapCollisionEvent>,
+    /// The collisions that the collision system still needs to handle.
+    collision_events: Vec<TilemapCollisionEvent>,
     /// A set of all spawned chunks.
     spawned: HashSet<(i32, i32)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    /// Chunks which are waiting to be spawned within the budgets.
+    spawn_queue: Vec<Point2>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    /// The chunks at the center of each camera view, spawned around first.
+    spawn_focus: Vec<Point2>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    /// If the chunks are currently drawn as level of detail textures.
+    lod_active: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    /// The lights at tile points.
+    lights: HashMap<Point2, TileLight>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    /// If the lights need to be spread again over every chunk.
+    light_dirty: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    /// The chunks whose tiles or lights changed, whose light and the light of
+    /// the chunks around them needs to be spread again.
+    light_dirty_chunks: HashSet<Point2>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    /// The named regions, indexed by their tile points.
+    regions: TilemapRegions,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    /// The child entities with a tile position, indexed by their tile points.
+    tile_entities: TileEntityIndex,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    /// The tiles that changed since the last frame.
+    tile_changes: TileChanges,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    /// The center of the camera view that parallax layers are relative to.
+    parallax_focus: Vec2,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    /// How far each scrolling layer has scrolled by its z order.
+    layer_scrolls: HashMap<usize, Vec2>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    /// Chunk points outside of the dimensions which show a wrapped chunk.
+    mirror_chunks: HashSet<Point2>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    /// The layer entities of the spawned mirrored chunks.
+    mirror_entities: HashMap<Point2, Vec<Entity>>,
 }
 
 /// Tilemap factory, which can be used to construct and configure new tilemaps.
@@ -334,6 +541,10 @@ pub struct TilemapBuilder {
     topology: GridTopology,
     /// An optional field which can contain the tilemaps dimensions in chunks.
     dimensions: Option<Dimension2>,
+    /// If the tilemap wraps around horizontally.
+    wrap_x: bool,
+    /// If the tilemap wraps around vertically.
+    wrap_y: bool,
     /// The chunks dimensions in tiles.
     chunk_dimensions: Dimension2,
     /// The tiles dimensions in pixels.
@@ -348,6 +559,24 @@ pub struct TilemapBuilder {
     auto_flags: AutoFlags,
     /// The radius of chunks to spawn from a camera's transform.
     auto_spawn: Option<Dimension2>,
+    /// Extra chunks to spawn around the view of each camera.
+    auto_spawn_margin: u32,
+    /// Extra chunks past the margin that stay spawned before despawning.
+    auto_spawn_hysteresis: u32,
+    /// The maximum amount of chunks to spawn per frame.
+    spawn_budget: Option<usize>,
+    /// The maximum amount of chunk layer meshes to build per frame.
+    mesh_budget: Option<usize>,
+    /// The camera zoom at which chunks are drawn as a single texture.
+    lod_threshold: Option<f32>,
+    /// The amount that the level of detail textures are downsampled by.
+    lod_downsample: u32,
+    /// The maximum amount of level of detail textures to bake per frame.
+    lod_bake_budget: Option<usize>,
+    /// The settings of the lighting, if the tilemap is lit.
+    lighting: Option<TilemapLighting>,
+    /// The undo and redo history of tile edits, if it is recorded.
+    history: Option<TilemapHistory>,
     /// Rapier physics scale for colliders and rigid bodies created
     /// for layers with colliders.
     #[cfg(feature = "bevy_rapier2d")]
@@ -359,6 +588,8 @@ impl Default for TilemapBuilder {
         TilemapBuilder {
             topology: GridTopology::Square,
             dimensions: None,
+            wrap_x: false,
+            wrap_y: false,
             chunk_dimensions: DEFAULT_CHUNK_DIMENSIONS,
             tile_dimensions: None,
             z_layers: DEFAULT_Z_LAYERS,
@@ -366,6 +597,15 @@ impl Default for TilemapBuilder {
             texture_atlas: None,
             auto_flags: AutoFlags::NONE,
             auto_spawn: None,
+            auto_spawn_margin: 0,
+            auto_spawn_hysteresis: DEFAULT_AUTO_SPAWN_HYSTERESIS,
+            spawn_budget: None,
+            mesh_budget: None,
+            lod_threshold: None,
+            lod_downsample: DEFAULT_LOD_DOWNSAMPLE,
+            lod_bake_budget: None,
+            lighting: None,
+            history: None,
             #[cfg(feature = "bevy_rapier2d")]
             physics_scale: 1.0,
         }
@@ -552,6 +792,10 @@ impl TilemapBuilder {
     /// fit the screen. It is possible that it may not be able to catch all
     /// dimensions but typical uses should be completely fine.
     ///
+    /// The width and height are the minimum radius in chunks to spawn around
+    /// each camera. The radius is grown to fit each camera's view. With more
+    /// than one camera, the chunks of all views are kept spawned together.
+    ///
     /// # Examples
     /// ```
     /// use bevy_tilemap::prelude::*;
@@ -563,6 +807,181 @@ impl TilemapBuilder {
         self
     }
 
+    /// Sets the extra chunks to spawn around the view of each camera when
+    /// automatically spawning chunks.
+    ///
+    /// A margin makes sure chunks are already spawned before they scroll or
+    /// zoom into view. By default there is no margin.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let builder = TilemapBuilder::new().auto_spawn(2, 2).auto_spawn_margin(1);
+    /// ```
+    pub fn auto_spawn_margin(mut self, margin: u32) -> Self {
+        self.auto_spawn_margin = margin;
+        self
+    }
+
+    /// Sets the extra chunks past the margin which stay spawned before they
+    /// are automatically despawned.
+    ///
+    /// This stops chunks from being spawned and despawned over and over when
+    /// a camera moves back and forth over a chunk border. By default this is
+    /// 1 chunk.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let builder = TilemapBuilder::new().auto_spawn(2, 2).auto_spawn_hysteresis(2);
+    /// ```
+    pub fn auto_spawn_hysteresis(mut self, hysteresis: u32) -> Self {
+        self.auto_spawn_hysteresis = hysteresis;
+        self
+    }
+
+    /// Sets the maximum amount of chunks that are spawned per frame.
+    ///
+    /// Chunks past the budget wait for the following frames. The chunks
+    /// closest to the center of a camera's view are spawned first and chunks
+    /// that leave the view before they are spawned are not spawned at all. By
+    /// default there is no budget.
+    ///
+    /// Only spawning is budgeted, chunks are always despawned in the frame
+    /// they leave the view as that is cheap.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let builder = TilemapBuilder::new().auto_spawn(2, 2).spawn_budget(4);
+    /// ```
+    pub fn spawn_budget(mut self, chunks: usize) -> Self {
+        self.spawn_budget = Some(chunks);
+        self
+    }
+
+    /// Sets the maximum amount of chunk layer meshes that are built per frame
+    /// when spawning chunks.
+    ///
+    /// Each layer of a chunk has its own mesh, so a tilemap with many layers
+    /// builds many meshes for each chunk. At least one chunk is always spawned
+    /// per frame. By default there is no budget.
+    ///
+    /// The meshes of spawned chunks that are rebuilt after their tiles change
+    /// are not budgeted, they are always rebuilt in the same frame so that
+    /// edits show up right away.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let builder = TilemapBuilder::new().auto_spawn(2, 2).mesh_budget(8);
+    /// ```
+    pub fn mesh_budget(mut self, meshes: usize) -> Self {
+        self.mesh_budget = Some(meshes);
+        self
+    }
+
+    /// Draws each chunk as a single downsampled texture once the cameras are
+    /// zoomed out past a threshold.
+    ///
+    /// The threshold is the scale of the camera relative to the scale of the
+    /// tilemap, so a threshold of `4.0` switches once four times as much of
+    /// the tilemap is seen. The textures are baked from every layer of a chunk
+    /// and are a `downsample` times smaller than the tiles in each direction.
+    /// If any camera is zoomed in closer than the threshold, the tiles are
+    /// drawn as usual. Palettes are not applied to the baked textures.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let builder = TilemapBuilder::new().auto_spawn(2, 2).lod(4.0, 8);
+    /// ```
+    pub fn lod(mut self, threshold: f32, downsample: u32) -> Self {
+        self.lod_threshold = Some(threshold);
+        self.lod_downsample = downsample.max(1);
+        self
+    }
+
+    /// Sets the maximum amount of level of detail textures that are baked per
+    /// frame.
+    ///
+    /// Baking reads every tile of a chunk on the CPU, so it is budgeted apart
+    /// from spawning. Chunks past the budget are drawn with their tiles until
+    /// their texture is baked in a following frame. At least one texture is
+    /// always baked per frame. By default there is no budget.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let builder = TilemapBuilder::new().lod(4.0, 8).lod_bake_budget(2);
+    /// ```
+    pub fn lod_bake_budget(mut self, chunks: usize) -> Self {
+        self.lod_bake_budget = Some(chunks);
+        self
+    }
+
+    /// Wraps the tilemap around horizontally, vertically or both, like a
+    /// cylinder or a torus.
+    ///
+    /// This requires the tilemap to have [`dimensions`]. Tile points outside
+    /// of the dimensions are wrapped back into them and chunks across the seam
+    /// are shown again on the other side when automatically spawned.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let builder = TilemapBuilder::new().dimensions(8, 4).wrap(true, false);
+    /// ```
+    ///
+    /// [`dimensions`]: TilemapBuilder::dimensions
+    pub fn wrap(mut self, x: bool, y: bool) -> Self {
+        self.wrap_x = x;
+        self.wrap_y = y;
+        self
+    }
+
+    /// Lights the tiles with ambient light and lights that spread over the
+    /// grid.
+    ///
+    /// See the [`light`] module for more information.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::{light::TilemapLighting, prelude::*};
+    ///
+    /// let builder = TilemapBuilder::new().lighting(TilemapLighting::default());
+    /// ```
+    ///
+    /// [`light`]: crate::light
+    pub fn lighting(mut self, lighting: TilemapLighting) -> Self {
+        self.lighting = Some(lighting);
+        self
+    }
+
+    /// Records the tile edits so that they can be undone and redone.
+    ///
+    /// See the [`history`] module for more information.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::{history::TilemapHistory, prelude::*};
+    ///
+    /// let builder = TilemapBuilder::new().history(TilemapHistory::new(50, 1024 * 1024));
+    /// ```
+    ///
+    /// [`history`]: crate::history
+    pub fn history(mut self, history: TilemapHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
+
     /// Sets the Rapier physics scale for colliders and rigid bodies created
     /// for layers with colliders.
     #[cfg(feature = "bevy_rapier2d")]
@@ -623,21 +1042,45 @@ impl TilemapBuilder {
         let mut tilemap = Tilemap {
             topology: self.topology,
             dimensions: self.dimensions,
+            wrap_x: self.wrap_x,
+            wrap_y: self.wrap_y,
             chunk_dimensions: self.chunk_dimensions,
             tile_dimensions,
             layers: vec![None; z_layers],
             auto_flags: self.auto_flags,
             auto_spawn: self.auto_spawn,
+            auto_spawn_margin: self.auto_spawn_margin,
+            auto_spawn_hysteresis: self.auto_spawn_hysteresis,
+            spawn_budget: self.spawn_budget,
+            mesh_budget: self.mesh_budget,
+            lod_threshold: self.lod_threshold,
+            lod_downsample: self.lod_downsample,
+            lod_bake_budget: self.lod_bake_budget,
+            lighting: self.lighting,
+            history: self.history,
             #[cfg(feature = "bevy_rapier2d")]
             physics_scale: self.physics_scale,
             custom_flags: Vec::new(),
             texture_atlas,
             chunks: Default::default(),
             entities: Default::default(),
-            chunk_events: Default::default(),
+            chunk_requests: Vec::new(),
             #[cfg(feature = "bevy_rapier2d")]
-            collision_events: Default::default(),
+            collision_events: Vec::new(),
             spawned: Default::default(),
+            spawn_queue: Vec::new(),
+            spawn_focus: Vec::new(),
+            lod_active: false,
+            lights: HashMap::default(),
+            light_dirty: false,
+            light_dirty_chunks: HashSet::default(),
+            regions: TilemapRegions::default(),
+            tile_entities: TileEntityIndex::default(),
+            tile_changes: TileChanges::default(),
+            parallax_focus: Vec2::zero(),
+            layer_scrolls: HashMap::default(),
+            mirror_chunks: HashSet::default(),
+            mirror_entities: HashMap::default(),
         };
 
         if let Some(mut layers) = self.layers {
@@ -645,11 +1088,26 @@ impl TilemapBuilder {
                 tilemap.add_layer(layer, z_layer)?;
             }
         }
+        tilemap.mark_light_dirty();
 
         Ok(tilemap)
     }
 }
 
+/// Takes a tile point in a chunk and returns the global tile point.
+fn chunk_tile_to_point(
+    chunk_dimensions: Dimension2,
+    chunk_point: Point2,
+    tile_point: Point2,
+) -> Point2 {
+    let width = chunk_dimensions.width as i32;
+    let height = chunk_dimensions.height as i32;
+    Point2::new(
+        tile_point.x + (width * chunk_point.x) - (width / 2),
+        tile_point.y + (height * chunk_point.y) - (height / 2),
+    )
+}
+
 impl TypeUuid for Tilemap {
     const TYPE_UUID: Uuid = Uuid::from_u128(109481186966523254410691740507722642628);
 }
@@ -659,21 +1117,45 @@ impl Default for Tilemap {
         Tilemap {
             topology: GridTopology::Square,
             dimensions: None,
+            wrap_x: false,
+            wrap_y: false,
             chunk_dimensions: DEFAULT_CHUNK_DIMENSIONS,
             tile_dimensions: DEFAULT_TEXTURE_DIMENSIONS,
             layers: vec![None; DEFAULT_Z_LAYERS],
             auto_flags: AutoFlags::NONE,
             auto_spawn: None,
+            auto_spawn_margin: 0,
+            auto_spawn_hysteresis: DEFAULT_AUTO_SPAWN_HYSTERESIS,
+            spawn_budget: None,
+            mesh_budget: None,
+            lod_threshold: None,
+            lod_downsample: DEFAULT_LOD_DOWNSAMPLE,
+            lod_bake_budget: None,
+            lighting: None,
+            history: None,
             #[cfg(feature = "bevy_rapier2d")]
             physics_scale: 1.0,
             custom_flags: Vec::new(),
             texture_atlas: Handle::default(),
             chunks: Default::default(),
             entities: Default::default(),
-            chunk_events: Default::default(),
+            chunk_requests: Vec::new(),
             #[cfg(feature = "bevy_rapier2d")]
-            collision_events: Default::default(),
+            collision_events: Vec::new(),
             spawned: Default::default(),
+            spawn_queue: Vec::new(),
+            spawn_focus: Vec::new(),
+            lod_active: false,
+            lights: HashMap::default(),
+            light_dirty: false,
+            light_dirty_chunks: HashSet::default(),
+            regions: TilemapRegions::default(),
+            tile_entities: TileEntityIndex::default(),
+            tile_changes: TileChanges::default(),
+            parallax_focus: Vec2::zero(),
+            layer_scrolls: HashMap::default(),
+            mirror_chunks: HashSet::default(),
+            mirror_entities: HashMap::default(),
         }
     }
 }
@@ -824,12 +1306,16 @@ impl Tilemap {
         let layer_kinds = self
             .layers
             .iter()
-            .map(|x| x.and_then(|y| Some(y.kind)))
+            .map(|x| x.as_ref().map(|y| y.kind))
             .collect::<Vec<Option<LayerKind>>>();
         let chunk = Chunk::new(point, &layer_kinds, self.chunk_dimensions);
         match self.chunks.insert(point, chunk) {
             Some(_) => Err(ErrorKind::ChunkAlreadyExists(point).into()),
-            None => Ok(()),
+            None => {
+                self.chunk_requests.push(ChunkRequest::Load { point });
+                self.mark_light_chunk_dirty(point);
+                Ok(())
+            }
         }
     }
 
@@ -863,6 +1349,16 @@ impl Tilemap {
     pub fn add_layer_with_kind(&mut self, kind: LayerKind, z_order: usize) -> TilemapResult<()> {
         let layer = TilemapLayer {
             kind,
+            pipeline: None,
+            blend: LayerBlend::default(),
+            depth: LayerDepth::default(),
+            z_offset: 0.0,
+            parallax: Vec2::one(),
+            scroll: Vec2::zero(),
+            wrap: false,
+            visible: true,
+            opacity: 1.0,
+            tint: Color::WHITE,
             #[cfg(feature = "bevy_rapier2d")]
             interaction_groups: InteractionGroups::default(),
         };
@@ -913,7 +1409,7 @@ impl Tilemap {
     /// };
     /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
     ///
-    /// assert!(tilemap.add_layer(layer, 1).is_ok());
+    /// assert!(tilemap.add_layer(layer.clone(), 1).is_ok());
     /// assert!(tilemap.add_layer(layer, 1).is_err());
     /// ```
     ///
@@ -925,7 +1421,7 @@ impl Tilemap {
             if inner_layer.is_some() {
                 return Err(ErrorKind::LayerExists(z_order).into());
             }
-            *inner_layer = Some(layer);
+            *inner_layer = Some(layer.clone());
         }
 
         for chunk in self.chunks.values_mut() {
@@ -984,6 +1480,165 @@ impl Tilemap {
         Ok(())
     }
 
+    /// Returns the configuration of a layer, if it exists.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.add_layer(TilemapLayer { kind: LayerKind::Sparse, ..Default::default() }, 1);
+    ///
+    /// assert_eq!(tilemap.layer(1).map(|layer| layer.kind), Some(LayerKind::Sparse));
+    /// assert!(tilemap.layer(2).is_none());
+    /// ```
+    pub fn layer(&self, z_order: usize) -> Option<&TilemapLayer> {
+        self.layers.get(z_order).and_then(|layer| layer.as_ref())
+    }
+
+    /// Returns a mutable reference to a layer, or an error if it does not
+    /// exist.
+    fn layer_mut(&mut self, z_order: usize) -> TilemapResult<&mut TilemapLayer> {
+        self.layers
+            .get_mut(z_order)
+            .and_then(|layer| layer.as_mut())
+            .ok_or_else(|| ErrorKind::LayerDoesNotExist(z_order).into())
+    }
+
+    /// Marks the level of detail textures of every chunk to be baked again.
+    fn mark_lod_dirty(&mut self) {
+        for chunk in self.chunks.values_mut() {
+            chunk.mark_lod_dirty();
+        }
+    }
+
+    /// Shows or hides a layer.
+    ///
+    /// The chunk layers are hidden without despawning them or building their
+    /// meshes again, so this is cheap to toggle, such as to hide a roof while
+    /// the player is inside.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the layer does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.add_layer(TilemapLayer { kind: LayerKind::Sparse, ..Default::default() }, 1);
+    ///
+    /// assert!(tilemap.set_layer_visible(1, false).is_ok());
+    /// assert_eq!(tilemap.layer(1).map(|layer| layer.visible), Some(false));
+    /// assert!(tilemap.set_layer_visible(2, false).is_err());
+    /// ```
+    pub fn set_layer_visible(&mut self, z_order: usize, visible: bool) -> TilemapResult<()> {
+        let layer = self.layer_mut(z_order)?;
+        if layer.visible != visible {
+            layer.visible = visible;
+            self.mark_lod_dirty();
+        }
+        Ok(())
+    }
+
+    /// Sets the opacity of a layer, multiplied into the alpha of its tiles.
+    ///
+    /// This is applied as a uniform to the chunk layers without building their
+    /// meshes again, so it can be animated every frame to fade a layer in or
+    /// out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the layer does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.add_layer(TilemapLayer { kind: LayerKind::Sparse, ..Default::default() }, 1);
+    ///
+    /// assert!(tilemap.set_layer_opacity(1, 0.5).is_ok());
+    /// assert_eq!(tilemap.layer(1).map(|layer| layer.opacity), Some(0.5));
+    /// ```
+    pub fn set_layer_opacity(&mut self, z_order: usize, opacity: f32) -> TilemapResult<()> {
+        let opacity = opacity.clamp(0.0, 1.0);
+        let layer = self.layer_mut(z_order)?;
+        if (layer.opacity - opacity).abs() > f32::EPSILON {
+            layer.opacity = opacity;
+            self.mark_lod_dirty();
+        }
+        Ok(())
+    }
+
+    /// Sets a tint that is multiplied into the color of every tile of a layer.
+    ///
+    /// Like the opacity, this is applied as a uniform and can be animated
+    /// without building the meshes of the chunk layers again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the layer does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_render::prelude::*;
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.add_layer(TilemapLayer { kind: LayerKind::Sparse, ..Default::default() }, 1);
+    ///
+    /// assert!(tilemap.set_layer_tint(1, Color::RED).is_ok());
+    /// assert_eq!(tilemap.layer(1).map(|layer| layer.tint), Some(Color::RED));
+    /// ```
+    pub fn set_layer_tint(&mut self, z_order: usize, tint: Color) -> TilemapResult<()> {
+        let layer = self.layer_mut(z_order)?;
+        if layer.tint != tint {
+            layer.tint = tint;
+            self.mark_lod_dirty();
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if a layer exists and is visible.
+    pub(crate) fn is_layer_visible(&self, z_order: usize) -> bool {
+        self.layer(z_order).map_or(false, |layer| layer.visible)
+    }
+
+    /// Returns the color that the tiles of a layer are multiplied by, from its
+    /// tint and opacity.
+    pub(crate) fn layer_color(&self, z_order: usize) -> Color {
+        self.layer(z_order).map_or(Color::WHITE, |layer| {
+            let [r, g, b, a]: [f32; 4] = layer.tint.into();
+            Color::rgba(r, g, b, a * layer.opacity)
+        })
+    }
+
     /// Removes a layer from the tilemap and inner chunks.
     ///
     /// **Warning**: This is destructive if you have tiles that exist on that
@@ -1064,7 +1719,7 @@ impl Tilemap {
         if self.spawned.contains(&(point.x, point.y)) {
             return Ok(());
         } else {
-            self.chunk_events.send(TilemapChunkEvent::Spawned { point });
+            self.chunk_requests.push(ChunkRequest::Spawn { point });
         }
 
         Ok(())
@@ -1155,8 +1810,9 @@ impl Tilemap {
 
         if let Some(chunk) = self.chunks.get_mut(&point) {
             let entities = chunk.get_entities();
-            self.chunk_events
-                .send(TilemapChunkEvent::Despawned { entities, point })
+            chunk.clear_lod();
+            self.chunk_requests
+                .push(ChunkRequest::Despawn { entities, point })
         }
 
         Ok(())
@@ -1203,13 +1859,18 @@ impl Tilemap {
         let point = point.into();
         self.despawn_chunk(point)?;
 
-        self.chunks.remove(&point);
+        if self.chunks.remove(&point).is_some() {
+            self.chunk_requests.push(ChunkRequest::Unload { point });
+            self.mark_light_chunk_dirty(point);
+        }
 
         Ok(())
     }
 
     /// Takes a tile point and changes it into a chunk point.
     ///
+    /// The tile point is wrapped first if the tilemap wraps.
+    ///
     /// # Examples
     /// ```
     /// use bevy_asset::{prelude::*, HandleId};
@@ -1238,12 +1899,18 @@ impl Tilemap {
     /// assert_eq!((-1, -1), chunk_point);
     /// ```
     pub fn point_to_chunk_point<P: Into<Point2>>(&self, point: P) -> (i32, i32) {
-        let point: Point2 = point.into();
+        let point = self.unwrapped_chunk_point(self.wrap_point(point));
+        (point.x, point.y)
+    }
+
+    /// Takes a tile point and changes it into a chunk point without wrapping
+    /// it.
+    pub(crate) fn unwrapped_chunk_point(&self, point: Point2) -> Point2 {
         let width = self.chunk_dimensions.width as f32;
         let height = self.chunk_dimensions.height as f32;
         let x = ((point.x as f32 + width / 2.0) / width).floor() as i32;
         let y = ((point.y as f32 + height / 2.0) / height).floor() as i32;
-        (x, y)
+        Point2::new(x, y)
     }
 
     /// Sorts tiles into the chunks they belong to.
@@ -1260,12 +1927,12 @@ impl Tilemap {
 
         let mut chunk_map: HashMap<Point2, Vec<Tile<Point2>>> = HashMap::default();
         for tile in tiles.into_iter() {
-            let global_tile_point: Point2 = tile.point.into();
+            let global_tile_point = self.wrap_point(tile.point);
             let chunk_point: Point2 = self.point_to_chunk_point(global_tile_point).into();
 
-            if let Some(layer) = self.layers.get(tile.z_order as usize) {
+            if let Some(layer) = self.layers.get(tile.z_order) {
                 if layer.as_ref().is_none() {
-                    self.add_layer(TilemapLayer::default(), tile.z_order as usize)?;
+                    self.add_layer(TilemapLayer::default(), tile.z_order)?;
                 }
             } else {
                 return Err(ErrorKind::LayerDoesNotExist(tile.z_order).into());
@@ -1281,6 +1948,7 @@ impl Tilemap {
                 z_order: tile.z_order,
                 sprite_index: tile.sprite_index,
                 tint: tile.tint,
+                palette: tile.palette,
             };
             if let Some(tiles) = chunk_map.get_mut(&chunk_point) {
                 tiles.push(chunk_tile);
@@ -1334,9 +2002,9 @@ impl Tilemap {
     /// // Set multiple tiles and unwrap the result
     /// tilemap.insert_tiles(tiles).unwrap();
     ///
-    /// assert_eq!(tilemap.get_tile((1, 1), 0), Some(&RawTile { index: 0, color: Color::WHITE }));
-    /// assert_eq!(tilemap.get_tile((2, 2), 0), Some(&RawTile { index: 1, color: Color::WHITE }));
-    /// assert_eq!(tilemap.get_tile((3, 3), 0), Some(&RawTile { index: 2, color: Color::WHITE }));
+    /// assert_eq!(tilemap.get_tile((1, 1), 0), Some(&RawTile { index: 0, color: Color::WHITE, palette: 0 }));
+    /// assert_eq!(tilemap.get_tile((2, 2), 0), Some(&RawTile { index: 1, color: Color::WHITE, palette: 0 }));
+    /// assert_eq!(tilemap.get_tile((3, 3), 0), Some(&RawTile { index: 2, color: Color::WHITE, palette: 0 }));
     /// assert_eq!(tilemap.get_tile((4, 4), 0), None);
     /// ```
     ///
@@ -1347,6 +2015,7 @@ impl Tilemap {
         I: IntoIterator<Item = Tile<P>>,
     {
         let chunk_map = self.sort_tiles_to_chunks(tiles)?;
+        let implicit = self.begin_implicit_transaction("Insert tiles");
         for (chunk_point, tiles) in chunk_map.into_iter() {
             // Is there a better way to do this? Clippy hates if I don't do it
             // like this talking about constructing regardless yet, here it is,
@@ -1355,10 +2024,12 @@ impl Tilemap {
             let layers = self.layers.clone();
             let chunk_dimensions = self.chunk_dimensions;
             let chunk = if self.auto_flags.contains(AutoFlags::AUTO_CHUNK) {
+                let chunk_requests = &mut self.chunk_requests;
                 self.chunks.entry(chunk_point).or_insert_with(|| {
+                    chunk_requests.push(ChunkRequest::Load { point: chunk_point });
                     let layer_kinds = layers
                         .iter()
-                        .map(|x| x.and_then(|y| Some(y.kind)))
+                        .map(|x| x.as_ref().map(|y| y.kind))
                         .collect::<Vec<Option<LayerKind>>>();
                     Chunk::new(chunk_point, &layer_kinds, chunk_dimensions)
                 })
@@ -1369,22 +2040,32 @@ impl Tilemap {
                 }
             };
 
-            let mut layers = HashMap::default();
+            let mut layers = Vec::new();
             for tile in tiles.iter() {
                 let index = self.chunk_dimensions.encode_point_unchecked(tile.point);
+                let point = chunk_tile_to_point(chunk_dimensions, chunk_point, tile.point);
+                let old = chunk.get_tile(tile.z_order, index).copied();
+                self.tile_changes.record(point, tile.z_order, old);
+                if let Some(history) = self.history.as_mut() {
+                    history.record(point, tile.z_order, old);
+                }
                 // TODO: Tile collider must be added to the chunk.
                 chunk.set_tile(index, *tile);
-                if let Some(entity) = chunk.get_entity(tile.z_order) {
-                    layers.entry(tile.z_order).or_insert(entity);
+                if !layers.contains(&tile.z_order) {
+                    layers.push(tile.z_order);
                 }
             }
 
-            self.chunk_events
-                .send(TilemapChunkEvent::Modified { layers });
+            self.chunk_requests.push(ChunkRequest::Modify {
+                point: chunk_point,
+                layers,
+            });
+            self.mark_light_chunk_dirty(chunk_point);
             #[cfg(feature = "bevy_rapier2d")]
             self.collision_events
-                .send(TilemapCollisionEvent::Spawned { chunk_point, tiles });
+                .push(TilemapCollisionEvent::Spawned { chunk_point, tiles });
         }
+        self.commit_implicit_transaction(implicit);
 
         Ok(())
     }
@@ -1419,7 +2100,7 @@ impl Tilemap {
     /// let tile = Tile { point, sprite_index, ..Default::default() };
     ///
     /// assert!(tilemap.insert_tile(tile).is_ok());
-    /// assert_eq!(tilemap.get_tile((9, 3), 0), Some(&RawTile { index: 3, color: Color::WHITE }))
+    /// assert_eq!(tilemap.get_tile((9, 3), 0), Some(&RawTile { index: 3, color: Color::WHITE, palette: 0 }))
     /// ```
     ///
     /// # Errors
@@ -1465,7 +2146,7 @@ impl Tilemap {
     /// tilemap.clear_tiles(to_remove).unwrap();
     /// assert_eq!(tilemap.get_tile((1, 1), 0), None);
     /// assert_eq!(tilemap.get_tile((2, 2), 0), None);
-    /// assert_eq!(tilemap.get_tile((3, 3), 0), Some(&RawTile { index: 0, color: Color::WHITE} ));
+    /// assert_eq!(tilemap.get_tile((3, 3), 0), Some(&RawTile { index: 0, color: Color::WHITE, palette: 0 } ));
     /// ```
     ///
     /// # Errors
@@ -1484,36 +2165,49 @@ impl Tilemap {
                 sprite_index: 0,
                 z_order,
                 tint: Color::rgba(0.0, 0.0, 0.0, 0.0),
+                palette: 0,
             });
         }
         let chunk_map = self.sort_tiles_to_chunks(tiles)?;
-        let mut layers = HashMap::default();
+        let implicit = self.begin_implicit_transaction("Clear tiles");
+        let chunk_dimensions = self.chunk_dimensions;
         for (chunk_point, tiles) in chunk_map.into_iter() {
             let chunk = match self.chunks.get_mut(&chunk_point) {
                 Some(c) => c,
                 None => return Err(ErrorKind::MissingChunk.into()),
             };
+            let mut layers = Vec::new();
             for tile in tiles.iter() {
                 let index = self.chunk_dimensions.encode_point_unchecked(tile.point);
+                let point = chunk_tile_to_point(chunk_dimensions, chunk_point, tile.point);
+                let old = chunk.get_tile(tile.z_order, index).copied();
+                self.tile_changes.record(point, tile.z_order, old);
+                if let Some(history) = self.history.as_mut() {
+                    history.record(point, tile.z_order, old);
+                }
                 chunk.remove_tile(index, tile.z_order);
-                if let Some(entity) = chunk.get_entity(tile.z_order) {
-                    layers.entry(tile.z_order).or_insert(entity);
+                if !layers.contains(&tile.z_order) {
+                    layers.push(tile.z_order);
                 }
             }
 
+            self.chunk_requests.push(ChunkRequest::Modify {
+                point: chunk_point,
+                layers,
+            });
+            self.mark_light_chunk_dirty(chunk_point);
             #[cfg(feature = "bevy_rapier2d")]
             self.collision_events
-                .send(TilemapCollisionEvent::Despawned { chunk_point, tiles });
+                .push(TilemapCollisionEvent::Despawned { chunk_point, tiles });
         }
-
-        self.chunk_events
-            .send(TilemapChunkEvent::Modified { layers });
+        self.commit_implicit_transaction(implicit);
 
         Ok(())
     }
 
     /// Takes a global tile point and returns a tile point in a chunk.
-    fn point_to_tile_point(&self, point: Point2) -> Point2 {
+    pub(crate) fn point_to_tile_point(&self, point: Point2) -> Point2 {
+        let point = self.wrap_point(point);
         let chunk_point: Point2 = self.point_to_chunk_point(point).into();
         let width = self.chunk_dimensions.width as i32;
         let height = self.chunk_dimensions.height as i32;
@@ -1588,7 +2282,7 @@ impl Tilemap {
     /// let tile = Tile { point, sprite_index, ..Default::default() };
     ///
     /// assert!(tilemap.insert_tile(tile).is_ok());
-    /// assert_eq!(tilemap.get_tile((9, 3), 0), Some(&RawTile { index: 3, color: Color::WHITE }));
+    /// assert_eq!(tilemap.get_tile((9, 3), 0), Some(&RawTile { index: 3, color: Color::WHITE, palette: 0 }));
     /// assert_eq!(tilemap.get_tile((10, 4), 0), None);
     /// ```
     pub fn get_tile<P>(&mut self, point: P, z_order: usize) -> Option<&RawTile>
@@ -1629,7 +2323,7 @@ impl Tilemap {
     /// let tile = Tile { point, sprite_index, ..Default::default() };
     ///
     /// assert!(tilemap.insert_tile(tile).is_ok());
-    /// assert_eq!(tilemap.get_tile_mut((2, 5), 0), Some(&mut RawTile { index: 2, color: Color::WHITE }));
+    /// assert_eq!(tilemap.get_tile_mut((2, 5), 0), Some(&mut RawTile { index: 2, color: Color::WHITE, palette: 0 }));
     /// assert_eq!(tilemap.get_tile_mut((1, 4), 0), None);
     /// ```
     pub fn get_tile_mut<P>(&mut self, point: P, z_order: usize) -> Option<&mut RawTile>
@@ -1639,22 +2333,206 @@ impl Tilemap {
         let point: Point2 = point.into();
         let chunk_point: Point2 = self.point_to_chunk_point(point).into();
         let tile_point = self.point_to_tile_point(point);
+        let implicit = self.begin_implicit_transaction("Change tile");
+        let chunk_dimensions = self.chunk_dimensions;
         let chunk = self.chunks.get_mut(&chunk_point)?;
         let index = self.chunk_dimensions.encode_point_unchecked(tile_point);
-        let mut layers = HashMap::default();
-        if let Some(entity) = chunk.get_entity(z_order) {
-            layers.insert(z_order, entity);
-            self.chunk_events
-                .send(TilemapChunkEvent::Modified { layers });
+        let old = chunk.get_tile(z_order, index).copied();
+        let point = chunk_tile_to_point(chunk_dimensions, chunk_point, tile_point);
+        self.tile_changes.record(point, z_order, old);
+        if let Some(history) = self.history.as_mut() {
+            if old.is_some() {
+                history.record(point, z_order, old);
+            }
+            if implicit {
+                history.commit();
+            }
+        }
+        self.chunk_requests.push(ChunkRequest::Modify {
+            point: chunk_point,
+            layers: vec![z_order],
+        });
+        if self.lighting.is_some() {
+            self.light_dirty_chunks.insert(chunk_point);
         }
         chunk.get_tile_mut(z_order, index)
     }
 
-    /// Returns the center tile, if the tilemap has dimensions.
-    ///
-    /// Returns `None` if the tilemap has no constrainted dimensions.
+    /// Returns the undo and redo history of tile edits, if it is recorded.
+    pub fn history(&self) -> Option<&TilemapHistory> {
+        self.history.as_ref()
+    }
+
+    /// Sets the undo and redo history of tile edits. `None` stops recording
+    /// tile edits and drops the history.
+    pub fn set_history(&mut self, history: Option<TilemapHistory>) {
+        self.history = history;
+    }
+
+    /// Starts a named transaction which groups the following tile edits to be
+    /// undone and redone at once, committing the transaction before it.
     ///
-    /// # Examples
+    /// Does nothing if the tilemap has no history.
+    pub fn begin_transaction<S: Into<String>>(&mut self, name: S) {
+        if let Some(history) = self.history.as_mut() {
+            history.begin(name.into());
+        }
+    }
+
+    /// Commits the current transaction so that it can be undone.
+    ///
+    /// Transactions without any tile edits are dropped.
+    pub fn commit_transaction(&mut self) {
+        if let Some(history) = self.history.as_mut() {
+            history.commit();
+        }
+    }
+
+    /// Puts the tiles of the last transaction back as they were, returning its
+    /// name, or `None` if there was nothing to undo.
+    ///
+    /// A transaction that was not yet committed is committed first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a tile can no longer be set, such as when its chunk
+    /// was removed. No tile is changed and the transaction is dropped in that
+    /// case.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::{history::TilemapHistory, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.set_history(Some(TilemapHistory::default()));
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// tilemap.insert_tile(Tile { point: (0, 0), sprite_index: 1, ..Default::default() }).unwrap();
+    /// tilemap.insert_tile(Tile { point: (0, 0), sprite_index: 2, ..Default::default() }).unwrap();
+    ///
+    /// assert!(tilemap.undo().unwrap().is_some());
+    /// assert_eq!(tilemap.get_tile((0, 0), 0).map(|tile| tile.index), Some(1));
+    /// assert!(tilemap.undo().unwrap().is_some());
+    /// assert_eq!(tilemap.get_tile((0, 0), 0).map(|tile| tile.index), None);
+    /// assert!(tilemap.undo().unwrap().is_none());
+    /// ```
+    pub fn undo(&mut self) -> TilemapResult<Option<String>> {
+        self.replay_transaction(true)
+    }
+
+    /// Applies the last undone transaction again, returning its name, or
+    /// `None` if there was nothing to redo.
+    ///
+    /// Any new tile edit after an undo clears the transactions to redo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a tile can no longer be set, such as when its chunk
+    /// was removed. No tile is changed and the transaction is dropped in that
+    /// case.
+    pub fn redo(&mut self) -> TilemapResult<Option<String>> {
+        self.replay_transaction(false)
+    }
+
+    /// Undoes or redoes a transaction, recording the tiles it replaced as the
+    /// transaction to redo or undo it again.
+    fn replay_transaction(&mut self, undo: bool) -> TilemapResult<Option<String>> {
+        // The history is taken out so that restoring the tiles is not recorded.
+        let mut history = if let Some(history) = self.history.take() {
+            history
+        } else {
+            return Ok(None);
+        };
+        let transaction = if undo {
+            history.pop_undo()
+        } else {
+            history.pop_redo()
+        };
+        let result = match transaction {
+            Some(transaction) => self.restore_edits(&transaction.edits).map(|edits| {
+                let name = transaction.name.clone();
+                let inverse = Transaction::from_edits(transaction.name, edits);
+                if undo {
+                    history.push_redo(inverse);
+                } else {
+                    history.push_undo(inverse);
+                }
+                Some(name)
+            }),
+            None => Ok(None),
+        };
+        self.history = Some(history);
+        result
+    }
+
+    /// Sets tiles back to recorded values, returning the values they had.
+    ///
+    /// Every tile is checked to be settable first so that nothing is changed
+    /// if any of them is not.
+    fn restore_edits(&mut self, edits: &[TileEdit]) -> TilemapResult<Vec<TileEdit>> {
+        let auto_chunk = self.auto_flags.contains(AutoFlags::AUTO_CHUNK);
+        for edit in edits.iter() {
+            if self.layers.get(edit.z_order).is_none() {
+                return Err(ErrorKind::LayerDoesNotExist(edit.z_order).into());
+            }
+            let chunk_point: Point2 = self.point_to_chunk_point(edit.point).into();
+            let can_create = auto_chunk && edit.tile.is_some();
+            if !can_create && !self.chunks.contains_key(&chunk_point) {
+                return Err(ErrorKind::MissingChunk.into());
+            }
+        }
+        let mut replaced = Vec::with_capacity(edits.len());
+        let mut tiles = Vec::new();
+        let mut cleared = Vec::new();
+        for edit in edits.iter() {
+            replaced.push(TileEdit {
+                point: edit.point,
+                z_order: edit.z_order,
+                tile: self.get_tile(edit.point, edit.z_order).copied(),
+            });
+            if let Some(tile) = edit.tile {
+                tiles.push(Tile {
+                    point: edit.point,
+                    z_order: edit.z_order,
+                    sprite_index: tile.index,
+                    tint: tile.color,
+                    palette: tile.palette,
+                });
+            } else {
+                cleared.push((edit.point, edit.z_order));
+            }
+        }
+        self.clear_tiles(cleared)?;
+        self.insert_tiles(tiles)?;
+        Ok(replaced)
+    }
+
+    /// Starts a transaction for a single tile edit if the history is recorded
+    /// and no transaction is started, returning `true` if it did.
+    fn begin_implicit_transaction(&mut self, name: &str) -> bool {
+        self.history
+            .as_mut()
+            .map_or(false, |history| history.begin_implicit(name))
+    }
+
+    /// Commits the transaction of a single tile edit, if it was started for
+    /// it.
+    fn commit_implicit_transaction(&mut self, implicit: bool) {
+        if implicit {
+            self.commit_transaction();
+        }
+    }
+
+    /// Returns the center tile, if the tilemap has dimensions.
+    ///
+    /// Returns `None` if the tilemap has no constrainted dimensions.
+    ///
+    /// # Examples
     ///
     /// ```
     /// use bevy_asset::{prelude::*, HandleId};
@@ -1863,136 +2741,1656 @@ impl Tilemap {
         self.chunks.get(point)
     }
 
-    /// The topology of the tilemap grid.
+    /// Returns the translation of a chunk relative to the tilemap.
+    pub(crate) fn chunk_translation(&self, point: Point2) -> Vec2 {
+        let tile_dimensions = self.tile_dimensions;
+        let chunk_dimensions = self.chunk_dimensions;
+        use GridTopology::*;
+        let x = match self.topology {
+            HexX | HexEvenCols | HexOddCols => {
+                (((point.x * tile_dimensions.width as i32) as f32 * 0.75) as i32
+                    * chunk_dimensions.width as i32) as f32
+            }
+            HexY => {
+                (point.x * tile_dimensions.width as i32 * chunk_dimensions.width as i32) as f32
+                    + (point.y as f32 * chunk_dimensions.height as f32 * 0.5)
+                        * tile_dimensions.width as f32
+            }
+            Square | HexEvenRows | HexOddRows => {
+                (point.x * tile_dimensions.width as i32 * chunk_dimensions.width as i32) as f32
+            }
+        };
+        let y = match self.topology {
+            HexX => {
+                (point.y * tile_dimensions.height as i32 * chunk_dimensions.height as i32) as f32
+                    + (point.x as f32 * chunk_dimensions.width as f32 * 0.5)
+                        * tile_dimensions.height as f32
+            }
+            HexY | HexEvenRows | HexOddRows => {
+                (((point.y * tile_dimensions.height as i32) as f32 * 0.75) as i32
+                    * chunk_dimensions.height as i32) as f32
+            }
+            Square | HexEvenCols | HexOddCols => {
+                (point.y * tile_dimensions.height as i32 * chunk_dimensions.height as i32) as f32
+            }
+        };
+        Vec2::new(x, y)
+    }
+
+    /// Returns `true` if the tilemap wraps around horizontally.
     ///
-    /// Currently there are 7 topologies which are set with [`GridTopology`]. By
-    /// default this is square as it is the most common topology.
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
     ///
-    /// Typically, for most situations squares are used for local maps and hex
-    /// is used for war games or world maps. It is easier to define structures
-    /// with walls and floors with square but not impossible with hex.
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// [`GridTopology`]: crate::render::GridTopology
+    /// let mut tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .tile_dimensions(32, 32)
+    ///     .dimensions(4, 4)
+    ///     .finish()
+    ///     .unwrap();
+    /// assert!(!tilemap.wraps_x());
+    ///
+    /// tilemap.set_wrap(true, false);
+    /// assert!(tilemap.wraps_x());
+    /// assert!(!tilemap.wraps_y());
+    /// ```
+    pub fn wraps_x(&self) -> bool {
+        self.wrap_x
+    }
+
+    /// Returns `true` if the tilemap wraps around vertically.
+    pub fn wraps_y(&self) -> bool {
+        self.wrap_y
+    }
+
+    /// Sets if the tilemap wraps around horizontally and vertically.
+    ///
+    /// This only has an effect if the tilemap has dimensions.
+    pub fn set_wrap(&mut self, x: bool, y: bool) {
+        self.wrap_x = x;
+        self.wrap_y = y;
+    }
+
+    /// Wraps a tile point into the dimensions of the tilemap along the axes
+    /// that it wraps on.
+    ///
+    /// Points are returned as they are if the tilemap does not wrap or has no
+    /// dimensions.
     ///
     /// # Examples
     /// ```
     /// use bevy_asset::{prelude::*, HandleId};
     /// use bevy_sprite::prelude::*;
     /// use bevy_tilemap::prelude::*;
+    /// use bevy_tilemap_types::point::Point2;
     ///
     /// // In production use a strong handle from an actual source.
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
+    /// // Chunks -1 to 1 in each direction, so 96 tiles across.
     /// let tilemap = TilemapBuilder::new()
     ///     .texture_atlas(texture_atlas_handle)
-    ///     .topology(GridTopology::HexX)
     ///     .tile_dimensions(32, 32)
+    ///     .dimensions(2, 2)
+    ///     .chunk_dimensions(32, 32)
+    ///     .wrap(true, false)
     ///     .finish()
     ///     .unwrap();
     ///
-    /// assert_eq!(tilemap.topology(), GridTopology::HexX);
+    /// assert_eq!(tilemap.wrap_point((48, 48)), Point2::new(-48, 48));
+    /// assert_eq!(tilemap.wrap_point((-49, 0)), Point2::new(47, 0));
     /// ```
-    pub fn topology(&self) -> GridTopology {
-        self.topology
+    pub fn wrap_point<P: Into<Point2>>(&self, point: P) -> Point2 {
+        let point: Point2 = point.into();
+        let dimensions = match self.dimensions {
+            Some(dimensions) if self.wrap_x || self.wrap_y => dimensions,
+            _ => return point,
+        };
+        let width = self.chunk_dimensions.width as i32;
+        let height = self.chunk_dimensions.height as i32;
+        let wrap = |value: i32, min_chunk: i32, max_chunk: i32, size: i32| {
+            let min = (min_chunk as f32 * size as f32 - size as f32 / 2.0).ceil() as i32;
+            let period = (max_chunk - min_chunk + 1) * size;
+            (value - min).rem_euclid(period) + min
+        };
+        let x = if self.wrap_x {
+            wrap(point.x, dimensions.x_min(), dimensions.x_max(), width)
+        } else {
+            point.x
+        };
+        let y = if self.wrap_y {
+            wrap(point.y, dimensions.y_min(), dimensions.y_max(), height)
+        } else {
+            point.y
+        };
+        Point2::new(x, y)
     }
 
-    /// Returns a reference to the tilemap chunk events.
+    /// Returns the points of the tiles which share an edge with a tile, for the
+    /// topology of the tilemap.
     ///
-    /// This is handy if it is needed to know when new chunks are created which
-    /// can then be used to trigger events with other systems. For example,
-    /// if you have a system that adds tiles procedurally to the chunks, upon
-    /// a chunk event this can be used to trigger the creation of those tiles.
+    /// Square tiles have four neighbours and hex tiles six. The neighbours are
+    /// wrapped if the tilemap wraps, and those outside of the dimensions of
+    /// the tilemap are left out. Offset hex topologies assume chunks with an
+    /// even width and height, so the offset rows or columns line up across
+    /// chunks.
     ///
     /// # Examples
     /// ```
-    /// use bevy_app::prelude::*;
     /// use bevy_asset::{prelude::*, HandleId};
     /// use bevy_sprite::prelude::*;
-    /// use bevy_tilemap::{prelude::*, event::TilemapChunkEvent};
+    /// use bevy_tilemap::prelude::*;
+    /// use bevy_tilemap_types::point::Point2;
     ///
     /// // In production use a strong handle from an actual source.
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// let tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .tile_dimensions(32, 32)
+    ///     .dimensions(2, 2)
+    ///     .chunk_dimensions(32, 32)
+    ///     .wrap(true, false)
+    ///     .finish()
+    ///     .unwrap();
     ///
-    /// let events: &Events<TilemapChunkEvent> = tilemap.chunk_events();
+    /// let neighbours = tilemap.neighbours((47, 0));
+    /// assert_eq!(neighbours.len(), 4);
+    /// assert!(neighbours.contains(&Point2::new(-48, 0)));
+    ///
+    /// // The top edge does not wrap.
+    /// assert_eq!(tilemap.neighbours((0, 47)).len(), 3);
     /// ```
-    pub fn chunk_events(&self) -> &Events<TilemapChunkEvent> {
-        &self.chunk_events
+    pub fn neighbours<P: Into<Point2>>(&self, point: P) -> Vec<Point2> {
+        let point: Point2 = point.into();
+        let is_even = |value: i32| value.rem_euclid(2) == 0;
+        use GridTopology::*;
+        let offsets: &[(i32, i32)] = match self.topology {
+            Square => &[(1, 0), (-1, 0), (0, 1), (0, -1)],
+            HexY => &[(1, 0), (-1, 0), (0, 1), (-1, 1), (0, -1), (1, -1)],
+            HexX => &[(0, 1), (0, -1), (1, 0), (1, -1), (-1, 0), (-1, 1)],
+            // Rows that are shifted right have their diagonal neighbours to the
+            // right, and rows shifted left to the left.
+            HexEvenRows | HexOddRows => {
+                if is_even(point.y) == (self.topology == HexEvenRows) {
+                    &[(1, 0), (-1, 0), (0, 1), (1, 1), (0, -1), (1, -1)]
+                } else {
+                    &[(1, 0), (-1, 0), (-1, 1), (0, 1), (-1, -1), (0, -1)]
+                }
+            }
+            // Columns that are shifted up have their diagonal neighbours above,
+            // and columns shifted down below.
+            HexEvenCols | HexOddCols => {
+                if is_even(point.x) == (self.topology == HexEvenCols) {
+                    &[(0, 1), (0, -1), (1, 0), (1, 1), (-1, 0), (-1, 1)]
+                } else {
+                    &[(0, 1), (0, -1), (1, -1), (1, 0), (-1, -1), (-1, 0)]
+                }
+            }
+        };
+        offsets
+            .iter()
+            .map(|(x, y)| self.wrap_point(Point2::new(point.x + x, point.y + y)))
+            .filter(|neighbour| {
+                self.dimensions.map_or(true, |dimensions| {
+                    dimensions
+                        .check_point(self.point_to_chunk_point(*neighbour).into())
+                        .is_ok()
+                })
+            })
+            .collect()
     }
 
-    /// Updates the chunk events. This should only be done once per frame.
-    pub(crate) fn chunk_events_update(&mut self) {
-        self.chunk_events.update()
+    /// Copies the tiles of a rectangle between two opposite corners on a set of
+    /// layers into a stamp.
+    ///
+    /// The stamp starts at the bottom left corner of the rectangle, even if
+    /// there are no tiles there. See the [`stamp`] module for more
+    /// information.
+    ///
+    /// [`stamp`]: crate::stamp
+    pub fn copy_rect<P: Into<Point2>>(
+        &self,
+        corner: P,
+        opposite: P,
+        layers: &[usize],
+    ) -> TileStamp {
+        let points = rect_points(corner.into(), opposite.into(), true);
+        self.copy_points(points, layers)
     }
 
-    /// Returns a reference to the tilemap collision events.
+    /// Copies the tiles at a set of points on a set of layers into a stamp.
+    ///
+    /// The points do not need to be connected. The stamp starts at the lowest
+    /// coordinates of the points, even if there are no tiles there.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    /// use bevy_tilemap_types::point::Point2;
     ///
-    /// This is handy if you need to know which collisions were spawned which
-    /// can then be used to trigger other systems. It should be used in
-    /// conjunction with [`chunk_events_update`] as collisions will spawn
-    /// on a chunk spawn and be despawned with a chunk despawn. Those will not
-    /// trigger events.
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// [`chunk_events_update`]:
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_tile(Tile { point: (3, 3), sprite_index: 1, ..Default::default() }).unwrap();
     ///
+    /// let stamp = tilemap.copy_tiles(vec![(2, 2), (3, 3), (9, 9)], &[0]);
     ///
-    #[cfg(feature = "bevy_rapier2d")]
-    pub fn collision_events(&self) -> &Events<TilemapCollisionEvent> {
-        &self.collision_events
+    /// assert_eq!(stamp.len(), 1);
+    /// assert_eq!(stamp.tiles()[0].point, Point2::new(1, 1));
+    /// ```
+    pub fn copy_tiles<P, I>(&self, points: I, layers: &[usize]) -> TileStamp
+    where
+        P: Into<Point2>,
+        I: IntoIterator<Item = P>,
+    {
+        let points = points.into_iter().map(|point| point.into()).collect();
+        self.copy_points(points, layers)
     }
 
-    /// Updates the collision events. This should only be done once per frame.
-    #[cfg(feature = "bevy_rapier2d")]
-    pub(crate) fn collision_events_update(&mut self) {
-        self.collision_events.update()
-    }
+    /// Copies the tiles at points into a stamp which starts at the lowest
+    /// coordinates of the points, looking up each chunk once.
+    fn copy_points(&self, points: Vec<Point2>, layers: &[usize]) -> TileStamp {
+        let origin = points.iter().fold(None, |min: Option<Point2>, point| {
+            Some(min.map_or(*point, |min| {
+                Point2::new(min.x.min(point.x), min.y.min(point.y))
+            }))
+        });
+        let origin = if let Some(origin) = origin {
+            origin
+        } else {
+            return TileStamp::default();
+        };
 
-    /// Returns a copy of the physics scale.
-    #[cfg(feature = "bevy_rapier2d")]
-    pub fn physics_scale(&self) -> f32 {
-        self.physics_scale
+        let mut chunk_map: HashMap<Point2, Vec<Point2>> = HashMap::default();
+        for point in points.into_iter() {
+            let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+            chunk_map.entry(chunk_point).or_default().push(point);
+        }
+        let mut tiles = Vec::new();
+        for (chunk_point, points) in chunk_map.into_iter() {
+            let chunk = if let Some(chunk) = self.chunks.get(&chunk_point) {
+                chunk
+            } else {
+                continue;
+            };
+            for point in points.into_iter() {
+                let tile_point = self.point_to_tile_point(point);
+                let index = self.chunk_dimensions.encode_point_unchecked(tile_point);
+                for z_order in layers.iter() {
+                    if let Some(tile) = chunk.get_tile(*z_order, index) {
+                        tiles.push(StampTile {
+                            point: point - origin,
+                            z_order: *z_order,
+                            tile: *tile,
+                        });
+                    }
+                }
+            }
+        }
+        // Sorted so that copies of the same tiles are equal.
+        tiles.sort_by_key(|tile| (tile.z_order, tile.point.y, tile.point.x));
+        TileStamp::from_tiles(tiles)
     }
 
-    /// Sets the physics scale.
-    #[cfg(feature = "bevy_rapier2d")]
-    pub fn set_physics_scale(&mut self, scale: f32) {
-        self.physics_scale = scale;
+    /// Pastes a stamp with its bottom left corner at an offset.
+    ///
+    /// Only the tiles of the stamp are set, the tiles around them are left as
+    /// they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a tile is out of bounds or its chunk does not exist
+    /// and the tilemap does not automatically create chunks.
+    ///
+    /// Returns an error if the stamp was rotated or flipped and the topology
+    /// of the tilemap is not square, as the shape of its tiles would be lost.
+    pub fn paste<P: Into<Point2>>(&mut self, stamp: &TileStamp, offset: P) -> TilemapResult<()> {
+        if stamp.is_transformed() && self.topology != GridTopology::Square {
+            return Err(ErrorKind::UnsupportedStampTransform.into());
+        }
+        self.insert_tiles(stamp.to_tiles(offset.into()))
     }
 
-    /// Returns an option containing a Dimension2.
-    pub(crate) fn auto_spawn(&self) -> Option<Dimension2> {
-        self.auto_spawn
+    /// Returns the points and z orders of the tiles that a stamp would replace
+    /// if it was pasted at an offset.
+    pub fn stamp_overlaps<P: Into<Point2>>(
+        &self,
+        stamp: &TileStamp,
+        offset: P,
+    ) -> Vec<(Point2, usize)> {
+        let offset: Point2 = offset.into();
+        let mut chunk_map: HashMap<Point2, Vec<(Point2, usize)>> = HashMap::default();
+        for tile in stamp.tiles().iter() {
+            let point = tile.point + offset;
+            let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+            chunk_map
+                .entry(chunk_point)
+                .or_default()
+                .push((point, tile.z_order));
+        }
+        let mut overlaps = Vec::new();
+        for (chunk_point, tiles) in chunk_map.into_iter() {
+            let chunk = if let Some(chunk) = self.chunks.get(&chunk_point) {
+                chunk
+            } else {
+                continue;
+            };
+            for (point, z_order) in tiles.into_iter() {
+                let tile_point = self.point_to_tile_point(point);
+                let index = self.chunk_dimensions.encode_point_unchecked(tile_point);
+                if chunk.get_tile(z_order, index).is_some() {
+                    overlaps.push((point, z_order));
+                }
+            }
+        }
+        overlaps
     }
 
-    /// Sets the auto spawn radius.
-    pub(crate) fn set_auto_spawn(&mut self, dimension: Dimension2) {
-        self.auto_spawn = Some(dimension);
+    /// Returns `true` if a stamp can be pasted at an offset without replacing
+    /// any tiles.
+    ///
+    /// Every tile of the stamp needs to be on a layer within the z layers of
+    /// the tilemap, within its dimensions and in a chunk that exists, unless
+    /// chunks are created automatically. Rotated and flipped stamps only fit
+    /// square tilemaps, as with [`paste`].
+    ///
+    /// [`paste`]: Tilemap::paste
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_render::prelude::*;
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::{chunk::RawTile, prelude::*, stamp::{StampTile, TileStamp}};
+    /// use bevy_tilemap_types::point::Point2;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// let tile = RawTile { index: 1, color: Color::WHITE, palette: 0 };
+    /// let pillar = TileStamp::new(vec![
+    ///     StampTile { point: Point2::new(0, 0), z_order: 0, tile },
+    ///     StampTile { point: Point2::new(0, 1), z_order: 0, tile },
+    /// ]);
+    ///
+    /// assert!(tilemap.stamp_fits(&pillar, (3, 3)));
+    /// tilemap.paste(&pillar, (3, 3)).unwrap();
+    ///
+    /// assert!(!tilemap.stamp_fits(&pillar, (3, 4)));
+    /// assert!(tilemap.stamp_fits(&pillar, (4, 4)));
+    ///
+    /// let floating = TileStamp::new(vec![StampTile { point: Point2::new(0, 0), z_order: 99, tile }]);
+    /// assert!(!tilemap.stamp_fits(&floating, (8, 8)));
+    /// assert!(tilemap.paste(&floating, (8, 8)).is_err());
+    /// ```
+    pub fn stamp_fits<P: Into<Point2>>(&self, stamp: &TileStamp, offset: P) -> bool {
+        if stamp.is_transformed() && self.topology != GridTopology::Square {
+            return false;
+        }
+        let offset: Point2 = offset.into();
+        let auto_chunk = self.auto_flags.contains(AutoFlags::AUTO_CHUNK);
+        let is_placeable = stamp.tiles().iter().all(|tile| {
+            if self.layers.get(tile.z_order).is_none() {
+                return false;
+            }
+            let chunk_point: Point2 = self.point_to_chunk_point(tile.point + offset).into();
+            let is_in_bounds = self.dimensions.map_or(true, |dimensions| {
+                dimensions.check_point(chunk_point).is_ok()
+            });
+            is_in_bounds && (auto_chunk || self.chunks.contains_key(&chunk_point))
+        });
+        is_placeable && self.stamp_overlaps(stamp, offset).is_empty()
     }
 
-    /// Returns a copy of the chunk's dimensions.
-    pub(crate) fn chunk_dimensions(&self) -> Dimension2 {
-        self.chunk_dimensions
+    /// Sets every tile of a layer in a line between two points, including
+    /// both.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a tile is out of bounds or its chunk does not exist
+    /// and the tilemap does not automatically create chunks.
+    pub fn draw_line<P: Into<Point2>>(
+        &mut self,
+        from: P,
+        to: P,
+        z_order: usize,
+        tile: RawTile,
+    ) -> TilemapResult<()> {
+        let points = line_points(from.into(), to.into());
+        self.draw_points(points, z_order, tile)
     }
 
-    /// Returns a copy of the chunk's tile dimensions.
-    pub(crate) fn tile_dimensions(&self) -> Dimension2 {
-        self.tile_dimensions
+    /// Sets every tile of a layer in a rectangle between two opposite corners,
+    /// either only its outline or filled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a tile is out of bounds or its chunk does not exist
+    /// and the tilemap does not automatically create chunks.
+    pub fn draw_rect<P: Into<Point2>>(
+        &mut self,
+        corner: P,
+        opposite: P,
+        z_order: usize,
+        tile: RawTile,
+        filled: bool,
+    ) -> TilemapResult<()> {
+        let points = rect_points(corner.into(), opposite.into(), filled);
+        self.draw_points(points, z_order, tile)
     }
 
-    /// Returns a reference to the hash set of spawned chunks.
-    pub(crate) fn spawned_chunks(&self) -> &HashSet<(i32, i32)> {
-        &self.spawned
+    /// Sets every tile of a layer in a circle of a radius in tiles around a
+    /// center, either only its outline or filled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a tile is out of bounds or its chunk does not exist
+    /// and the tilemap does not automatically create chunks.
+    pub fn draw_circle<P: Into<Point2>>(
+        &mut self,
+        center: P,
+        radius: u32,
+        z_order: usize,
+        tile: RawTile,
+        filled: bool,
+    ) -> TilemapResult<()> {
+        let points = circle_points(center.into(), radius, filled);
+        self.draw_points(points, z_order, tile)
     }
 
-    /// Returns a mutable reference to the spawned chunk points.
-    pub(crate) fn spawned_chunks_mut(&mut self) -> &mut HashSet<(i32, i32)> {
-        &mut self.spawned
-    }
+    /// Sets the contiguous tiles of a layer that are the same as the tile at a
+    /// point, returning how many were set.
+    ///
+    /// The tiles spread to the neighbours of the topology, see
+    /// [`neighbours`], and only through chunks which exist. Filling an empty
+    /// area fills every empty tile connected to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the layer does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_render::prelude::*;
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::{chunk::RawTile, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// let wall = RawTile { index: 1, color: Color::WHITE, palette: 0 };
+    /// let water = RawTile { index: 2, color: Color::WHITE, palette: 0 };
+    /// tilemap.draw_rect((0, 0), (4, 4), 0, wall, false).unwrap();
+    ///
+    /// assert_eq!(tilemap.flood_fill((2, 2), 0, water).unwrap(), 9);
+    /// assert_eq!(tilemap.get_tile((3, 3), 0), Some(&water));
+    /// assert_eq!(tilemap.get_tile((5, 5), 0), None);
+    /// ```
+    ///
+    /// [`neighbours`]: Tilemap::neighbours
+    pub fn flood_fill<P: Into<Point2>>(
+        &mut self,
+        point: P,
+        z_order: usize,
+        tile: RawTile,
+    ) -> TilemapResult<usize> {
+        if self
+            .layers
+            .get(z_order)
+            .map_or(true, |layer| layer.is_none())
+        {
+            return Err(ErrorKind::LayerDoesNotExist(z_order).into());
+        }
+        let start = self.wrap_point(point);
+        let target = self.raw_tile(start, z_order).copied();
+        if target == Some(tile) {
+            return Ok(0);
+        }
 
-    /// Returns a reference to the layers in the tilemap.
-    pub(crate) fn layers(&self) -> Vec<Option<TilemapLayer>> {
-        self.layers.clone()
+        let mut filled: HashSet<Point2> = HashSet::default();
+        let mut queue = VecDeque::new();
+        queue.push_back(start);
+        while let Some(point) = queue.pop_front() {
+            if filled.contains(&point) {
+                continue;
+            }
+            let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+            if !self.chunks.contains_key(&chunk_point)
+                || self.raw_tile(point, z_order).copied() != target
+            {
+                continue;
+            }
+            filled.insert(point);
+            for neighbour in self.neighbours(point).into_iter() {
+                if !filled.contains(&neighbour) {
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        let count = filled.len();
+        self.draw_points(filled.into_iter().collect(), z_order, tile)?;
+        Ok(count)
+    }
+
+    /// Sets the tiles of a layer at points to a tile.
+    fn draw_points(
+        &mut self,
+        points: Vec<Point2>,
+        z_order: usize,
+        tile: RawTile,
+    ) -> TilemapResult<()> {
+        self.insert_tiles(points.into_iter().map(|point| Tile {
+            point,
+            z_order,
+            sprite_index: tile.index,
+            tint: tile.color,
+            palette: tile.palette,
+        }))
+    }
+
+    /// Returns the raw tile at a point and z order.
+    pub(crate) fn raw_tile(&self, point: Point2, z_order: usize) -> Option<&RawTile> {
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        let tile_point = self.point_to_tile_point(point);
+        let chunk = self.chunks.get(&chunk_point)?;
+        let index = self.chunk_dimensions.encode_point_unchecked(tile_point);
+        chunk.get_tile(z_order, index)
+    }
+
+    /// Returns how far a layer is moved by its parallax and scroll relative to
+    /// the tilemap, in pixels.
+    ///
+    /// This is zero for layers which move with the tilemap. The offset is
+    /// updated every frame from the first camera the tilemap is spawned for.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_math::Vec2;
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let background = TilemapLayer {
+    ///     parallax: Vec2::new(0.5, 0.5),
+    ///     ..Default::default()
+    /// };
+    /// let tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .tile_dimensions(32, 32)
+    ///     .add_layer(background, 0)
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(tilemap.layer_offset(0), Vec2::zero());
+    /// ```
+    pub fn layer_offset(&self, z_order: usize) -> Vec2 {
+        let layer = if let Some(Some(layer)) = self.layers.get(z_order) {
+            layer
+        } else {
+            return Vec2::zero();
+        };
+        let scroll = self
+            .layer_scrolls
+            .get(&z_order)
+            .copied()
+            .unwrap_or_else(Vec2::zero);
+        self.parallax_focus * (Vec2::one() - layer.parallax) + scroll
+    }
+
+    /// Returns `true` if any layer has a parallax, scroll or wraps.
+    pub(crate) fn has_parallax_layers(&self) -> bool {
+        self.layers.iter().flatten().any(|layer| {
+            layer.parallax != Vec2::one() || layer.scroll != Vec2::zero() || layer.wrap
+        })
+    }
+
+    /// Returns `true` if any layer scrolls over time.
+    pub(crate) fn has_scrolling_layers(&self) -> bool {
+        self.layers
+            .iter()
+            .flatten()
+            .any(|layer| layer.scroll != Vec2::zero())
+    }
+
+    /// Moves the parallax layers relative to a camera center in the space of
+    /// the tilemap, and scrolls the layers by the seconds passed.
+    pub(crate) fn update_parallax(&mut self, focus: Vec2, delta_seconds: f32) {
+        self.parallax_focus = focus;
+        for (z_order, layer) in self.layers.iter().enumerate() {
+            if let Some(layer) = layer {
+                if layer.scroll != Vec2::zero() {
+                    let scroll = self.layer_scrolls.entry(z_order).or_insert_with(Vec2::zero);
+                    *scroll += layer.scroll * delta_seconds;
+                }
+            }
+        }
+    }
+
+    /// Returns the distinct offsets of the layers, with whether the layers
+    /// with the offset wrap.
+    pub(crate) fn layer_shifts(&self) -> Vec<(Vec2, bool)> {
+        let mut shifts: Vec<(Vec2, bool)> = Vec::new();
+        for (z_order, layer) in self.layers.iter().enumerate() {
+            if let Some(layer) = layer {
+                let shift = (
+                    self.layer_offset(z_order),
+                    layer.wrap && self.dimensions.is_some(),
+                );
+                if !shifts.contains(&shift) {
+                    shifts.push(shift);
+                }
+            }
+        }
+        shifts
+    }
+
+    /// The distance in pixels that a wrapping layer repeats at along the
+    /// columns and the rows of chunks, if the tilemap has dimensions.
+    pub(crate) fn wrap_period(&self) -> Option<(Vec2, Vec2)> {
+        let dimensions = self.dimensions?;
+        let columns = dimensions.x_max() - dimensions.x_min() + 1;
+        let rows = dimensions.y_max() - dimensions.y_min() + 1;
+        let origin = self.chunk_translation(Point2::new(0, 0));
+        Some((
+            self.chunk_translation(Point2::new(columns, 0)) - origin,
+            self.chunk_translation(Point2::new(0, rows)) - origin,
+        ))
+    }
+
+    /// Wraps a chunk point into the dimensions of the tilemap along the given
+    /// axes, if it has dimensions.
+    pub(crate) fn wrap_chunk_point(&self, point: Point2, wrap_x: bool, wrap_y: bool) -> Point2 {
+        let dimensions = if let Some(dimensions) = self.dimensions {
+            dimensions
+        } else {
+            return point;
+        };
+        let columns = dimensions.x_max() - dimensions.x_min() + 1;
+        let rows = dimensions.y_max() - dimensions.y_min() + 1;
+        let x = if wrap_x {
+            (point.x - dimensions.x_min()).rem_euclid(columns) + dimensions.x_min()
+        } else {
+            point.x
+        };
+        let y = if wrap_y {
+            (point.y - dimensions.y_min()).rem_euclid(rows) + dimensions.y_min()
+        } else {
+            point.y
+        };
+        Point2::new(x, y)
+    }
+
+    /// Wraps a chunk point into the dimensions along the axes the tilemap
+    /// wraps on.
+    pub(crate) fn wrap_chunk_point_of_tilemap(&self, point: Point2) -> Point2 {
+        self.wrap_chunk_point(point, self.wrap_x, self.wrap_y)
+    }
+
+    /// Sets the chunk points outside of the dimensions which show a wrapped
+    /// chunk.
+    pub(crate) fn set_mirror_chunks(&mut self, points: HashSet<Point2>) {
+        self.mirror_chunks = points;
+    }
+
+    /// Returns `true` if a chunk point is of a mirrored chunk past the seam.
+    pub(crate) fn is_mirror_chunk(&self, point: &Point2) -> bool {
+        self.mirror_chunks.contains(point)
+    }
+
+    /// Returns the mirrored chunk points which are not spawned yet.
+    pub(crate) fn mirrors_to_spawn(&self) -> Vec<Point2> {
+        self.mirror_chunks
+            .iter()
+            .filter(|point| !self.mirror_entities.contains_key(point))
+            .copied()
+            .collect()
+    }
+
+    /// Records the layer entities of a spawned mirrored chunk.
+    pub(crate) fn insert_mirror(&mut self, point: Point2, entities: Vec<Entity>) {
+        self.mirror_entities.insert(point, entities);
+    }
+
+    /// Removes and returns the layer entities of mirrored chunks which are no
+    /// longer needed, or which show a chunk that is no longer spawned.
+    pub(crate) fn take_stale_mirrors(&mut self) -> Vec<Entity> {
+        let stale: Vec<Point2> = self
+            .mirror_entities
+            .keys()
+            .filter(|point| {
+                let wrapped = self.wrap_chunk_point_of_tilemap(**point);
+                !self.mirror_chunks.contains(point)
+                    || !self.spawned.contains(&(wrapped.x, wrapped.y))
+            })
+            .copied()
+            .collect();
+        stale
+            .iter()
+            .filter_map(|point| self.mirror_entities.remove(point))
+            .flatten()
+            .collect()
+    }
+
+    /// Returns the translation of a chunk layer relative to the tilemap, moved
+    /// by the parallax and scroll of the layer.
+    ///
+    /// Chunks of wrapping layers are moved by whole periods of the tilemap to
+    /// the repetition that is closest to the camera.
+    pub(crate) fn layer_chunk_translation(&self, point: Point2, z_order: usize) -> Vec2 {
+        let translation = self.chunk_translation(point) + self.layer_offset(z_order);
+        let wraps = matches!(self.layers.get(z_order), Some(Some(layer)) if layer.wrap);
+        match self.wrap_period() {
+            Some((columns, rows)) if wraps => {
+                let to_focus = self.parallax_focus - translation;
+                let column_shift = if columns.x != 0.0 {
+                    (to_focus.x / columns.x).round()
+                } else {
+                    0.0
+                };
+                let row_shift = if rows.y != 0.0 {
+                    (to_focus.y / rows.y).round()
+                } else {
+                    0.0
+                };
+                translation + columns * column_shift + rows * row_shift
+            }
+            _ => translation,
+        }
+    }
+
+    /// Returns the bounds of a chunk relative to the tilemap's transform.
+    ///
+    /// The bounds are calculated for the topology of the tilemap. Hex
+    /// topologies offset their rows or columns, in which case the bounds are
+    /// padded by half a tile to stay conservative.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_math::Vec2;
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .chunk_dimensions(32, 32)
+    ///     .tile_dimensions(32, 32)
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// let bounds = tilemap.chunk_bounds((1, 0));
+    ///
+    /// assert_eq!(bounds.min, Vec2::new(512.0, -512.0));
+    /// assert_eq!(bounds.max, Vec2::new(1536.0, 512.0));
+    /// ```
+    pub fn chunk_bounds<P: Into<Point2>>(&self, point: P) -> WorldRect {
+        let point: Point2 = point.into();
+        let tile_width = self.tile_dimensions.width as f32;
+        let tile_height = self.tile_dimensions.height as f32;
+        let half_width = self.chunk_dimensions.width as f32 / 2.0;
+        let half_height = self.chunk_dimensions.height as f32 / 2.0;
+        use GridTopology::*;
+        let half_extents = match self.topology {
+            Square => Vec2::new(half_width * tile_width, half_height * tile_height),
+            HexX => Vec2::new(
+                half_width * tile_width * 0.75 + tile_width * 0.5,
+                (half_height + half_width * 0.5) * tile_height + tile_height * 0.5,
+            ),
+            HexY => Vec2::new(
+                (half_width + half_height * 0.5) * tile_width + tile_width * 0.5,
+                half_height * tile_height * 0.75 + tile_height * 0.5,
+            ),
+            HexEvenCols | HexOddCols => Vec2::new(
+                half_width * tile_width * 0.75 + tile_width * 0.5,
+                half_height * tile_height + tile_height * 0.5,
+            ),
+            HexEvenRows | HexOddRows => Vec2::new(
+                half_width * tile_width + tile_width * 0.5,
+                half_height * tile_height * 0.75 + tile_height * 0.5,
+            ),
+        };
+        WorldRect::from_center(self.chunk_translation(point), half_extents)
+    }
+
+    /// The topology of the tilemap grid.
+    ///
+    /// Currently there are 7 topologies which are set with [`GridTopology`]. By
+    /// default this is square as it is the most common topology.
+    ///
+    /// Typically, for most situations squares are used for local maps and hex
+    /// is used for war games or world maps. It is easier to define structures
+    /// with walls and floors with square but not impossible with hex.
+    ///
+    /// [`GridTopology`]: crate::render::GridTopology
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .topology(GridTopology::HexX)
+    ///     .tile_dimensions(32, 32)
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(tilemap.topology(), GridTopology::HexX);
+    /// ```
+    pub fn topology(&self) -> GridTopology {
+        self.topology
+    }
+
+    /// Takes the changes to chunks that the tilemap system needs to handle.
+    pub(crate) fn take_chunk_requests(&mut self) -> Vec<ChunkRequest> {
+        mem::take(&mut self.chunk_requests)
+    }
+
+    /// Takes the tiles that changed since this was last called, with the point,
+    /// z order, previous tile and current tile of each.
+    ///
+    /// Tiles which are the same as they were before are left out.
+    pub(crate) fn take_tile_changes(
+        &mut self,
+    ) -> Vec<(Point2, usize, Option<RawTile>, Option<RawTile>)> {
+        let mut changes = Vec::new();
+        for (point, z_order, old) in self.tile_changes.take().into_iter() {
+            let new = self.get_tile(point, z_order).copied();
+            if old != new {
+                changes.push((point, z_order, old, new));
+            }
+        }
+        changes
+    }
+
+    /// Takes the collisions that the collision system needs to handle.
+    #[cfg(feature = "bevy_rapier2d")]
+    pub(crate) fn take_collision_events(&mut self) -> Vec<TilemapCollisionEvent> {
+        mem::take(&mut self.collision_events)
+    }
+
+    /// Returns a copy of the physics scale.
+    #[cfg(feature = "bevy_rapier2d")]
+    pub fn physics_scale(&self) -> f32 {
+        self.physics_scale
+    }
+
+    /// Sets the physics scale.
+    #[cfg(feature = "bevy_rapier2d")]
+    pub fn set_physics_scale(&mut self, scale: f32) {
+        self.physics_scale = scale;
+    }
+
+    /// Returns an option containing a Dimension2.
+    pub(crate) fn auto_spawn(&self) -> Option<Dimension2> {
+        self.auto_spawn
+    }
+
+    /// Returns the extra chunks spawned around the view of each camera.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .tile_dimensions(32, 32)
+    ///     .auto_spawn(2, 2)
+    ///     .auto_spawn_margin(1)
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(tilemap.auto_spawn_margin(), 1);
+    /// ```
+    pub fn auto_spawn_margin(&self) -> u32 {
+        self.auto_spawn_margin
+    }
+
+    /// Sets the extra chunks spawned around the view of each camera.
+    pub fn set_auto_spawn_margin(&mut self, margin: u32) {
+        self.auto_spawn_margin = margin;
+    }
+
+    /// Returns the extra chunks past the margin which stay spawned before
+    /// they are despawned.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// assert_eq!(tilemap.auto_spawn_hysteresis(), 1);
+    /// ```
+    pub fn auto_spawn_hysteresis(&self) -> u32 {
+        self.auto_spawn_hysteresis
+    }
+
+    /// Sets the extra chunks past the margin which stay spawned before they
+    /// are despawned.
+    pub fn set_auto_spawn_hysteresis(&mut self, hysteresis: u32) {
+        self.auto_spawn_hysteresis = hysteresis;
+    }
+
+    /// Returns the maximum amount of chunks spawned per frame, if any.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// assert_eq!(tilemap.spawn_budget(), None);
+    ///
+    /// tilemap.set_spawn_budget(Some(4));
+    /// assert_eq!(tilemap.spawn_budget(), Some(4));
+    /// ```
+    pub fn spawn_budget(&self) -> Option<usize> {
+        self.spawn_budget
+    }
+
+    /// Sets the maximum amount of chunks spawned per frame. `None` removes the
+    /// budget.
+    pub fn set_spawn_budget(&mut self, chunks: Option<usize>) {
+        self.spawn_budget = chunks;
+    }
+
+    /// Returns the maximum amount of chunk layer meshes built per frame, if
+    /// any.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// assert_eq!(tilemap.mesh_budget(), None);
+    ///
+    /// tilemap.set_mesh_budget(Some(8));
+    /// assert_eq!(tilemap.mesh_budget(), Some(8));
+    /// ```
+    pub fn mesh_budget(&self) -> Option<usize> {
+        self.mesh_budget
+    }
+
+    /// Sets the maximum amount of chunk layer meshes built per frame. `None`
+    /// removes the budget.
+    pub fn set_mesh_budget(&mut self, meshes: Option<usize>) {
+        self.mesh_budget = meshes;
+    }
+
+    /// Returns the camera zoom at which chunks are drawn as a single texture,
+    /// if any.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// assert_eq!(tilemap.lod_threshold(), None);
+    ///
+    /// tilemap.set_lod(Some(4.0), 8);
+    /// assert_eq!(tilemap.lod_threshold(), Some(4.0));
+    /// assert_eq!(tilemap.lod_downsample(), 8);
+    /// ```
+    pub fn lod_threshold(&self) -> Option<f32> {
+        self.lod_threshold
+    }
+
+    /// Returns the amount that level of detail textures are downsampled by.
+    pub fn lod_downsample(&self) -> u32 {
+        self.lod_downsample
+    }
+
+    /// Sets the camera zoom at which chunks are drawn as a single texture and
+    /// the amount that the textures are downsampled by. `None` disables
+    /// level of detail.
+    ///
+    /// Textures that were already baked are baked again.
+    pub fn set_lod(&mut self, threshold: Option<f32>, downsample: u32) {
+        let downsample = downsample.max(1);
+        if downsample != self.lod_downsample {
+            for chunk in self.chunks.values_mut() {
+                chunk.mark_lod_dirty();
+            }
+        }
+        self.lod_threshold = threshold;
+        self.lod_downsample = downsample;
+    }
+
+    /// Returns the maximum amount of level of detail textures baked per
+    /// frame, if any.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// assert_eq!(tilemap.lod_bake_budget(), None);
+    ///
+    /// tilemap.set_lod_bake_budget(Some(2));
+    /// assert_eq!(tilemap.lod_bake_budget(), Some(2));
+    /// ```
+    pub fn lod_bake_budget(&self) -> Option<usize> {
+        self.lod_bake_budget
+    }
+
+    /// Sets the maximum amount of level of detail textures baked per frame.
+    /// `None` removes the budget.
+    pub fn set_lod_bake_budget(&mut self, chunks: Option<usize>) {
+        self.lod_bake_budget = chunks;
+    }
+
+    /// Returns `true` if the chunks are currently drawn as level of detail
+    /// textures.
+    pub fn is_lod_active(&self) -> bool {
+        self.lod_active
+    }
+
+    /// Sets if the chunks are currently drawn as level of detail textures.
+    pub(crate) fn set_lod_active(&mut self, active: bool) {
+        self.lod_active = active;
+    }
+
+    /// Returns the settings of the lighting, if the tilemap is lit.
+    pub fn lighting(&self) -> Option<&TilemapLighting> {
+        self.lighting.as_ref()
+    }
+
+    /// Sets the settings of the lighting. `None` removes the lighting and
+    /// fully lights every tile again.
+    pub fn set_lighting(&mut self, lighting: Option<TilemapLighting>) {
+        self.lighting = lighting;
+        self.light_dirty = true;
+    }
+
+    /// Inserts a light at a tile point, returning the light that was there
+    /// before, if any.
+    ///
+    /// The light only spreads once the tilemap has lighting.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::{light::{TileLight, TilemapLighting}, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.set_lighting(Some(TilemapLighting::default()));
+    ///
+    /// assert_eq!(tilemap.insert_light((2, 2), TileLight::default()), None);
+    /// assert_eq!(tilemap.remove_light((2, 2)), Some(TileLight::default()));
+    /// ```
+    pub fn insert_light<P: Into<Point2>>(
+        &mut self,
+        point: P,
+        light: TileLight,
+    ) -> Option<TileLight> {
+        let point: Point2 = point.into();
+        self.mark_light_reach_dirty(point, light);
+        let old = self.lights.insert(point, light);
+        if let Some(old) = old {
+            self.mark_light_reach_dirty(point, old);
+        }
+        old
+    }
+
+    /// Removes the light at a tile point, returning it if there was one.
+    pub fn remove_light<P: Into<Point2>>(&mut self, point: P) -> Option<TileLight> {
+        let point: Point2 = point.into();
+        let light = self.lights.remove(&point);
+        if let Some(light) = light {
+            self.mark_light_reach_dirty(point, light);
+        }
+        light
+    }
+
+    /// Returns the light that falls on a tile point, if the tilemap is lit and
+    /// its chunk exists.
+    ///
+    /// The light is spread once per frame by the tilemap's systems, so this
+    /// does not include changes made in the same frame.
+    pub fn get_light<P: Into<Point2>>(&self, point: P) -> Option<Color> {
+        let point: Point2 = point.into();
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        let tile_point = self.point_to_tile_point(point);
+        let index = self.chunk_dimensions.encode_point_unchecked(tile_point);
+        let [r, g, b, a] = self.chunks.get(&chunk_point)?.get_light(index)?;
+        Some(Color::rgba(r, g, b, a))
+    }
+
+    /// Returns a reference to the lights at tile points.
+    pub(crate) fn lights(&self) -> &HashMap<Point2, TileLight> {
+        &self.lights
+    }
+
+    /// Inserts a named region, returning the region that had the same name
+    /// before, if any.
+    ///
+    /// Every tile point of the region is indexed, so a large rectangle costs
+    /// memory for each of its tiles.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::{prelude::*, region::Region};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// assert!(tilemap.insert_region("spawn", Region::rect((0, 0), (3, 3))).is_none());
+    /// assert!(tilemap.insert_region("spawn", Region::rect((8, 8), (9, 9))).is_some());
+    /// assert!(tilemap.regions_at((0, 0)).is_empty());
+    /// assert_eq!(tilemap.regions_at((9, 9)), ["spawn"]);
+    /// ```
+    pub fn insert_region<S: Into<String>>(&mut self, name: S, region: Region) -> Option<Region> {
+        self.regions.insert(name.into(), region)
+    }
+
+    /// Removes a region by its name, returning it if it existed.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::{prelude::*, region::Region};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_region("room", Region::tiles(vec![(1, 1), (1, 2)]));
+    ///
+    /// assert!(tilemap.remove_region("room").is_some());
+    /// assert!(tilemap.remove_region("room").is_none());
+    /// assert!(tilemap.regions_at((1, 1)).is_empty());
+    /// ```
+    pub fn remove_region(&mut self, name: &str) -> Option<Region> {
+        self.regions.remove(name)
+    }
+
+    /// Returns a region by its name, if it exists.
+    pub fn region(&self, name: &str) -> Option<&Region> {
+        self.regions.get(name)
+    }
+
+    /// Returns an iterator over every region and its name, in no particular
+    /// order.
+    pub fn regions(&self) -> impl Iterator<Item = (&str, &Region)> {
+        self.regions.iter()
+    }
+
+    /// Returns the names of the regions that contain a tile point, in the
+    /// order they were inserted.
+    ///
+    /// The point is wrapped first if the tilemap wraps.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::{prelude::*, region::Region};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_region("hall", Region::rect((0, 0), (9, 2)));
+    /// tilemap.insert_region("trap", Region::tiles(vec![(5, 1)]));
+    ///
+    /// for name in tilemap.regions_at((5, 1)) {
+    ///     let region = tilemap.region(name).unwrap();
+    ///     assert!(region.area.contains((5, 1)));
+    /// }
+    /// assert_eq!(tilemap.regions_at((5, 1)).len(), 2);
+    /// ```
+    pub fn regions_at<P: Into<Point2>>(&self, point: P) -> &[String] {
+        self.regions.at(self.wrap_point(point))
+    }
+
+    /// Returns the translation of the center of a tile relative to the
+    /// tilemap, for every topology.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_math::Vec2;
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// assert_eq!(tilemap.tile_translation((0, 0)), Vec2::new(16.0, 16.0));
+    /// assert_eq!(tilemap.tile_translation((1, -1)), Vec2::new(48.0, -16.0));
+    /// ```
+    pub fn tile_translation<P: Into<Point2>>(&self, point: P) -> Vec2 {
+        let point: Point2 = point.into();
+        let chunk_point = self.unwrapped_chunk_point(point);
+        let width = self.chunk_dimensions.width as i32;
+        let height = self.chunk_dimensions.height as i32;
+        let tile_point = Point2::new(
+            point.x - (width * chunk_point.x) + (width / 2),
+            point.y - (height * chunk_point.y) + (height / 2),
+        );
+        let tile_size = Vec2::new(
+            self.tile_dimensions.width as f32,
+            self.tile_dimensions.height as f32,
+        );
+        self.chunk_translation(chunk_point)
+            + self
+                .topology
+                .tile_corner(tile_point, self.chunk_dimensions, tile_size)
+            + tile_size / 2.0
+    }
+
+    /// Returns the child entities with a [`TilePosition`] on a tile point.
+    ///
+    /// The entities are indexed once per frame by the tilemap's systems, so
+    /// this does not include changes made in the same frame.
+    ///
+    /// [`TilePosition`]: crate::position::TilePosition
+    pub fn entities_at<P: Into<Point2>>(&self, point: P) -> &[Entity] {
+        self.tile_entities.at(self.wrap_point(point))
+    }
+
+    /// Returns the tile point of a child entity with a [`TilePosition`], if it
+    /// has been indexed.
+    ///
+    /// [`TilePosition`]: crate::position::TilePosition
+    pub fn entity_point(&self, entity: Entity) -> Option<Point2> {
+        self.tile_entities.point(entity)
+    }
+
+    /// Returns the child entities with a [`TilePosition`] in a chunk.
+    ///
+    /// [`TilePosition`]: crate::position::TilePosition
+    pub fn entities_in_chunk<P: Into<Point2>>(&self, chunk_point: P) -> &[Entity] {
+        self.tile_entities.in_chunk(chunk_point.into())
+    }
+
+    /// Returns the child entities with a [`TilePosition`] in a rectangle of
+    /// tile points, including both corners.
+    ///
+    /// Only the chunks that overlap the rectangle are searched. Like the other
+    /// spatial queries, this does not reach across the seam of a wrapping
+    /// tilemap.
+    ///
+    /// [`TilePosition`]: crate::position::TilePosition
+    pub fn entities_in_rect<P: Into<Point2>>(&self, corner: P, opposite: P) -> Vec<Entity> {
+        let corner = self.wrap_point(corner);
+        let opposite = self.wrap_point(opposite);
+        let min = Point2::new(corner.x.min(opposite.x), corner.y.min(opposite.y));
+        let max = Point2::new(corner.x.max(opposite.x), corner.y.max(opposite.y));
+        self.entities_in_bounds(min, max, |_| true)
+    }
+
+    /// Returns the child entities with a [`TilePosition`] within a distance of
+    /// a tile point, measured in tiles as a straight line between the tile
+    /// points.
+    ///
+    /// [`TilePosition`]: crate::position::TilePosition
+    pub fn entities_in_radius<P: Into<Point2>>(&self, center: P, radius: f32) -> Vec<Entity> {
+        let center = self.wrap_point(center);
+        let reach = radius.max(0.0).floor() as i32;
+        let min = Point2::new(center.x - reach, center.y - reach);
+        let max = Point2::new(center.x + reach, center.y + reach);
+        let radius_squared = radius * radius;
+        self.entities_in_bounds(min, max, |point| {
+            let x = (point.x - center.x) as f32;
+            let y = (point.y - center.y) as f32;
+            x * x + y * y <= radius_squared
+        })
+    }
+
+    /// Returns the child entities with a [`TilePosition`] within a number of
+    /// steps to neighbouring tiles of a tile point, see [`tile_distance`].
+    ///
+    /// For hex topologies this is the hex range around the tile.
+    ///
+    /// [`TilePosition`]: crate::position::TilePosition
+    /// [`tile_distance`]: Tilemap::tile_distance
+    pub fn entities_in_range<P: Into<Point2>>(&self, center: P, range: u32) -> Vec<Entity> {
+        let center = self.wrap_point(center);
+        let reach = range as i32;
+        let min = Point2::new(center.x - reach, center.y - reach);
+        let max = Point2::new(center.x + reach, center.y + reach);
+        self.entities_in_bounds(min, max, |point| self.tile_distance(center, point) <= range)
+    }
+
+    /// Returns the child entities with a tile position in the chunks that
+    /// overlap a rectangle of tile points, that are inside of it and pass a
+    /// filter.
+    fn entities_in_bounds<F>(&self, min: Point2, max: Point2, filter: F) -> Vec<Entity>
+    where
+        F: Fn(Point2) -> bool,
+    {
+        let min_chunk = self.unwrapped_chunk_point(min);
+        let max_chunk = self.unwrapped_chunk_point(max);
+        let mut entities = Vec::new();
+        for y in min_chunk.y..=max_chunk.y {
+            for x in min_chunk.x..=max_chunk.x {
+                for entity in self.tile_entities.in_chunk(Point2::new(x, y)) {
+                    let point = if let Some(point) = self.tile_entities.point(*entity) {
+                        point
+                    } else {
+                        continue;
+                    };
+                    if point.x >= min.x
+                        && point.x <= max.x
+                        && point.y >= min.y
+                        && point.y <= max.y
+                        && filter(point)
+                    {
+                        entities.push(*entity);
+                    }
+                }
+            }
+        }
+        entities
+    }
+
+    /// Returns the number of steps to neighbouring tiles between two tile
+    /// points, as given by [`neighbours`].
+    ///
+    /// For square tiles this is the Manhattan distance and for hex tiles the
+    /// hex distance. Wrapping is not taken into account.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let square = Tilemap::new(texture_atlas_handle.clone(), 32, 32);
+    /// assert_eq!(square.tile_distance((0, 0), (2, 3)), 5);
+    ///
+    /// let hex = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle.clone())
+    ///     .tile_dimensions(32, 32)
+    ///     .topology(GridTopology::HexY)
+    ///     .finish()
+    ///     .unwrap();
+    /// assert_eq!(hex.tile_distance((0, 0), (2, -2)), 2);
+    /// assert_eq!(hex.tile_distance((0, 0), (2, 2)), 4);
+    ///
+    /// // Odd rows are shifted right, so the row above the tile at (0, 0)
+    /// // starts at (-1, 1).
+    /// let offset_hex = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .tile_dimensions(32, 32)
+    ///     .topology(GridTopology::HexOddRows)
+    ///     .finish()
+    ///     .unwrap();
+    /// assert_eq!(offset_hex.tile_distance((0, 0), (-1, 1)), 1);
+    /// assert_eq!(offset_hex.tile_distance((0, 0), (1, 1)), 2);
+    /// assert_eq!(offset_hex.tile_distance((0, 0), (3, 2)), 4);
+    /// assert_eq!(offset_hex.tile_distance((-1, 1), (2, 3)), 4);
+    /// ```
+    ///
+    /// [`neighbours`]: Tilemap::neighbours
+    pub fn tile_distance<P: Into<Point2>>(&self, from: P, to: P) -> u32 {
+        let from = self.axial_point(from.into());
+        let to = self.axial_point(to.into());
+        let x = to.x - from.x;
+        let y = to.y - from.y;
+        match self.topology {
+            GridTopology::Square => (x.abs() + y.abs()) as u32,
+            _ => ((x.abs() + y.abs() + (x + y).abs()) / 2) as u32,
+        }
+    }
+
+    /// Converts a tile point of an offset hex topology to axial coordinates,
+    /// where the neighbours are the same for every tile.
+    fn axial_point(&self, point: Point2) -> Point2 {
+        let shift = |value: i32, is_even_shifted: bool| {
+            let parity = value.rem_euclid(2);
+            if is_even_shifted {
+                (value + parity) / 2
+            } else {
+                (value - parity) / 2
+            }
+        };
+        use GridTopology::*;
+        match self.topology {
+            Square | HexX | HexY => point,
+            HexEvenRows => Point2::new(point.x - shift(point.y, true), point.y),
+            HexOddRows => Point2::new(point.x - shift(point.y, false), point.y),
+            HexEvenCols => Point2::new(point.x, point.y - shift(point.x, true)),
+            HexOddCols => Point2::new(point.x, point.y - shift(point.x, false)),
+        }
+    }
+
+    /// Indexes an entity at a tile point.
+    pub(crate) fn insert_tile_entity(&mut self, entity: Entity, point: Point2) {
+        let point = self.wrap_point(point);
+        let chunk_point = self.unwrapped_chunk_point(point);
+        self.tile_entities.insert(entity, point, chunk_point);
+    }
+
+    /// Removes an entity from the index of tile points.
+    pub(crate) fn remove_tile_entity(&mut self, entity: Entity) {
+        self.tile_entities.remove(entity);
+    }
+
+    /// Flags the lights to be spread again over every chunk, if the tilemap
+    /// is lit.
+    fn mark_light_dirty(&mut self) {
+        if self.lighting.is_some() {
+            self.light_dirty = true;
+        }
+    }
+
+    /// Flags the lights around a chunk to be spread again, if the tilemap is
+    /// lit.
+    fn mark_light_chunk_dirty(&mut self, chunk_point: Point2) {
+        if self.lighting.is_some() {
+            self.light_dirty_chunks.insert(chunk_point);
+        }
+    }
+
+    /// Flags every chunk that a light reaches to be spread again, if the
+    /// tilemap is lit.
+    fn mark_light_reach_dirty(&mut self, point: Point2, light: TileLight) {
+        let reach = if let Some(lighting) = self.lighting.as_ref() {
+            light_reach(self, lighting, point, &light)
+        } else {
+            return;
+        };
+        self.light_dirty_chunks.extend(reach);
+    }
+
+    /// Returns `true` if the lights need to be spread again over every chunk.
+    pub(crate) fn is_light_dirty(&self) -> bool {
+        self.light_dirty
+    }
+
+    /// Takes the chunks around which the lights need to be spread again.
+    pub(crate) fn take_light_dirty_chunks(&mut self) -> HashSet<Point2> {
+        mem::take(&mut self.light_dirty_chunks)
+    }
+
+    /// Flags the lights as spread.
+    pub(crate) fn clean_light(&mut self) {
+        self.light_dirty = false;
+        self.light_dirty_chunks.clear();
+    }
+
+    /// Returns the amount of chunks that are waiting to be spawned.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_asset::{prelude::*, HandleId};
+    /// use bevy_sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// assert_eq!(tilemap.pending_chunk_spawns(), 0);
+    /// ```
+    pub fn pending_chunk_spawns(&self) -> usize {
+        self.spawn_queue.len()
+    }
+
+    /// Queues a chunk to be spawned, if it is not already spawned or queued.
+    pub(crate) fn queue_chunk_spawn(&mut self, point: Point2) {
+        if !self.spawned.contains(&(point.x, point.y)) && !self.spawn_queue.contains(&point) {
+            self.spawn_queue.push(point);
+        }
+    }
+
+    /// Removes a chunk from the spawn queue.
+    pub(crate) fn dequeue_chunk_spawn(&mut self, point: Point2) {
+        self.spawn_queue.retain(|queued| *queued != point);
+    }
+
+    /// Removes every chunk from the spawn queue which is not in a set, such as
+    /// the chunks that went out of view before they were spawned.
+    pub(crate) fn retain_spawn_queue(&mut self, points: &HashSet<Point2>) {
+        self.spawn_queue.retain(|queued| points.contains(queued));
+    }
+
+    /// Sets the chunks that the spawn queue is prioritised around.
+    pub(crate) fn set_spawn_focus(&mut self, focus: Vec<Point2>) {
+        self.spawn_focus = focus;
+    }
+
+    /// Takes the next chunks to spawn this frame from the queue.
+    ///
+    /// The chunks closest to a focus are taken first and no more are taken
+    /// than allowed by the spawn and mesh budgets. At least one chunk is taken
+    /// if any are queued.
+    pub(crate) fn next_spawn_batch(&mut self) -> Vec<Point2> {
+        if self.spawn_queue.is_empty() {
+            return Vec::new();
+        }
+        let focus = &self.spawn_focus;
+        if !focus.is_empty() {
+            self.spawn_queue.sort_by_key(|point| {
+                focus
+                    .iter()
+                    .map(|f| {
+                        let dx = point.x - f.x;
+                        let dy = point.y - f.y;
+                        dx * dx + dy * dy
+                    })
+                    .min()
+                    .unwrap_or(0)
+            });
+        }
+        let mut batch_size = self.spawn_queue.len();
+        if let Some(budget) = self.spawn_budget {
+            batch_size = batch_size.min(budget.max(1));
+        }
+        if let Some(budget) = self.mesh_budget {
+            let layers = self.layers.iter().filter(|layer| layer.is_some()).count();
+            batch_size = batch_size.min((budget / layers.max(1)).max(1));
+        }
+        self.spawn_queue.drain(..batch_size).collect()
+    }
+
+    /// Returns a copy of the chunk's dimensions.
+    pub(crate) fn chunk_dimensions(&self) -> Dimension2 {
+        self.chunk_dimensions
+    }
+
+    /// Returns a copy of the chunk's tile dimensions.
+    #[cfg(feature = "bevy_rapier2d")]
+    pub(crate) fn tile_dimensions(&self) -> Dimension2 {
+        self.tile_dimensions
+    }
+
+    /// Returns a reference to the hash set of spawned chunks.
+    pub(crate) fn spawned_chunks(&self) -> &HashSet<(i32, i32)> {
+        &self.spawned
+    }
+
+    /// Returns a mutable reference to the spawned chunk points.
+    pub(crate) fn spawned_chunks_mut(&mut self) -> &mut HashSet<(i32, i32)> {
+        &mut self.spawned
+    }
+
+    /// Returns a reference to the layers in the tilemap.
+    pub(crate) fn layers(&self) -> Vec<Option<TilemapLayer>> {
+        self.layers.clone()
+    }
+
+    /// Returns a reference to the inner chunks.
+    pub(crate) fn chunks(&self) -> &HashMap<Point2, Chunk> {
+        &self.chunks
     }
 
     /// Returns a mutable reference to the inner chunks.